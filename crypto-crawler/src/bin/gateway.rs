@@ -0,0 +1,87 @@
+use std::{str::FromStr, sync::mpsc::channel};
+
+use clap::Parser;
+use crypto_crawler::{crawl, Gateway, MarketType, MessageType};
+use tokio::net::TcpListener;
+
+/// Runs crawls and serves messages to local clients.
+#[derive(Parser)]
+#[command(name = "gateway", version)]
+struct Cli {
+    /// Serve newline-delimited JSON over TCP, 127.0.0.1:9000 by default if
+    /// --ws is absent too
+    #[arg(long)]
+    tcp: Option<String>,
+    /// Serve JSON over WebSocket
+    #[arg(long)]
+    ws: Option<String>,
+    /// EXCHANGE/MARKET_TYPE/MSG_TYPE[/SYMBOL,SYMBOL...], e.g.,
+    /// binance/spot/trade or okx/linear_swap/bbo/BTC-USDT-SWAP
+    #[arg(required = true, value_parser = Crawl::from_str)]
+    crawls: Vec<Crawl>,
+}
+
+#[derive(Clone)]
+struct Crawl {
+    exchange: String,
+    market_type: MarketType,
+    msg_type: MessageType,
+    symbols: Option<Vec<String>>,
+}
+
+impl FromStr for Crawl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v: Vec<&str> = s.split('/').collect();
+        if v.len() < 3 || v.len() > 4 {
+            return Err(format!("Invalid crawl {s}"));
+        }
+        let market_type =
+            MarketType::from_str(v[1]).map_err(|_| format!("Unknown market type {}", v[1]))?;
+        let msg_type =
+            MessageType::from_str(v[2]).map_err(|_| format!("Unknown message type {}", v[2]))?;
+        let symbols = v.get(3).map(|symbols| symbols.split(',').map(|x| x.to_string()).collect());
+        Ok(Crawl { exchange: v[0].to_string(), market_type, msg_type, symbols })
+    }
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let tcp_addr = match (cli.tcp, cli.ws.as_ref()) {
+        (None, None) => Some("127.0.0.1:9000".to_string()),
+        (tcp_addr, _) => tcp_addr,
+    };
+
+    let gateway = Gateway::new(65536);
+    if let Some(addr) = tcp_addr {
+        let listener =
+            TcpListener::bind(&addr).await.unwrap_or_else(|err| exit_with_error(&err.to_string()));
+        let gateway = gateway.clone();
+        tokio::task::spawn(async move { gateway.serve_tcp(listener).await });
+    }
+    if let Some(addr) = cli.ws {
+        let listener =
+            TcpListener::bind(&addr).await.unwrap_or_else(|err| exit_with_error(&err.to_string()));
+        let gateway = gateway.clone();
+        tokio::task::spawn(async move { gateway.serve_ws(listener).await });
+    }
+
+    let (tx, rx) = channel();
+    for c in cli.crawls {
+        let tx = tx.clone();
+        tokio::task::spawn(async move {
+            crawl(&c.exchange, c.market_type, c.msg_type, c.symbols.as_deref(), tx).await;
+        });
+    }
+    drop(tx);
+    tokio::task::spawn_blocking(move || gateway.forward(rx)).await.unwrap();
+}
@@ -0,0 +1,42 @@
+use std::{path::PathBuf, sync::mpsc::channel};
+
+use clap::Parser;
+use crypto_crawler::{replay, Pace};
+
+/// Replays files written by FileSink, one JSON message per line.
+#[derive(Parser)]
+#[command(name = "replay", version)]
+struct Cli {
+    /// Keep the original pacing, SPEED times faster, messages are replayed as
+    /// fast as possible if omitted
+    #[arg(long, value_parser = parse_speed)]
+    speed: Option<f64>,
+    /// Files written by FileSink
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 0.0 => Ok(speed),
+        _ => Err("SPEED must be a positive number".to_string()),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let pace = match cli.speed {
+        Some(speed) => Pace::Original { speed },
+        None => Pace::AsFastAsPossible,
+    };
+
+    let (tx, rx) = channel();
+    let handle = std::thread::spawn(move || replay(&cli.files, pace, tx));
+    for msg in rx {
+        println!("{msg}");
+    }
+    if let Err(err) = handle.join().unwrap() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,421 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{RecvError, RecvTimeoutError, SendError, Sender, TryRecvError, TrySendError},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{conflation, msg::Message};
+
+/// What to do when the buffer of a crawl is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Wait until the consumer catches up, which stops the crawler from
+    /// reading the websocket, so that the exchange is throttled by TCP flow
+    /// control instead of messages piling up in memory.
+    Block,
+    /// Drop the oldest message in the buffer to make room for the new one.
+    DropOldest,
+    /// Drop the new message.
+    DropNewest,
+    /// Stop the crawl.
+    Fail,
+}
+
+struct State {
+    queue: VecDeque<Message>,
+    num_senders: usize,
+    receiver_alive: bool,
+    failed: bool,
+}
+
+pub(crate) struct Shared {
+    state: Mutex<State>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+pub(crate) enum Inner {
+    Unbounded(Sender<Message>),
+    // closed once the flag is set, see `MessageSender::cancellable()`
+    Cancellable(Sender<Message>, Arc<AtomicBool>),
+    Bounded(Arc<Shared>),
+    Conflating(Arc<conflation::Shared>),
+}
+
+/// The sending half of a crawl, passed to `crawl_*()` functions.
+///
+/// It can be converted from a `std::sync::mpsc::Sender<Message>`, which is
+/// unbounded, or created by `bounded_channel()` and `conflating_channel()`.
+pub struct MessageSender(pub(crate) Inner);
+
+/// The receiving half of a channel created by `bounded_channel()`.
+pub struct MessageReceiver(Arc<Shared>);
+
+/// Creates a channel which buffers at most `capacity` messages, `policy`
+/// decides what to do when the buffer is full.
+pub fn bounded_channel(
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> (MessageSender, MessageReceiver) {
+    assert!(capacity > 0, "capacity must be positive");
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(capacity),
+            num_senders: 1,
+            receiver_alive: true,
+            failed: false,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity,
+        policy,
+        dropped: AtomicU64::new(0),
+    });
+    (MessageSender(Inner::Bounded(shared.clone())), MessageReceiver(shared))
+}
+
+impl MessageSender {
+    /// Sends a message, returns an error if the receiver has been dropped or
+    /// the crawl has failed due to `OverflowPolicy::Fail`.
+    pub fn send(&self, msg: Message) -> Result<(), SendError<Message>> {
+        self.push(msg, true).map_err(|err| match err {
+            TrySendError::Full(msg) | TrySendError::Disconnected(msg) => SendError(msg),
+        })
+    }
+
+    /// Sends a message without blocking.
+    ///
+    /// Returns `TrySendError::Full` if the buffer is full and `send()` would
+    /// block, i.e., the policy is `OverflowPolicy::Block` or the passthrough
+    /// buffer of a conflating channel is full, other policies never block.
+    pub fn try_send(&self, msg: Message) -> Result<(), TrySendError<Message>> {
+        self.push(msg, false)
+    }
+
+    /// Wraps `tx` into a sender which, together with all its clones, is closed
+    /// once the returned flag is set.
+    pub(crate) fn cancellable(tx: Sender<Message>) -> (MessageSender, Arc<AtomicBool>) {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        (MessageSender(Inner::Cancellable(tx, cancelled.clone())), cancelled)
+    }
+
+    /// Returns true if sending is known to fail, i.e., the receiver has been
+    /// dropped, the crawl has failed or the sender has been cancelled.
+    ///
+    /// Crawls of RESTful APIs check it before each request, so that they stop
+    /// without waiting for the next `send()`.
+    pub(crate) fn is_closed(&self) -> bool {
+        match &self.0 {
+            // std senders only find out on send
+            Inner::Unbounded(_) => false,
+            Inner::Cancellable(_, cancelled) => cancelled.load(Ordering::Acquire),
+            Inner::Bounded(shared) => {
+                let state = shared.state.lock().unwrap();
+                !state.receiver_alive || state.failed
+            }
+            Inner::Conflating(shared) => shared.is_closed(),
+        }
+    }
+
+    fn push(&self, msg: Message, block: bool) -> Result<(), TrySendError<Message>> {
+        let shared = match &self.0 {
+            Inner::Unbounded(tx) => {
+                return tx.send(msg).map_err(|err| TrySendError::Disconnected(err.0));
+            }
+            Inner::Cancellable(_, cancelled) if cancelled.load(Ordering::Acquire) => {
+                return Err(TrySendError::Disconnected(msg));
+            }
+            Inner::Cancellable(tx, _) => {
+                return tx.send(msg).map_err(|err| TrySendError::Disconnected(err.0));
+            }
+            Inner::Bounded(shared) => shared,
+            Inner::Conflating(shared) => return shared.push(msg, block),
+        };
+        let mut state = shared.state.lock().unwrap();
+        loop {
+            if !state.receiver_alive || state.failed {
+                return Err(TrySendError::Disconnected(msg));
+            }
+            if state.queue.len() < shared.capacity {
+                break;
+            }
+            match shared.policy {
+                OverflowPolicy::Block if block => state = shared.not_full.wait(state).unwrap(),
+                OverflowPolicy::Block => return Err(TrySendError::Full(msg)),
+                OverflowPolicy::DropOldest => {
+                    state.queue.pop_front();
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                OverflowPolicy::DropNewest => {
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                OverflowPolicy::Fail => {
+                    error!("The buffer is full, capacity: {}, stopping the crawl", shared.capacity);
+                    state.failed = true;
+                    shared.not_empty.notify_all();
+                    return Err(TrySendError::Disconnected(msg));
+                }
+            }
+        }
+        state.queue.push_back(msg);
+        shared.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl Clone for MessageSender {
+    fn clone(&self) -> Self {
+        match &self.0 {
+            Inner::Unbounded(tx) => MessageSender(Inner::Unbounded(tx.clone())),
+            Inner::Cancellable(tx, cancelled) => {
+                MessageSender(Inner::Cancellable(tx.clone(), cancelled.clone()))
+            }
+            Inner::Bounded(shared) => {
+                shared.state.lock().unwrap().num_senders += 1;
+                MessageSender(Inner::Bounded(shared.clone()))
+            }
+            Inner::Conflating(shared) => {
+                shared.add_sender();
+                MessageSender(Inner::Conflating(shared.clone()))
+            }
+        }
+    }
+}
+
+impl Drop for MessageSender {
+    fn drop(&mut self) {
+        match &self.0 {
+            Inner::Unbounded(_) | Inner::Cancellable(..) => (),
+            Inner::Bounded(shared) => {
+                let mut state = shared.state.lock().unwrap();
+                state.num_senders -= 1;
+                if state.num_senders == 0 {
+                    shared.not_empty.notify_all();
+                }
+            }
+            Inner::Conflating(shared) => shared.remove_sender(),
+        }
+    }
+}
+
+impl From<Sender<Message>> for MessageSender {
+    fn from(tx: Sender<Message>) -> Self {
+        MessageSender(Inner::Unbounded(tx))
+    }
+}
+
+impl MessageReceiver {
+    /// Blocks until a message is available.
+    ///
+    /// Returns an error if the buffer is empty and all senders have been
+    /// dropped or the crawl has failed.
+    pub fn recv(&self) -> Result<Message, RecvError> {
+        let mut state = self.0.state.lock().unwrap();
+        loop {
+            if let Some(msg) = self.pop(&mut state) {
+                return Ok(msg);
+            }
+            if state.num_senders == 0 || state.failed {
+                return Err(RecvError);
+            }
+            state = self.0.not_empty.wait(state).unwrap();
+        }
+    }
+
+    /// Returns a message if one is available, without blocking.
+    pub fn try_recv(&self) -> Result<Message, TryRecvError> {
+        let mut state = self.0.state.lock().unwrap();
+        if let Some(msg) = self.pop(&mut state) {
+            Ok(msg)
+        } else if state.num_senders == 0 || state.failed {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Waits at most `timeout` for a message.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Message, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.0.state.lock().unwrap();
+        loop {
+            if let Some(msg) = self.pop(&mut state) {
+                return Ok(msg);
+            }
+            if state.num_senders == 0 || state.failed {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = self.0.not_empty.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Returns an iterator which blocks waiting for messages.
+    pub fn iter(&self) -> impl Iterator<Item = Message> + '_ {
+        std::iter::from_fn(move || self.recv().ok())
+    }
+
+    /// Number of messages dropped by `OverflowPolicy::DropOldest` or
+    /// `OverflowPolicy::DropNewest`.
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages in the buffer.
+    pub fn len(&self) -> usize {
+        self.0.state.lock().unwrap().queue.len()
+    }
+
+    /// Returns true if the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the crawl has been stopped by `OverflowPolicy::Fail`.
+    pub fn is_failed(&self) -> bool {
+        self.0.state.lock().unwrap().failed
+    }
+
+    fn pop(&self, state: &mut State) -> Option<Message> {
+        let msg = state.queue.pop_front();
+        if msg.is_some() {
+            self.0.not_full.notify_one();
+        }
+        msg
+    }
+}
+
+impl Drop for MessageReceiver {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().receiver_alive = false;
+        self.0.not_full.notify_all();
+    }
+}
+
+impl IntoIterator for MessageReceiver {
+    type Item = Message;
+    type IntoIter = Box<dyn Iterator<Item = Message> + Send>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(std::iter::from_fn(move || self.recv().ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+
+    fn new_msg(i: usize) -> Message {
+        Message::new("binance".to_string(), MarketType::Spot, MessageType::Trade, i.to_string())
+    }
+
+    fn drain(rx: &MessageReceiver) -> Vec<String> {
+        let mut v = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            v.push(msg.json);
+        }
+        v
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (tx, rx) = bounded_channel(2, OverflowPolicy::DropOldest);
+        for i in 0..5 {
+            tx.send(new_msg(i)).unwrap();
+        }
+        assert_eq!(vec!["3", "4"], drain(&rx));
+        assert_eq!(3, rx.dropped());
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let (tx, rx) = bounded_channel(2, OverflowPolicy::DropNewest);
+        for i in 0..5 {
+            tx.send(new_msg(i)).unwrap();
+        }
+        assert_eq!(vec!["0", "1"], drain(&rx));
+        assert_eq!(3, rx.dropped());
+    }
+
+    #[test]
+    fn test_fail() {
+        let (tx, rx) = bounded_channel(2, OverflowPolicy::Fail);
+        tx.send(new_msg(0)).unwrap();
+        tx.send(new_msg(1)).unwrap();
+        assert!(tx.send(new_msg(2)).is_err());
+        assert!(rx.is_failed());
+        // buffered messages are still delivered
+        assert_eq!(vec!["0", "1"], drain(&rx));
+        assert!(rx.recv().is_err());
+        assert!(tx.clone().send(new_msg(3)).is_err());
+    }
+
+    #[test]
+    fn test_block() {
+        let (tx, rx) = bounded_channel(1, OverflowPolicy::Block);
+        let handle = std::thread::spawn(move || {
+            for i in 0..3 {
+                tx.send(new_msg(i)).unwrap();
+            }
+        });
+        let messages: Vec<String> = rx.into_iter().map(|msg| msg.json).collect();
+        handle.join().unwrap();
+        assert_eq!(vec!["0", "1", "2"], messages);
+    }
+
+    #[test]
+    fn test_try_send() {
+        let (tx, rx) = bounded_channel(1, OverflowPolicy::Block);
+        tx.try_send(new_msg(0)).unwrap();
+        assert!(matches!(tx.try_send(new_msg(1)), Err(TrySendError::Full(_))));
+        assert_eq!(vec!["0"], drain(&rx));
+        tx.try_send(new_msg(2)).unwrap();
+        drop(rx);
+        assert!(matches!(tx.try_send(new_msg(3)), Err(TrySendError::Disconnected(_))));
+    }
+
+    #[test]
+    fn test_cancellable() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (tx, cancelled) = MessageSender::cancellable(tx);
+        let tx2 = tx.clone();
+        tx.send(new_msg(0)).unwrap();
+        assert!(!tx2.is_closed());
+        cancelled.store(true, Ordering::Release);
+        assert!(tx2.is_closed());
+        assert!(tx2.send(new_msg(1)).is_err());
+        assert_eq!(vec!["0"], rx.try_iter().map(|msg| msg.json).collect::<Vec<_>>());
+
+        let (tx, rx) = bounded_channel(1, OverflowPolicy::Block);
+        assert!(!tx.is_closed());
+        drop(rx);
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn test_receiver_dropped() {
+        let (tx, rx) = bounded_channel(1, OverflowPolicy::Block);
+        tx.send(new_msg(0)).unwrap();
+        let handle = std::thread::spawn(move || tx.send(new_msg(1)).is_err());
+        std::thread::sleep(Duration::from_millis(50));
+        drop(rx);
+        assert!(handle.join().unwrap());
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{RecvError, TryRecvError, TrySendError},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+
+use crate::{
+    buffer::{Inner, MessageSender},
+    msg::Message,
+};
+
+// exchange, market_type, msg_type, symbol
+type Key = (String, MarketType, MessageType, String);
+
+enum Slot {
+    Conflated(Key),       // the message is in `latest`
+    Passthrough(Message), // a message which can NOT be conflated
+}
+
+struct State {
+    latest: HashMap<Key, Message>,
+    // Pending messages in arrival order, a newer message of the same key takes
+    // over the slot of the older one
+    queue: VecDeque<Slot>,
+    num_passthrough: usize,
+    num_senders: usize,
+    receiver_alive: bool,
+}
+
+pub(crate) struct Shared {
+    state: Mutex<State>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize, // max number of pending passthrough messages
+    conflated: AtomicU64,
+}
+
+/// The receiving half of a channel created by `conflating_channel()`.
+///
+/// Only the newest BBO, ticker and level2 top-K message per symbol is kept,
+/// older ones are superseded. Other messages, as well as messages containing
+/// multiple symbols, are delivered as is. Messages are delivered in arrival
+/// order, a superseding message takes the place of the one it replaces.
+pub struct ConflatingReceiver(Arc<Shared>);
+
+/// Creates a channel which keeps only the newest message per (symbol,
+/// msg_type) for `crawl_bbo()`, `crawl_ticker()` and `crawl_l2_topk()`.
+///
+/// At most `capacity` messages which can NOT be conflated are buffered,
+/// senders block when the limit is reached, see `OverflowPolicy::Block`.
+pub fn conflating_channel(capacity: usize) -> (MessageSender, ConflatingReceiver) {
+    assert!(capacity > 0, "capacity must be positive");
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            latest: HashMap::new(),
+            queue: VecDeque::new(),
+            num_passthrough: 0,
+            num_senders: 1,
+            receiver_alive: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity,
+        conflated: AtomicU64::new(0),
+    });
+    (MessageSender(Inner::Conflating(shared.clone())), ConflatingReceiver(shared))
+}
+
+fn get_key(msg: &Message) -> Option<Key> {
+    if !matches!(msg.msg_type, MessageType::BBO | MessageType::Ticker | MessageType::L2TopK) {
+        return None;
+    }
+    // None for messages of multiple symbols
+    let symbol = msg.extract_symbol()?;
+    Some((msg.exchange.clone(), msg.market_type, msg.msg_type, symbol))
+}
+
+impl Shared {
+    // Only messages which can NOT be conflated wait for room, if `block` is
+    // false they are returned as `TrySendError::Full` instead.
+    pub(crate) fn push(&self, msg: Message, block: bool) -> Result<(), TrySendError<Message>> {
+        let key = get_key(&msg);
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if !state.receiver_alive {
+                return Err(TrySendError::Disconnected(msg));
+            }
+            if key.is_some() || state.num_passthrough < self.capacity {
+                break;
+            }
+            if !block {
+                return Err(TrySendError::Full(msg));
+            }
+            state = self.not_full.wait(state).unwrap();
+        }
+        match key {
+            Some(key) => {
+                if state.latest.contains_key(&key) {
+                    self.conflated.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    state.queue.push_back(Slot::Conflated(key.clone()));
+                }
+                state.latest.insert(key, msg);
+            }
+            None => {
+                state.queue.push_back(Slot::Passthrough(msg));
+                state.num_passthrough += 1;
+            }
+        }
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        !self.state.lock().unwrap().receiver_alive
+    }
+
+    pub(crate) fn add_sender(&self) {
+        self.state.lock().unwrap().num_senders += 1;
+    }
+
+    pub(crate) fn remove_sender(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.num_senders -= 1;
+        if state.num_senders == 0 {
+            self.not_empty.notify_all();
+        }
+    }
+}
+
+impl State {
+    fn pop(&mut self, not_full: &Condvar) -> Option<Message> {
+        match self.queue.pop_front()? {
+            Slot::Conflated(key) => self.latest.remove(&key),
+            Slot::Passthrough(msg) => {
+                self.num_passthrough -= 1;
+                not_full.notify_one();
+                Some(msg)
+            }
+        }
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.num_senders == 0
+    }
+}
+
+impl ConflatingReceiver {
+    /// Blocks until a message is available.
+    ///
+    /// Returns an error if there is no message and all senders have been
+    /// dropped.
+    pub fn recv(&self) -> Result<Message, RecvError> {
+        let mut state = self.0.state.lock().unwrap();
+        loop {
+            if let Some(msg) = state.pop(&self.0.not_full) {
+                return Ok(msg);
+            }
+            if state.is_disconnected() {
+                return Err(RecvError);
+            }
+            state = self.0.not_empty.wait(state).unwrap();
+        }
+    }
+
+    /// Returns a message if one is available, without blocking.
+    pub fn try_recv(&self) -> Result<Message, TryRecvError> {
+        let mut state = self.0.state.lock().unwrap();
+        if let Some(msg) = state.pop(&self.0.not_full) {
+            Ok(msg)
+        } else if state.is_disconnected() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Takes all pending messages at once, i.e., the current state of all
+    /// symbols which have been updated since the last call.
+    pub fn drain(&self) -> Vec<Message> {
+        let mut state = self.0.state.lock().unwrap();
+        let mut messages = Vec::with_capacity(state.queue.len());
+        while let Some(msg) = state.pop(&self.0.not_full) {
+            messages.push(msg);
+        }
+        messages
+    }
+
+    /// Returns an iterator which yields pending messages every `interval`.
+    ///
+    /// Empty batches are skipped, the iterator ends after all senders have
+    /// been dropped.
+    pub fn iter_interval(&self, interval: Duration) -> impl Iterator<Item = Vec<Message>> + '_ {
+        std::iter::from_fn(move || loop {
+            std::thread::sleep(interval);
+            let disconnected = self.0.state.lock().unwrap().is_disconnected();
+            let messages = self.drain();
+            if !messages.is_empty() {
+                return Some(messages);
+            } else if disconnected {
+                return None;
+            }
+        })
+    }
+
+    /// Number of messages superseded by newer ones.
+    pub fn conflated(&self) -> u64 {
+        self.0.conflated.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ConflatingReceiver {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().receiver_alive = false;
+        self.0.not_full.notify_all();
+    }
+}
+
+impl IntoIterator for ConflatingReceiver {
+    type Item = Message;
+    type IntoIter = Box<dyn Iterator<Item = Message> + Send>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(std::iter::from_fn(move || self.recv().ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_msg(msg_type: MessageType, symbol: &str, json: &str) -> Message {
+        Message::new_with_symbol(
+            "binance".to_string(),
+            MarketType::Spot,
+            msg_type,
+            symbol.to_string(),
+            json.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_conflate() {
+        let (tx, rx) = conflating_channel(16);
+        tx.send(new_msg(MessageType::BBO, "BTCUSDT", "1")).unwrap();
+        tx.send(new_msg(MessageType::BBO, "ETHUSDT", "2")).unwrap();
+        tx.send(new_msg(MessageType::BBO, "BTCUSDT", "3")).unwrap();
+        tx.send(new_msg(MessageType::Ticker, "BTCUSDT", "4")).unwrap();
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "5")).unwrap();
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "6")).unwrap();
+
+        let messages: Vec<String> = rx.drain().into_iter().map(|msg| msg.json).collect();
+        assert_eq!(vec!["3", "2", "4", "5", "6"], messages);
+        assert_eq!(1, rx.conflated());
+        assert!(rx.drain().is_empty());
+    }
+
+    #[test]
+    fn test_arrival_order() {
+        let (tx, rx) = conflating_channel(16);
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "1")).unwrap();
+        tx.send(new_msg(MessageType::BBO, "BTCUSDT", "2")).unwrap();
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "3")).unwrap();
+        tx.send(new_msg(MessageType::BBO, "BTCUSDT", "4")).unwrap();
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "5")).unwrap();
+
+        let messages: Vec<String> = rx.drain().into_iter().map(|msg| msg.json).collect();
+        assert_eq!(vec!["1", "4", "3", "5"], messages);
+    }
+
+    #[test]
+    fn test_bounded_passthrough() {
+        let (tx, rx) = conflating_channel(1);
+        tx.send(new_msg(MessageType::Trade, "BTCUSDT", "1")).unwrap();
+        // conflated messages never block
+        tx.send(new_msg(MessageType::BBO, "BTCUSDT", "2")).unwrap();
+        let handle = std::thread::spawn(move || {
+            tx.send(new_msg(MessageType::Trade, "BTCUSDT", "3")).unwrap();
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!handle.is_finished());
+
+        assert_eq!("1", rx.recv().unwrap().json);
+        handle.join().unwrap();
+        let messages: Vec<String> = rx.drain().into_iter().map(|msg| msg.json).collect();
+        assert_eq!(vec!["2", "3"], messages);
+    }
+
+    #[test]
+    fn test_extract_symbol() {
+        let (tx, rx) = conflating_channel(16);
+        for (i, price) in ["19000.1", "19000.2"].iter().enumerate() {
+            let json = format!(
+                r#"{{"stream":"btcusdt@bookTicker","data":{{"u":{i},"s":"BTCUSDT","b":"{price}","B":"1","a":"19000.3","A":"1"}}}}"#
+            );
+            let msg = Message::new("binance".to_string(), MarketType::Spot, MessageType::BBO, json);
+            tx.send(msg).unwrap();
+        }
+        drop(tx);
+
+        let messages: Vec<Message> = rx.into_iter().collect();
+        assert_eq!(1, messages.len());
+        assert!(messages[0].json.contains("19000.2"));
+    }
+}
@@ -13,5 +13,301 @@ pub(super) mod zbg;
 pub use utils::fetch_symbols_retry;
 pub(super) use utils::{
     crawl_candlestick_ext, crawl_event, crawl_open_interest, crawl_snapshot,
-    create_ws_client_symbol,
+    create_ws_client_symbol, plan_candlestick_ext, plan_event,
 };
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+
+use crate::{
+    buffer::MessageSender,
+    error::{Error, Result},
+    get_hot_spot_symbols,
+    utils::{cmc_rank::sort_by_cmc_rank, REST_LOCKS},
+};
+
+/// How a websocket crawl is carried out, decided by `route()`.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Route {
+    /// Through `crawl_event()` or `crawl_candlestick_ext()` with the given
+    /// symbols, None or empty means all symbols
+    Generic(Option<Vec<String>>),
+    /// By a dedicated crawler of the exchange, e.g., binance all-market streams
+    Dedicated,
+}
+
+/// Decides how to crawl `msg_type` of `exchange` through websocket.
+///
+/// Both crawling and planning go through this function, so that a plan always
+/// matches the crawl.
+pub(super) fn route(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+    symbols: Option<&[String]>,
+) -> Result<Route> {
+    check_channel(exchange, msg_type)?;
+
+    let all_symbols = symbols.is_none_or(|symbols| symbols.is_empty());
+    let dedicated = match (exchange, msg_type) {
+        ("bitmex", _) => market_type == MarketType::Unknown,
+        ("binance", MessageType::Trade) => all_symbols && market_type == MarketType::EuropeanOption,
+        ("binance", MessageType::BBO) if all_symbols && market_type == MarketType::Spot => {
+            // spot `!bookTicker` has been removed since December 7, 2022
+            let mut hot_spot_symbols = tokio::task::block_in_place(move || {
+                let spot_symbols = fetch_symbols_retry(exchange, market_type);
+                get_hot_spot_symbols(exchange, &spot_symbols)
+            });
+            sort_by_cmc_rank(exchange, &mut hot_spot_symbols);
+            return Ok(Route::Generic(Some(hot_spot_symbols)));
+        }
+        ("binance", MessageType::BBO)
+        | ("binance", MessageType::Ticker)
+        | ("deribit", MessageType::Trade)
+        | ("zbg", MessageType::Ticker) => all_symbols,
+        ("huobi", MessageType::L2Event) => market_type == MarketType::Spot,
+        ("kucoin", MessageType::BBO) => all_symbols && market_type == MarketType::Spot,
+        ("zb", MessageType::Ticker) => all_symbols && market_type == MarketType::LinearSwap,
+        _ => false,
+    };
+    if dedicated {
+        Ok(Route::Dedicated)
+    } else {
+        Ok(Route::Generic(symbols.map(|symbols| symbols.to_vec())))
+    }
+}
+
+// Checks that `exchange` has the websocket channel of `msg_type`.
+fn check_channel(exchange: &str, msg_type: MessageType) -> Result<()> {
+    let (exchanges, channel): (&[&str], &str) = match msg_type {
+        MessageType::Trade => (
+            &[
+                "binance",
+                "bitfinex",
+                "bitget",
+                "bithumb",
+                "bitmex",
+                "bitstamp",
+                "bitz",
+                "bybit",
+                "coinbase_pro",
+                "deribit",
+                "dydx",
+                "ftx",
+                "gate",
+                "huobi",
+                "kraken",
+                "kucoin",
+                "mexc",
+                "okx",
+                "zb",
+                "zbg",
+            ],
+            "the trade websocket channel",
+        ),
+        MessageType::L2Event => (
+            &[
+                "binance",
+                "bitfinex",
+                "bitget",
+                "bithumb",
+                "bitmex",
+                "bitstamp",
+                "bitz",
+                "bybit",
+                "coinbase_pro",
+                "deribit",
+                "dydx",
+                "ftx",
+                "gate",
+                "huobi",
+                "kraken",
+                "kucoin",
+                "mexc",
+                "okx",
+                "zb",
+                "zbg",
+            ],
+            "the incremental level2 websocket channel",
+        ),
+        MessageType::L3Event => (
+            &["bitfinex", "bitstamp", "coinbase_pro", "kucoin"],
+            "the incremental level3 websocket channel",
+        ),
+        MessageType::BBO => (
+            &["binance", "bitmex", "deribit", "ftx", "gate", "huobi", "kraken", "kucoin", "okx"],
+            "BBO websocket channel",
+        ),
+        MessageType::L2TopK => (
+            &[
+                "binance", "bitget", "bitmex", "bitstamp", "bybit", "deribit", "gate", "huobi",
+                "kucoin", "mexc", "okx", "zb",
+            ],
+            "the level2 top-k snapshot websocket channel",
+        ),
+        MessageType::Ticker => (
+            &[
+                "binance",
+                "bitfinex",
+                "bitget",
+                "bithumb",
+                "bitz",
+                "bybit",
+                "coinbase_pro",
+                "deribit",
+                "gate",
+                "huobi",
+                "kraken",
+                "kucoin",
+                "mexc",
+                "okx",
+                "zb",
+                "zbg",
+            ],
+            "the ticker websocket channel",
+        ),
+        MessageType::Candlestick => (
+            &[
+                "binance", "bitfinex", "bitget", "bitmex", "bitz", "bybit", "deribit", "gate",
+                "huobi", "kraken", "kucoin", "mexc", "okx", "zb", "zbg",
+            ],
+            "the candlestick websocket channel",
+        ),
+        _ => return Err(Error(format!("{msg_type} is NOT crawled through websocket"))),
+    };
+    if !exchanges.contains(&exchange) {
+        return Err(Error(format!("{exchange} does NOT have {channel}")));
+    }
+    Ok(())
+}
+
+/// Checks that `crate::crawl()` supports `msg_type` of `exchange`, without
+/// sending any request.
+pub(crate) fn check_msg_type(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+) -> Result<()> {
+    match msg_type {
+        MessageType::L2Snapshot | MessageType::L3Snapshot | MessageType::OpenInterest => {
+            if !REST_LOCKS.get(exchange).is_some_and(|locks| locks.contains_key(&market_type)) {
+                return Err(Error(format!(
+                    "{exchange} does NOT have the {market_type} market type"
+                )));
+            }
+            // see `crawl_open_interest()`
+            let open_interest_exchanges = [
+                "binance", "bitget", "bitz", "bybit", "deribit", "dydx", "ftx", "gate", "huobi",
+                "kucoin", "okx", "zbg",
+            ];
+            if msg_type == MessageType::OpenInterest && !open_interest_exchanges.contains(&exchange)
+            {
+                return Err(Error(format!("{exchange} does NOT have open interest RESTful API")));
+            }
+            Ok(())
+        }
+        MessageType::FundingRate => {
+            if ["binance", "bitmex", "huobi", "okx"].contains(&exchange) {
+                Ok(())
+            } else {
+                Err(Error(format!("{exchange} does NOT have perpetual swap market")))
+            }
+        }
+        _ => check_channel(exchange, msg_type),
+    }
+}
+
+/// Crawls `msg_type` through websocket, see `route()`.
+pub(super) async fn crawl_websocket(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+    symbols: Option<&[String]>,
+    tx: MessageSender,
+) {
+    match route(exchange, market_type, msg_type, symbols) {
+        Ok(Route::Generic(symbols)) => {
+            crawl_event(exchange, msg_type, market_type, symbols.as_deref(), tx).await
+        }
+        Ok(Route::Dedicated) => match (exchange, msg_type) {
+            ("binance", MessageType::Trade) => binance::crawl_trade(market_type, tx).await,
+            ("binance", MessageType::BBO) => binance::crawl_bbo(market_type, tx).await,
+            ("binance", MessageType::Ticker) => binance::crawl_ticker(market_type, tx).await,
+            ("bitmex", _) => bitmex::crawl_all(msg_type, tx).await,
+            ("deribit", MessageType::Trade) => deribit::crawl_trade(market_type, tx).await,
+            ("huobi", MessageType::L2Event) => {
+                huobi::crawl_l2_event(market_type, symbols, tx).await
+            }
+            ("kucoin", MessageType::BBO) => kucoin::crawl_bbo(market_type, tx).await,
+            ("zb", MessageType::Ticker) => zb::crawl_ticker(market_type, tx).await,
+            ("zbg", MessageType::Ticker) => zbg::crawl_ticker(market_type, tx).await,
+            _ => unreachable!("{exchange} {msg_type} does NOT have a dedicated crawler"),
+        },
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Crawls candlesticks through websocket, see `route()`.
+pub(super) async fn crawl_candlestick(
+    exchange: &str,
+    market_type: MarketType,
+    symbol_interval_list: Option<&[(String, usize)]>,
+    tx: MessageSender,
+) {
+    match route(exchange, market_type, MessageType::Candlestick, None) {
+        Ok(Route::Generic(_)) => {
+            crawl_candlestick_ext(exchange, market_type, symbol_interval_list, tx).await
+        }
+        Ok(Route::Dedicated) => bitmex::crawl_candlestick(tx).await,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_msg_type, route, Route};
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+
+    #[test]
+    fn test_route() {
+        let symbols = vec!["BTCUSDT".to_string()];
+        assert_eq!(
+            Route::Dedicated,
+            route("binance", MarketType::LinearSwap, MessageType::BBO, None).unwrap()
+        );
+        assert_eq!(
+            Route::Generic(Some(symbols.clone())),
+            route("binance", MarketType::LinearSwap, MessageType::BBO, Some(&symbols)).unwrap()
+        );
+        assert_eq!(
+            Route::Generic(None),
+            route("binance", MarketType::Spot, MessageType::Trade, None).unwrap()
+        );
+        assert_eq!(
+            Route::Dedicated,
+            route("huobi", MarketType::Spot, MessageType::L2Event, Some(&symbols)).unwrap()
+        );
+        assert_eq!(
+            Route::Dedicated,
+            route("bitmex", MarketType::Unknown, MessageType::Candlestick, None).unwrap()
+        );
+        assert_eq!(
+            "bitstamp does NOT have BBO websocket channel",
+            route("bitstamp", MarketType::Spot, MessageType::BBO, None).unwrap_err().0
+        );
+        assert!(route("binance", MarketType::Spot, MessageType::L2Snapshot, None).is_err());
+    }
+
+    #[test]
+    fn test_check_msg_type() {
+        assert!(check_msg_type("binance", MarketType::Spot, MessageType::BBO).is_ok());
+        assert!(check_msg_type("binance", MarketType::Spot, MessageType::L2Snapshot).is_ok());
+        assert!(check_msg_type("okx", MarketType::LinearSwap, MessageType::FundingRate).is_ok());
+        assert!(check_msg_type("bitstamp", MarketType::Spot, MessageType::BBO).is_err());
+        assert!(check_msg_type("binance", MarketType::Unknown, MessageType::L2Snapshot).is_err());
+        assert!(check_msg_type("bitstamp", MarketType::Spot, MessageType::OpenInterest).is_err());
+        assert!(check_msg_type("kraken", MarketType::Spot, MessageType::FundingRate).is_err());
+        assert!(check_msg_type("binance", MarketType::Spot, MessageType::Other).is_err());
+    }
+}
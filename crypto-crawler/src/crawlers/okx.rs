@@ -1,9 +1,11 @@
 use super::utils::fetch_symbols_retry;
-use crate::{crawlers::utils::create_conversion_thread, msg::Message};
+use crate::{
+    buffer::MessageSender,
+    crawlers::utils::{run_ws_client, unused_channel},
+};
 use crypto_market_type::MarketType;
 use crypto_msg_type::MessageType;
 use crypto_ws_client::*;
-use std::sync::mpsc::Sender;
 
 const EXCHANGE_NAME: &str = "okx";
 
@@ -11,15 +13,8 @@ const EXCHANGE_NAME: &str = "okx";
 pub(crate) async fn crawl_funding_rate(
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: MessageSender,
 ) {
-    let tx = create_conversion_thread(
-        EXCHANGE_NAME.to_string(),
-        MessageType::FundingRate,
-        market_type,
-        tx,
-    );
-
     let symbols: Vec<String> = if symbols.is_none() || symbols.unwrap().is_empty() {
         tokio::task::block_in_place(move || fetch_symbols_retry(EXCHANGE_NAME, market_type))
     } else {
@@ -30,10 +25,10 @@ pub(crate) async fn crawl_funding_rate(
 
     match market_type {
         MarketType::InverseSwap | MarketType::LinearSwap => {
-            let ws_client = OkxWSClient::new(tx, None).await;
+            let ws_client = OkxWSClient::new(unused_channel(), None).await;
             ws_client.subscribe(&topics).await;
-            ws_client.run().await;
-            ws_client.close().await;
+            run_ws_client(&ws_client, EXCHANGE_NAME, market_type, MessageType::FundingRate, tx)
+                .await;
         }
         _ => panic!("OKX {market_type} does NOT have funding rates"),
     }
@@ -44,15 +39,8 @@ pub(crate) async fn crawl_funding_rate(
 pub(crate) async fn crawl_open_interest(
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: MessageSender,
 ) {
-    let tx = create_conversion_thread(
-        EXCHANGE_NAME.to_string(),
-        MessageType::OpenInterest,
-        market_type,
-        tx,
-    );
-
     let symbols = if let Some(symbols) = symbols {
         if symbols.is_empty() {
             tokio::task::block_in_place(move || fetch_symbols_retry(EXCHANGE_NAME, market_type))
@@ -66,10 +54,9 @@ pub(crate) async fn crawl_open_interest(
         symbols.into_iter().map(|symbol| ("open-interest".to_string(), symbol)).collect();
 
     if market_type != MarketType::Spot {
-        let ws_client = OkxWSClient::new(tx, None).await;
+        let ws_client = OkxWSClient::new(unused_channel(), None).await;
         ws_client.subscribe(&topics).await;
-        ws_client.run().await;
-        ws_client.close().await;
+        run_ws_client(&ws_client, EXCHANGE_NAME, market_type, MessageType::OpenInterest, tx).await;
     } else {
         panic!("spot does NOT have open interest");
     }
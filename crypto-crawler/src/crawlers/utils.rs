@@ -1,5 +1,9 @@
 use std::{
-    sync::{mpsc::Sender, Arc},
+    collections::HashSet,
+    sync::{
+        mpsc::{Sender, TrySendError},
+        Arc,
+    },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -8,9 +12,19 @@ use crypto_market_type::{get_market_types, MarketType};
 use crypto_markets::fetch_symbols;
 use crypto_rest_client::{fetch_l2_snapshot, fetch_l3_snapshot, fetch_open_interest};
 use crypto_ws_client::*;
+use futures_util::StreamExt;
 use log::*;
+use tokio::task::JoinSet;
 
-use crate::{get_hot_spot_symbols, utils::cmc_rank::sort_by_cmc_rank, Message, MessageType};
+use crate::{
+    buffer::MessageSender,
+    event::{emit_symbol_event, SymbolEvent},
+    get_hot_spot_symbols,
+    plan::{ConnectionPlan, CrawlPlan},
+    supervisor::AbortOnDrop,
+    utils::cmc_rank::sort_by_cmc_rank,
+    Message, MessageType,
+};
 
 pub fn fetch_symbols_retry(exchange: &str, market_type: MarketType) -> Vec<String> {
     let retry_count = std::env::var("REST_RETRY_COUNT")
@@ -54,25 +68,82 @@ pub fn fetch_symbols_retry(exchange: &str, market_type: MarketType) -> Vec<Strin
 }
 
 pub(super) fn check_args(exchange: &str, market_type: MarketType, symbols: &[String]) {
+    if let Err(err) = validate_args(exchange, market_type, symbols) {
+        panic!("{}", err);
+    }
+}
+
+// Checks that `exchange` has `market_type` and `symbols` are all valid,
+// returns all valid symbols.
+fn validate_args(
+    exchange: &str,
+    market_type: MarketType,
+    symbols: &[String],
+) -> crate::error::Result<Vec<String>> {
     let market_types = get_market_types(exchange);
     if !market_types.contains(&market_type) {
-        panic!("{exchange} does NOT have the {market_type} market type");
+        return Err(crate::Error(format!(
+            "{exchange} does NOT have the {market_type} market type"
+        )));
     }
 
     let valid_symbols = fetch_symbols_retry(exchange, market_type);
     let invalid_symbols: Vec<String> =
         symbols.iter().filter(|symbol| !valid_symbols.contains(symbol)).cloned().collect();
     if !invalid_symbols.is_empty() {
-        panic!(
+        return Err(crate::Error(format!(
             "Invalid symbols: {}, {} {} available trading symbols are {}",
             invalid_symbols.join(","),
             exchange,
             market_type,
             valid_symbols.join(",")
-        );
+        )));
+    }
+    Ok(valid_symbols)
+}
+
+// Resolves the symbols to crawl, shared by crawling and planning.
+//
+// Returns the symbols and whether automatic symbol discovery is enabled, the
+// symbols are empty if `fetch_symbols_retry()` failed.
+fn resolve_symbols(
+    exchange: &str,
+    market_type: MarketType,
+    symbols: Option<&[String]>,
+) -> crate::error::Result<(Vec<String>, bool)> {
+    let valid_symbols = validate_args(exchange, market_type, symbols.unwrap_or_default())?;
+    match symbols {
+        Some(list) if !list.is_empty() => Ok((list.to_vec(), false)),
+        _ => Ok((valid_symbols, true)),
+    }
+}
+
+// Same as `resolve_symbols()`, with candlestick intervals.
+fn resolve_symbol_interval_list(
+    exchange: &str,
+    market_type: MarketType,
+    symbol_interval_list: Option<&[(String, usize)]>,
+) -> crate::error::Result<(Vec<(String, usize)>, bool)> {
+    let symbols: Option<Vec<String>> =
+        symbol_interval_list.map(|list| list.iter().map(|t| t.0.clone()).collect());
+    let (symbols, symbol_discovery) = resolve_symbols(exchange, market_type, symbols.as_deref())?;
+    if symbol_discovery {
+        let intervals = get_candlestick_intervals(exchange, market_type);
+        Ok((to_symbol_interval_list(&symbols, &intervals), true))
+    } else {
+        Ok((symbol_interval_list.unwrap().to_vec(), false))
     }
 }
 
+// Splits topics into chunks, each chunk has its own connection.
+fn split_into_connections<'a, T>(
+    exchange: &str,
+    market_type: MarketType,
+    topics: &'a [T],
+) -> std::slice::Chunks<'a, T> {
+    topics.chunks(get_num_subscriptions_per_connection(exchange, market_type))
+}
+
 fn get_cooldown_time_per_request(exchange: &str, market_type: MarketType) -> Duration {
     let millis = match exchange {
         "binance" => 500,    // spot weitht 1200, contract weight 2400
@@ -108,7 +179,7 @@ pub(crate) fn crawl_snapshot(
     market_type: MarketType,
     msg_type: MessageType, // L2Snapshot or L3Snapshot
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: MessageSender,
 ) {
     let is_empty = match symbols {
         Some(list) => {
@@ -126,6 +197,9 @@ pub(crate) fn crawl_snapshot(
 
     let lock = REST_LOCKS.get(exchange).unwrap().get(&market_type).unwrap().clone();
     'outer: loop {
+        if tx.is_closed() {
+            break;
+        }
         let mut real_symbols = if is_empty {
             if market_type == MarketType::Spot {
                 let spot_symbols = fetch_symbols_retry(exchange, market_type);
@@ -143,6 +217,9 @@ pub(crate) fn crawl_snapshot(
         let mut backoff_factor = 1;
         // retry 5 times at most
         while index < real_symbols.len() && backoff_factor < 6 {
+            if tx.is_closed() {
+                break 'outer;
+            }
             let symbol = real_symbols[index].as_str();
             let mut lock_ = lock.lock().unwrap();
             if !lock_.owns_lock() {
@@ -203,11 +280,14 @@ pub(crate) fn crawl_snapshot(
 }
 
 /// Crawl open interests of all trading symbols.
-pub(crate) fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: Sender<Message>) {
+pub(crate) fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: MessageSender) {
     let cooldown_time = get_cooldown_time_per_request(exchange, market_type);
 
     let lock = REST_LOCKS.get(exchange).unwrap().get(&market_type).unwrap().clone();
     'outer: loop {
+        if tx.is_closed() {
+            break;
+        }
         match exchange {
             "bitz" | "deribit" | "dydx" | "ftx" | "huobi" | "kucoin" | "okx" => {
                 let mut lock_ = lock.lock().unwrap();
@@ -226,7 +306,8 @@ pub(crate) fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: S
                                 x.to_string(),
                             );
                             if tx.send(message).is_err() {
-                                break; // break the loop if there is no receiver
+                                // break the loop if there is no receiver
+                                break 'outer;
                             }
                         }
                     } else {
@@ -237,7 +318,8 @@ pub(crate) fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: S
                             json,
                         );
                         if tx.send(message).is_err() {
-                            break; // break the loop if there is no receiver
+                            // break the loop if there is no receiver
+                            break 'outer;
                         }
                     }
                 }
@@ -256,6 +338,9 @@ pub(crate) fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: S
                 let mut backoff_factor = 1;
                 // retry 5 times at most
                 while index < real_symbols.len() && backoff_factor < 6 {
+                    if tx.is_closed() {
+                        break 'outer;
+                    }
                     let symbol = real_symbols[index].as_str();
                     let mut lock_ = lock.lock().unwrap();
                     if !lock_.owns_lock() {
@@ -371,99 +456,11 @@ async fn create_ws_client_internal(
     exchange: &str,
     market_type: MarketType,
     tx: Sender<String>,
+    url: Option<&str>,
 ) -> Arc<dyn WSClient + Send + Sync> {
-    match exchange {
-        "binance" => match market_type {
-            MarketType::Spot => Arc::new(BinanceSpotWSClient::new(tx, None).await),
-            MarketType::InverseFuture | MarketType::InverseSwap => {
-                Arc::new(BinanceInverseWSClient::new(tx, None).await)
-            }
-            MarketType::LinearFuture | MarketType::LinearSwap => {
-                Arc::new(BinanceLinearWSClient::new(tx, None).await)
-            }
-            MarketType::EuropeanOption => Arc::new(BinanceOptionWSClient::new(tx, None).await),
-            _ => panic!("Binance does NOT have the {market_type} market type"),
-        },
-        "bitfinex" => Arc::new(BitfinexWSClient::new(tx, None).await),
-        "bitget" => match market_type {
-            MarketType::Spot => Arc::new(BitgetSpotWSClient::new(tx, None).await),
-            MarketType::InverseFuture | MarketType::InverseSwap | MarketType::LinearSwap => {
-                Arc::new(BitgetSwapWSClient::new(tx, None).await)
-            }
-            _ => panic!("Bitget does NOT have the {market_type} market type"),
-        },
-        "bithumb" => Arc::new(BithumbWSClient::new(tx, None).await),
-        "bitmex" => Arc::new(BitmexWSClient::new(tx, None).await),
-        "bitstamp" => Arc::new(BitstampWSClient::new(tx, None).await),
-        "bitz" => match market_type {
-            MarketType::Spot => Arc::new(BitzSpotWSClient::new(tx, None).await),
-            _ => panic!("Bitz does NOT have the {market_type} market type"),
-        },
-        "bybit" => match market_type {
-            MarketType::InverseFuture | MarketType::InverseSwap => {
-                Arc::new(BybitInverseWSClient::new(tx, None).await)
-            }
-            MarketType::LinearSwap => Arc::new(BybitLinearSwapWSClient::new(tx, None).await),
-            _ => panic!("Bybit does NOT have the {market_type} market type"),
-        },
-        "coinbase_pro" => Arc::new(CoinbaseProWSClient::new(tx, None).await),
-        "deribit" => Arc::new(DeribitWSClient::new(tx, None).await),
-        "dydx" => match market_type {
-            MarketType::LinearSwap => Arc::new(DydxSwapWSClient::new(tx, None).await),
-            _ => panic!("dYdX does NOT have the {market_type} market type"),
-        },
-        "ftx" => Arc::new(FtxWSClient::new(tx, None).await),
-        "gate" => match market_type {
-            MarketType::Spot => Arc::new(GateSpotWSClient::new(tx, None).await),
-            MarketType::InverseSwap => Arc::new(GateInverseSwapWSClient::new(tx, None).await),
-            MarketType::LinearSwap => Arc::new(GateLinearSwapWSClient::new(tx, None).await),
-            MarketType::InverseFuture => Arc::new(GateInverseFutureWSClient::new(tx, None).await),
-            MarketType::LinearFuture => Arc::new(GateLinearFutureWSClient::new(tx, None).await),
-            _ => panic!("Gate does NOT have the {market_type} market type"),
-        },
-        "huobi" => match market_type {
-            MarketType::Spot => Arc::new(HuobiSpotWSClient::new(tx, None).await),
-            MarketType::InverseFuture => Arc::new(HuobiFutureWSClient::new(tx, None).await),
-            MarketType::LinearSwap => Arc::new(HuobiLinearSwapWSClient::new(tx, None).await),
-            MarketType::InverseSwap => Arc::new(HuobiInverseSwapWSClient::new(tx, None).await),
-            MarketType::EuropeanOption => Arc::new(HuobiOptionWSClient::new(tx, None).await),
-            _ => panic!("Huobi does NOT have the {market_type} market type"),
-        },
-        "kraken" => match market_type {
-            MarketType::Spot => Arc::new(KrakenSpotWSClient::new(tx, None).await),
-            MarketType::InverseFuture | MarketType::InverseSwap => {
-                Arc::new(KrakenFuturesWSClient::new(tx, None).await)
-            }
-            _ => panic!("Kraken does NOT have the {market_type} market type"),
-        },
-        "kucoin" => match market_type {
-            MarketType::Spot => Arc::new(KuCoinSpotWSClient::new(tx, None).await),
-            MarketType::InverseSwap | MarketType::LinearSwap | MarketType::InverseFuture => {
-                Arc::new(KuCoinSwapWSClient::new(tx, None).await)
-            }
-            _ => panic!("KuCoin does NOT have the {market_type} market type"),
-        },
-        "mexc" => match market_type {
-            MarketType::Spot => Arc::new(MexcSpotWSClient::new(tx, None).await),
-            MarketType::LinearSwap | MarketType::InverseSwap => {
-                Arc::new(MexcSwapWSClient::new(tx, None).await)
-            }
-            _ => panic!("MEXC does NOT have the {market_type} market type"),
-        },
-        "okx" => Arc::new(OkxWSClient::new(tx, None).await),
-        "zb" => match market_type {
-            MarketType::Spot => Arc::new(ZbSpotWSClient::new(tx, None).await),
-            MarketType::LinearSwap => Arc::new(ZbSwapWSClient::new(tx, None).await),
-            _ => panic!("ZB does NOT have the {market_type} market type"),
-        },
-        "zbg" => match market_type {
-            MarketType::Spot => Arc::new(ZbgSpotWSClient::new(tx, None).await),
-            MarketType::InverseSwap | MarketType::LinearSwap => {
-                Arc::new(ZbgSwapWSClient::new(tx, None).await)
-            }
-            _ => panic!("ZBG does NOT have the {market_type} market type"),
-        },
-        _ => panic!("Unknown exchange {exchange}"),
+    match crypto_ws_client::create_ws_client(exchange, market_type, tx, url).await {
+        Ok(ws_client) => ws_client.into(),
+        Err(err) => panic!("{}", err),
     }
 }
 
@@ -471,9 +468,8 @@ async fn create_ws_client(
     exchange: &str,
     market_type: MarketType,
     msg_type: MessageType,
-    tx: Sender<Message>,
 ) -> Arc<dyn WSClient + Send + Sync> {
-    let tx = create_conversion_thread(exchange.to_string(), msg_type, market_type, tx);
+    let tx = unused_channel();
     if let Some(interval) = get_connection_interval_ms(exchange, market_type) {
         let lock = WS_LOCKS.get(exchange).unwrap().get(&market_type).unwrap().clone();
         let mut lock = lock.lock().await;
@@ -493,14 +489,14 @@ async fn create_ws_client(
                 .await; // give chances to other tasks
             }
         }
-        let ws_client = create_ws_client_internal(exchange, market_type, tx).await;
+        let ws_client = create_ws_client_internal(exchange, market_type, tx, None).await;
         tokio::time::sleep(Duration::from_millis(interval)).await;
         if lock.owns_lock() {
             lock.unlock().unwrap();
         }
         ws_client
     } else {
-        create_ws_client_internal(exchange, market_type, tx).await
+        create_ws_client_internal(exchange, market_type, tx, None).await
     }
 }
 
@@ -510,22 +506,413 @@ pub(crate) async fn create_ws_client_symbol(
     tx: Sender<String>,
 ) -> Arc<dyn WSClient + Send + Sync> {
     let tx = create_parser_thread(exchange.to_string(), market_type, tx);
-    create_ws_client_internal(exchange, market_type, tx).await
+    create_ws_client_internal(exchange, market_type, tx, None).await
 }
 
 #[derive(Clone)]
 struct EmptyStruct {} // for stop channel
 
+/// The channel passed to `WSClient::subscribe()` by the high-level
+/// `subscribe_*()` APIs, which is needed to unsubscribe symbols.
+///
+/// None means unsubscribing is not supported.
+fn get_ws_channel(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+) -> Option<&'static str> {
+    let channel = match exchange {
+        "binance" if market_type != MarketType::EuropeanOption => match msg_type {
+            MessageType::Trade => "aggTrade",
+            MessageType::L2Event => "depth@100ms",
+            MessageType::L2TopK => "depth20",
+            MessageType::BBO => "bookTicker",
+            MessageType::Ticker => "ticker",
+            _ => return None,
+        },
+        "bitget" => match msg_type {
+            MessageType::Trade => "trade",
+            MessageType::L2Event => "books",
+            MessageType::L2TopK => "books15",
+            MessageType::Ticker => "ticker",
+            _ => return None,
+        },
+        "bitmex" => match msg_type {
+            MessageType::Trade => "trade",
+            MessageType::L2Event => "orderBookL2",
+            MessageType::L2TopK => "orderBook10",
+            MessageType::BBO => "quote",
+            _ => return None,
+        },
+        "bybit" => match msg_type {
+            MessageType::Trade => "trade",
+            MessageType::L2Event => "orderBookL2_25",
+            MessageType::Ticker => "instrument_info.100ms",
+            _ => return None,
+        },
+        "coinbase_pro" => match msg_type {
+            MessageType::Trade => "matches",
+            MessageType::L2Event => "level2",
+            MessageType::L3Event => "full",
+            MessageType::Ticker => "ticker",
+            _ => return None,
+        },
+        "deribit" => match msg_type {
+            MessageType::Trade => "trades.SYMBOL.100ms",
+            MessageType::L2Event => "book.SYMBOL.100ms",
+            MessageType::L2TopK => "book.SYMBOL.none.20.100ms",
+            MessageType::BBO => "quote.SYMBOL",
+            MessageType::Ticker => "ticker.SYMBOL.100ms",
+            _ => return None,
+        },
+        "gate" => match (market_type, msg_type) {
+            (_, MessageType::Trade) => "trades",
+            (_, MessageType::Ticker) => "tickers",
+            (MarketType::InverseFuture | MarketType::LinearFuture, MessageType::L2Event) => {
+                "order_book"
+            }
+            (MarketType::InverseFuture | MarketType::LinearFuture, _) => return None,
+            (_, MessageType::L2Event) => "order_book_update",
+            (_, MessageType::L2TopK) => "order_book",
+            (_, MessageType::BBO) => "book_ticker",
+            _ => return None,
+        },
+        "huobi" => match msg_type {
+            MessageType::Trade => "trade.detail",
+            MessageType::BBO => "bbo",
+            MessageType::Ticker => "detail",
+            _ => return None,
+        },
+        "kraken" => match msg_type {
+            MessageType::Trade => "trade",
+            MessageType::L2Event => "book",
+            MessageType::Ticker => "ticker",
+            MessageType::BBO if market_type == MarketType::Spot => "spread",
+            _ => return None,
+        },
+        "kucoin" => match (market_type, msg_type) {
+            (MarketType::Spot, MessageType::Trade) => "/market/match",
+            (MarketType::Spot, MessageType::BBO) => "/market/ticker",
+            (MarketType::Spot, MessageType::L2Event) => "/market/level2",
+            (MarketType::Spot, MessageType::L2TopK) => "/spotMarket/level2Depth5",
+            (MarketType::Spot, MessageType::Ticker) => "/market/snapshot",
+            (MarketType::Spot, _) => return None,
+            (_, MessageType::Trade) => "/contractMarket/execution",
+            (_, MessageType::BBO) => "/contractMarket/tickerV2",
+            (_, MessageType::L2Event) => "/contractMarket/level2",
+            (_, MessageType::L2TopK) => "/contractMarket/level2Depth5",
+            (_, MessageType::Ticker) => "/contractMarket/snapshot",
+            _ => return None,
+        },
+        "okx" => match msg_type {
+            MessageType::Trade => "trades",
+            MessageType::L2Event => "books",
+            MessageType::L2TopK => "books5",
+            MessageType::BBO => "bbo-tbt",
+            MessageType::Ticker => "tickers",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(channel)
+}
+
+/// What a crawler subscribes to for each symbol.
+#[derive(Clone)]
+enum Subscription {
+    Event(MessageType),
+    Candlestick(Vec<usize>), // intervals in seconds
+}
+
+impl Subscription {
+    fn msg_type(&self) -> MessageType {
+        match self {
+            Subscription::Event(msg_type) => *msg_type,
+            Subscription::Candlestick(_) => MessageType::Candlestick,
+        }
+    }
+
+    // Number of topics occupied by one symbol
+    fn topics_per_symbol(&self) -> usize {
+        match self {
+            Subscription::Event(_) => 1,
+            Subscription::Candlestick(intervals) => intervals.len(),
+        }
+    }
+
+    async fn subscribe(
+        &self,
+        exchange: &str,
+        market_type: MarketType,
+        symbols: Vec<String>,
+        ws_client: Arc<dyn WSClient + Send + Sync>,
+    ) {
+        match self {
+            Subscription::Event(msg_type) => {
+                subscribe_with_lock(
+                    exchange.to_string(),
+                    market_type,
+                    *msg_type,
+                    symbols,
+                    ws_client,
+                )
+                .await
+            }
+            Subscription::Candlestick(intervals) => {
+                let symbol_interval_list = to_symbol_interval_list(&symbols, intervals);
+                ws_client.subscribe_candlestick(&symbol_interval_list).await;
+            }
+        }
+    }
+
+    // Returns false if unsubscribing is not supported.
+    async fn unsubscribe(
+        &self,
+        exchange: &str,
+        market_type: MarketType,
+        symbols: &[String],
+        ws_client: &Arc<dyn WSClient + Send + Sync>,
+    ) -> bool {
+        let channel = match self {
+            Subscription::Event(msg_type) => get_ws_channel(exchange, market_type, *msg_type),
+            Subscription::Candlestick(_) => None,
+        };
+        if let Some(channel) = channel {
+            let topics = symbols
+                .iter()
+                .map(|symbol| (channel.to_string(), symbol.to_string()))
+                .collect::<Vec<(String, String)>>();
+            ws_client.unsubscribe(&topics).await;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A websocket connection and the symbols subscribed on it.
+struct Connection {
+    ws_client: Arc<dyn WSClient + Send + Sync>,
+    symbols: Vec<String>,
+    num_topics: usize,
+}
+
+fn to_symbol_interval_list(symbols: &[String], intervals: &[usize]) -> Vec<(String, usize)> {
+    symbols
+        .iter()
+        .flat_map(|symbol| intervals.iter().map(move |interval| (symbol.clone(), *interval)))
+        .collect()
+}
+
+/// Assign `symbols` to existing connections which still have capacity, the
+/// remaining symbols are split into chunks, each of which needs a new
+/// connection.
+///
+/// `num_topics` contains the number of topics subscribed on each existing
+/// connection.
+fn allocate_symbols(
+    num_topics: &[usize],
+    num_topics_per_connection: usize,
+    topics_per_symbol: usize,
+    symbols: &[String],
+) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+    let topics_per_symbol = std::cmp::max(topics_per_symbol, 1);
+    let mut remaining = symbols;
+    let assigned = num_topics
+        .iter()
+        .map(|n| {
+            let capacity = num_topics_per_connection.saturating_sub(*n) / topics_per_symbol;
+            let (head, tail) = remaining.split_at(std::cmp::min(capacity, remaining.len()));
+            remaining = tail;
+            head.to_vec()
+        })
+        .collect();
+    let chunk_size = std::cmp::max(num_topics_per_connection / topics_per_symbol, 1);
+    let overflow = remaining.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect();
+    (assigned, overflow)
+}
+
+/// State of the symbol discovery task.
+struct SymbolDiscovery {
+    exchange: String,
+    market_type: MarketType,
+    subscription: Subscription,
+    connections: Vec<Connection>,
+    num_initial_connections: usize,
+    listed_symbols: HashSet<String>,
+    handles: JoinSet<()>, // new connections, aborted when dropped
+    tx: MessageSender,    // used by new connections
+}
+
+impl SymbolDiscovery {
+    async fn refresh(&mut self) {
+        let exchange = self.exchange.clone();
+        let market_type = self.market_type;
+        let latest_symbols =
+            tokio::task::block_in_place(move || fetch_symbols_retry(&exchange, market_type));
+        if latest_symbols.is_empty() {
+            // An empty list means fetch_symbols_retry() failed, rather than all
+            // symbols have been delisted
+            warn!("Failed to fetch symbols of {} {}", self.exchange, self.market_type);
+            return;
+        }
+
+        let mut delisted_symbols: Vec<String> =
+            self.listed_symbols.iter().filter(|s| !latest_symbols.contains(s)).cloned().collect();
+        delisted_symbols.sort();
+        if !delisted_symbols.is_empty() {
+            warn!("Found delisted symbols: {}", delisted_symbols.join(", "));
+            self.remove_symbols(delisted_symbols).await;
+        }
+
+        let new_symbols: Vec<String> =
+            latest_symbols.into_iter().filter(|s| !self.listed_symbols.contains(s)).collect();
+        if !new_symbols.is_empty() {
+            warn!("Found new symbols: {}", new_symbols.join(", "));
+            self.add_symbols(new_symbols).await;
+        }
+    }
+
+    async fn remove_symbols(&mut self, delisted_symbols: Vec<String>) {
+        let topics_per_symbol = self.subscription.topics_per_symbol();
+        let mut still_subscribed = HashSet::new();
+        for conn in self.connections.iter_mut() {
+            let symbols: Vec<String> =
+                conn.symbols.iter().filter(|s| delisted_symbols.contains(s)).cloned().collect();
+            if symbols.is_empty() {
+                continue;
+            }
+            if self
+                .subscription
+                .unsubscribe(&self.exchange, self.market_type, &symbols, &conn.ws_client)
+                .await
+            {
+                conn.symbols.retain(|s| !symbols.contains(s));
+                conn.num_topics = conn.num_topics.saturating_sub(symbols.len() * topics_per_symbol);
+            } else {
+                warn!(
+                    "{} {} {} does NOT support unsubscribing, {} are still subscribed",
+                    self.exchange,
+                    self.market_type,
+                    self.subscription.msg_type(),
+                    symbols.join(", ")
+                );
+                still_subscribed.extend(symbols);
+            }
+        }
+        for symbol in delisted_symbols {
+            self.listed_symbols.remove(&symbol);
+            if still_subscribed.contains(&symbol) {
+                continue; // Delisted is only emitted after a real unsubscribe
+            }
+            emit_symbol_event(SymbolEvent::Delisted {
+                exchange: self.exchange.clone(),
+                market_type: self.market_type,
+                symbol,
+            });
+        }
+    }
+
+    async fn add_symbols(&mut self, new_symbols: Vec<String>) {
+        let num_topics_per_connection =
+            get_num_subscriptions_per_connection(&self.exchange, self.market_type);
+        let topics_per_symbol = self.subscription.topics_per_symbol();
+        let msg_type = self.subscription.msg_type();
+        // Delisted symbols which failed to unsubscribe are still subscribed
+        let symbols_to_subscribe: Vec<String> = new_symbols
+            .iter()
+            .filter(|s| !self.connections.iter().any(|conn| conn.symbols.contains(s)))
+            .cloned()
+            .collect();
+        let num_topics: Vec<usize> = self.connections.iter().map(|conn| conn.num_topics).collect();
+        let (assigned, overflow) = allocate_symbols(
+            &num_topics,
+            num_topics_per_connection,
+            topics_per_symbol,
+            &symbols_to_subscribe,
+        );
+
+        for (conn, symbols) in self.connections.iter_mut().zip(assigned) {
+            if symbols.is_empty() {
+                continue;
+            }
+            self.subscription
+                .subscribe(
+                    &self.exchange,
+                    self.market_type,
+                    symbols.clone(),
+                    conn.ws_client.clone(),
+                )
+                .await;
+            conn.num_topics += symbols.len() * topics_per_symbol;
+            conn.symbols.extend(symbols);
+        }
+        for symbols in overflow {
+            info!(
+                "All connections of {} {} {} are full, creating a new connection for {}",
+                self.exchange,
+                self.market_type,
+                msg_type,
+                symbols.join(", ")
+            );
+            let ws_client = create_ws_client(&self.exchange, self.market_type, msg_type).await;
+            self.subscription
+                .subscribe(&self.exchange, self.market_type, symbols.clone(), ws_client.clone())
+                .await;
+            let (ws_client_clone, exchange, market_type, tx) =
+                (ws_client.clone(), self.exchange.clone(), self.market_type, self.tx.clone());
+            self.handles.spawn(async move {
+                run_ws_client(ws_client_clone.as_ref(), &exchange, market_type, msg_type, tx).await;
+            });
+            let num_topics = symbols.len() * topics_per_symbol;
+            self.connections.push(Connection { ws_client, symbols, num_topics });
+        }
+
+        for symbol in new_symbols {
+            self.listed_symbols.insert(symbol.clone());
+            emit_symbol_event(SymbolEvent::Listed {
+                exchange: self.exchange.clone(),
+                market_type: self.market_type,
+                symbol,
+            });
+        }
+    }
+
+    // Close connections created by the discovery task
+    async fn close(mut self) {
+        for conn in self.connections.iter().skip(self.num_initial_connections) {
+            conn.ws_client.close().await;
+        }
+        while self.handles.join_next().await.is_some() {}
+    }
+}
+
+/// Discover new and delisted symbols every hour.
+///
+/// New symbols are subscribed on connections which still have capacity, and
+/// new connections are created if all connections are full. Delisted symbols
+/// are unsubscribed if the exchange supports it, and `SymbolEvent::Delisted`
+/// is emitted only for symbols that have actually been unsubscribed.
 fn create_symbol_discovery_thread(
     exchange: String,
     market_type: MarketType,
-    subscribed_symbols: Vec<String>,
+    subscription: Subscription,
+    connections: Vec<Connection>,
     mut stop_ch_rx: tokio::sync::broadcast::Receiver<EmptyStruct>,
-    tx: tokio::sync::mpsc::Sender<Vec<String>>, // send out new symbols
+    tx: MessageSender,
 ) -> tokio::task::JoinHandle<()> {
-    let num_topics_per_connection = get_num_subscriptions_per_connection(&exchange, market_type);
-    let mut subscribed_symbols = subscribed_symbols;
-    let mut num_subscribed_of_last_client = subscribed_symbols.len() % num_topics_per_connection;
+    let listed_symbols: HashSet<String> =
+        connections.iter().flat_map(|conn| conn.symbols.iter().cloned()).collect();
+    let mut discovery = SymbolDiscovery {
+        exchange,
+        market_type,
+        subscription,
+        num_initial_connections: connections.len(),
+        connections,
+        listed_symbols,
+        handles: JoinSet::new(),
+        tx,
+    };
     let mut hourly = tokio::time::interval(Duration::from_secs(3600));
     tokio::task::spawn(async move {
         loop {
@@ -534,93 +921,57 @@ fn create_symbol_discovery_thread(
                     break;
                 }
                 _ = hourly.tick() => {
-                    let exchange_clone = exchange.to_string();
-                    let latest_symbols = tokio::task::block_in_place(move || {
-                        fetch_symbols_retry(&exchange_clone, market_type)
-                    });
-
-                    let mut new_symbols: Vec<String> = latest_symbols
-                        .iter()
-                        .filter(|s| !subscribed_symbols.contains(s))
-                        .cloned()
-                        .collect();
-
-                    if !new_symbols.is_empty() {
-                        warn!("Found new symbols: {}", new_symbols.join(", "));
-                        if tx.send(new_symbols.clone()).await.is_err() {
-                            break; // break the loop if there is no receiver
-                        }
-                        num_subscribed_of_last_client += new_symbols.len();
-                        subscribed_symbols.append(&mut new_symbols);
-                    }
-                    if num_subscribed_of_last_client >= num_topics_per_connection {
-                        panic!(
-                            "The last connection has subscribed {num_subscribed_of_last_client} topics, which is more than {num_topics_per_connection}, restarting the process",
-                        ); // pm2 will restart the whole process
-                    }
+                    discovery.refresh().await;
                 }
             }
         }
+        discovery.close().await;
     })
 }
 
-fn create_new_symbol_receiver_thread(
-    exchange: String,
-    msg_type: MessageType,
-    market_type: MarketType,
-    mut symbols_rx: tokio::sync::mpsc::Receiver<Vec<String>>,
-    ws_client: Arc<dyn WSClient + Send + Sync>,
-) -> tokio::task::JoinHandle<()> {
-    tokio::task::spawn(async move {
-        let exchange_clone = exchange;
-        while let Some(new_symbols) = symbols_rx.recv().await {
-            subscribe_with_lock(
-                exchange_clone.clone(),
-                market_type,
-                msg_type,
-                new_symbols,
-                ws_client.clone(),
-            )
-            .await;
-        }
-    })
+// Websocket clients are constructed with a channel, which `run_ws_client()`
+// doesn't use.
+pub(crate) fn unused_channel() -> Sender<String> {
+    std::sync::mpsc::channel().0
 }
 
-fn create_new_symbol_receiver_thread_candlestick(
-    intervals: Vec<usize>,
-    mut rx: tokio::sync::mpsc::Receiver<Vec<String>>,
-    ws_client: Arc<dyn WSClient + Send + Sync>,
-) -> tokio::task::JoinHandle<()> {
-    tokio::task::spawn(async move {
-        while let Some(new_symbols) = rx.recv().await {
-            let new_symbol_interval_list = new_symbols
-                .iter()
-                .flat_map(|symbol| {
-                    intervals.clone().into_iter().map(move |interval| (symbol.clone(), interval))
-                })
-                .collect::<Vec<(String, usize)>>();
-            ws_client.subscribe_candlestick(&new_symbol_interval_list).await;
-        }
-    })
-}
-
-// create a thread to convert Sender<Message> Sender<String>
-pub(crate) fn create_conversion_thread(
-    exchange: String,
-    msg_type: MessageType,
+// Runs `ws_client` until it exits or the receiver of `tx` has been dropped,
+// then closes it.
+//
+// Frames are converted to `Message`s and sent to `tx` inline on the read task
+// of the websocket client, so that a full `OverflowPolicy::Block` buffer stops
+// reading from the socket instead of queueing frames in memory.
+pub(crate) async fn run_ws_client(
+    ws_client: &(dyn WSClient + Send + Sync),
+    exchange: &str,
     market_type: MarketType,
-    tx: Sender<Message>,
-) -> Sender<String> {
-    let (tx_raw, rx_raw) = std::sync::mpsc::channel();
-    tokio::task::spawn_blocking(move || {
-        for json in rx_raw {
-            let msg = Message::new(exchange.clone(), market_type, msg_type, json);
-            if tx.send(msg).is_err() {
-                break; // break the loop if there is no receiver
+    msg_type: MessageType,
+    tx: MessageSender,
+) {
+    let disconnected = Arc::new(tokio::sync::Notify::new());
+    let callback: FrameCallback = {
+        let (exchange, disconnected) = (exchange.to_string(), disconnected.clone());
+        Box::new(move |frame: &Frame| {
+            let msg =
+                Message::new(exchange.clone(), market_type, msg_type, frame.payload.to_string());
+            // Only a buffer which is full and blocks needs to leave the worker
+            // thread, block_in_place() is too costly to call on every frame
+            let sent = match tx.try_send(msg) {
+                Err(TrySendError::Full(msg)) => {
+                    tokio::task::block_in_place(|| tx.send(msg)).is_ok()
+                }
+                result => result.is_ok(),
+            };
+            if !sent {
+                disconnected.notify_one(); // the receiver has been dropped
             }
-        }
-    });
-    tx_raw
+        })
+    };
+    tokio::select! {
+        _ = ws_client.run_with_callback(callback) => {}
+        _ = disconnected.notified() => {}
+    }
+    ws_client.close().await;
 }
 
 // create a thread to call `crypto-msg-parser`
@@ -666,35 +1017,60 @@ fn create_parser_thread(
     tx_raw
 }
 
-async fn crawl_event_one_chunk(
+// Waits for all connections to exit. If one of them fails, the others are
+// aborted and the panic is re-raised, so that the crawl can be restarted as a
+// whole instead of running with missing connections.
+//
+// Dropping the returned future aborts the connections and the symbol discovery
+// task, which closes their websockets.
+async fn join_connections(
+    mut connections: JoinSet<()>,
+    stop_ch_tx: tokio::sync::broadcast::Sender<EmptyStruct>,
+    symbol_discovery_thread: Option<tokio::task::JoinHandle<()>>,
+) {
+    let _guard = symbol_discovery_thread.as_ref().map(|thread| AbortOnDrop(thread.abort_handle()));
+    let mut failure = None;
+    while let Some(result) = connections.join_next().await {
+        if let Err(err) = result {
+            failure = Some(err);
+            break;
+        }
+    }
+    connections.abort_all();
+    _ = stop_ch_tx.send(EmptyStruct {});
+    if let Some(thread) = symbol_discovery_thread {
+        if let Err(err) = thread.await {
+            failure = failure.or(Some(err));
+        }
+    }
+    if let Some(err) = failure {
+        if err.is_panic() {
+            std::panic::resume_unwind(err.into_panic());
+        }
+        panic!("{}", err);
+    }
+}
+
+fn crawl_event_one_chunk(
     exchange: String,
     market_type: MarketType,
     msg_type: MessageType,
-    ws_client: Option<Arc<dyn WSClient + Send + Sync>>,
+    ws_client: Arc<dyn WSClient + Send + Sync>,
     symbols: Vec<String>,
-    tx: Sender<Message>,
-) -> tokio::task::JoinHandle<()> {
-    let ws_client = if let Some(ws_client) = ws_client {
-        ws_client
-    } else {
-        let tx_clone = tx.clone();
-        create_ws_client(&exchange, market_type, msg_type, tx_clone).await
-    };
-
+    tx: MessageSender,
+    connections: &mut JoinSet<()>,
+) {
     {
         // fire and forget
-        let exchange_clone = exchange.to_string();
-        let ws_client_clone = ws_client.clone();
-        tokio::task::spawn(async move {
-            subscribe_with_lock(exchange_clone, market_type, msg_type, symbols, ws_client_clone)
-                .await;
+        let (exchange, ws_client_clone) = (exchange.clone(), ws_client.clone());
+        connections.spawn(async move {
+            subscribe_with_lock(exchange, market_type, msg_type, symbols, ws_client_clone).await;
         });
     }
 
-    tokio::task::spawn(async move {
-        ws_client.run().await;
-        ws_client.close().await;
-    })
+    connections.spawn(async move {
+        run_ws_client(ws_client.as_ref(), &exchange, market_type, msg_type, tx).await;
+    });
 }
 
 pub(crate) async fn crawl_event(
@@ -702,26 +1078,13 @@ pub(crate) async fn crawl_event(
     msg_type: MessageType,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: MessageSender,
 ) {
-    let num_topics_per_connection = get_num_subscriptions_per_connection(exchange, market_type);
-    let is_empty = match symbols {
-        Some(list) => {
-            if list.is_empty() {
-                true
-            } else {
-                tokio::task::block_in_place(move || check_args(exchange, market_type, list));
-                false
-            }
-        }
-        None => true,
-    };
-    let automatic_symbol_discovery = is_empty;
-
-    let real_symbols = if is_empty {
-        tokio::task::block_in_place(move || fetch_symbols_retry(exchange, market_type))
-    } else {
-        symbols.unwrap().to_vec()
+    let (real_symbols, automatic_symbol_discovery) = match tokio::task::block_in_place(move || {
+        resolve_symbols(exchange, market_type, symbols)
+    }) {
+        Ok(resolved) => resolved,
+        Err(err) => panic!("{}", err),
     };
     if real_symbols.is_empty() {
         error!("real_symbols is empty due to fetch_symbols_retry() failure");
@@ -731,97 +1094,133 @@ pub(crate) async fn crawl_event(
     // The stop channel is used by all tokio tasks
     let (stop_ch_tx, stop_ch_rx) = tokio::sync::broadcast::channel::<EmptyStruct>(1);
 
-    // create a thread to discover new symbols
-    let (tx_symbols, rx_symbols) = tokio::sync::mpsc::channel::<Vec<String>>(4);
+    let mut connections = Vec::new();
+    let mut handles = JoinSet::new();
+    for chunk in split_into_connections(exchange, market_type, &real_symbols) {
+        let ws_client = create_ws_client(exchange, market_type, msg_type).await;
+        crawl_event_one_chunk(
+            exchange.to_string(),
+            market_type,
+            msg_type,
+            ws_client.clone(),
+            chunk.to_vec(),
+            tx.clone(),
+            &mut handles,
+        );
+        connections.push(Connection {
+            ws_client,
+            symbols: chunk.to_vec(),
+            num_topics: chunk.len(),
+        });
+    }
+    debug!("{} symbols, {} connections", real_symbols.len(), connections.len());
+
+    // create a thread to discover new and delisted symbols
     let symbol_discovery_thread = if automatic_symbol_discovery {
         let thread = create_symbol_discovery_thread(
             exchange.to_string(),
             market_type,
-            real_symbols.clone(),
+            Subscription::Event(msg_type),
+            connections,
             stop_ch_rx,
-            tx_symbols,
+            tx,
         );
         Some(thread)
     } else {
+        drop(tx);
         None
     };
 
-    // create a thread to convert Sender<String> to Sender<Message>
-    if real_symbols.len() <= num_topics_per_connection {
-        let ws_client = create_ws_client(exchange, market_type, msg_type, tx).await;
-        subscribe_with_lock(
-            exchange.to_string(),
-            market_type,
-            msg_type,
-            real_symbols,
-            ws_client.clone(),
-        )
-        .await;
-        if automatic_symbol_discovery {
-            create_new_symbol_receiver_thread(
-                exchange.to_string(),
-                msg_type,
-                market_type,
-                rx_symbols,
-                ws_client.clone(),
-            );
-        }
-        ws_client.run().await;
-        ws_client.close().await;
-    } else {
-        // split to chunks
-        let mut chunks: Vec<Vec<String>> = Vec::new();
-        for i in (0..real_symbols.len()).step_by(num_topics_per_connection) {
-            let chunk = real_symbols
-                [i..(std::cmp::min(i + num_topics_per_connection, real_symbols.len()))]
-                .to_vec();
-            chunks.push(chunk);
-        }
-        debug!("{} {} {}", real_symbols.len(), num_topics_per_connection, chunks.len(),);
-        assert!(chunks.len() > 1);
-
-        let mut last_ws_client = None;
-        let mut handles = Vec::new();
-        {
-            let n = chunks.len();
-            for (i, chunk) in chunks.into_iter().enumerate() {
-                last_ws_client = if i == (n - 1) {
-                    let tx_clone = tx.clone();
-                    Some(create_ws_client(exchange, market_type, msg_type, tx_clone).await)
-                } else {
-                    None
-                };
-                let ret = crawl_event_one_chunk(
-                    exchange.to_string(),
-                    market_type,
-                    msg_type,
-                    last_ws_client.clone(),
-                    chunk,
-                    tx.clone(),
-                );
-                handles.push(ret.await);
-            }
-            drop(tx);
-        }
-        if automatic_symbol_discovery && last_ws_client.is_some() {
-            create_new_symbol_receiver_thread(
-                exchange.to_string(),
-                msg_type,
-                market_type,
-                rx_symbols,
-                last_ws_client.unwrap(),
-            );
-        }
-        for handle in handles {
-            if let Err(err) = handle.await {
-                panic!("{}", err); // TODO: use tokio::task::JoinSet or futures::stream::FuturesUnordered
+    join_connections(handles, stop_ch_tx, symbol_discovery_thread).await;
+}
+
+// Subscription commands sent by a websocket client, which connects to a local
+// endpoint capturing them instead of the exchange.
+async fn capture_commands<F, Fut>(
+    exchange: &str,
+    market_type: MarketType,
+    subscribe: F,
+) -> crate::error::Result<Vec<String>>
+where
+    F: FnOnce(Arc<dyn WSClient + Send + Sync>) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()> + Send,
+{
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("ws://{}", listener.local_addr()?);
+    let endpoint = tokio::task::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        let mut ws_stream =
+            tokio_tungstenite::accept_async(stream).await.map_err(std::io::Error::other)?;
+        let mut commands = Vec::new();
+        while let Some(msg) = ws_stream.next().await {
+            match msg.map_err(std::io::Error::other)? {
+                WsMessage::Text(command) => commands.push(command),
+                WsMessage::Close(_) => break,
+                _ => (),
             }
         }
-    };
-    _ = stop_ch_tx.send(EmptyStruct {});
-    if let Some(thread) = symbol_discovery_thread {
-        _ = thread.await;
+        Ok::<Vec<String>, std::io::Error>(commands)
+    });
+
+    let (exchange, (tx, _rx)) = (exchange.to_string(), std::sync::mpsc::channel());
+    // clients panic on unsupported market types and channels
+    let client = tokio::task::spawn(async move {
+        let ws_client = create_ws_client_internal(&exchange, market_type, tx, Some(&url)).await;
+        subscribe(ws_client.clone()).await;
+        // sent after all commands
+        ws_client.close().await;
+    });
+    if let Err(err) = client.await {
+        endpoint.abort();
+        return Err(crate::Error(if err.is_panic() {
+            let payload = err.into_panic();
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "The websocket client panicked".to_string())
+        } else {
+            err.to_string()
+        }));
     }
+    let commands = endpoint.await.map_err(|err| crate::Error(err.to_string()))??;
+    Ok(commands)
+}
+
+// Plans `crawl_event()` without connecting to the exchange.
+pub(crate) async fn plan_event(
+    exchange: &str,
+    msg_type: MessageType,
+    market_type: MarketType,
+    symbols: Option<&[String]>,
+) -> crate::error::Result<CrawlPlan> {
+    let num_topics_per_connection = get_num_subscriptions_per_connection(exchange, market_type);
+    let (real_symbols, symbol_discovery) =
+        tokio::task::block_in_place(move || resolve_symbols(exchange, market_type, symbols))?;
+    if real_symbols.is_empty() {
+        return Err(crate::Error("fetch_symbols_retry() returned no symbols".to_string()));
+    }
+
+    let mut connections = Vec::new();
+    for chunk in split_into_connections(exchange, market_type, &real_symbols) {
+        let (exchange_clone, symbols) = (exchange.to_string(), chunk.to_vec());
+        let commands = capture_commands(exchange, market_type, move |ws_client| {
+            subscribe_with_lock(exchange_clone, market_type, msg_type, symbols, ws_client)
+        })
+        .await?;
+        connections.push(ConnectionPlan { symbols: chunk.to_vec(), commands });
+    }
+    Ok(CrawlPlan {
+        exchange: exchange.to_string(),
+        market_type,
+        msg_type,
+        num_symbols: real_symbols.len(),
+        topics_per_connection: Some(num_topics_per_connection).filter(|n| *n != usize::MAX),
+        symbol_discovery,
+        connections,
+    })
 }
 
 // from 1m to 5m
@@ -849,159 +1248,299 @@ fn get_candlestick_intervals(exchange: &str, market_type: MarketType) -> Vec<usi
     }
 }
 
-async fn crawl_candlestick_one_chunk(
+fn crawl_candlestick_one_chunk(
     exchange: String,
     market_type: MarketType,
-    ws_client: Option<Arc<dyn WSClient + Send + Sync>>,
+    ws_client: Arc<dyn WSClient + Send + Sync>,
     symbol_interval_list: Vec<(String, usize)>,
-    tx: Sender<Message>,
-) -> tokio::task::JoinHandle<()> {
-    let ws_client = if let Some(ws_client) = ws_client {
-        ws_client
-    } else {
-        let tx_clone = tx.clone();
-        create_ws_client(&exchange, market_type, MessageType::Candlestick, tx_clone).await
-    };
-
+    tx: MessageSender,
+    connections: &mut JoinSet<()>,
+) {
     {
         // fire and forget
         let ws_client_clone = ws_client.clone();
-        tokio::task::spawn(async move {
+        connections.spawn(async move {
             ws_client_clone.subscribe_candlestick(&symbol_interval_list).await;
         });
     }
 
-    tokio::task::spawn(async move {
-        ws_client.run().await;
-        ws_client.close().await;
-    })
+    connections.spawn(async move {
+        let msg_type = MessageType::Candlestick;
+        run_ws_client(ws_client.as_ref(), &exchange, market_type, msg_type, tx).await;
+    });
 }
 
 pub(crate) async fn crawl_candlestick_ext(
     exchange: &str,
     market_type: MarketType,
     symbol_interval_list: Option<&[(String, usize)]>,
-    tx: Sender<Message>,
+    tx: MessageSender,
 ) {
-    let num_topics_per_connection = get_num_subscriptions_per_connection(exchange, market_type);
-    let is_empty = match symbol_interval_list {
-        Some(list) => {
-            if list.is_empty() {
-                true
-            } else {
-                let symbols: Vec<String> = list.iter().map(|t| t.0.clone()).collect();
-                tokio::task::block_in_place(move || check_args(exchange, market_type, &symbols));
-                false
-            }
-        }
-        None => true,
-    };
-    let automatic_symbol_discovery = is_empty;
-
-    let symbol_interval_list: Vec<(String, usize)> = if is_empty {
-        let symbols =
-            tokio::task::block_in_place(move || fetch_symbols_retry(exchange, market_type));
-        let intervals = get_candlestick_intervals(exchange, market_type);
-        symbols
-            .iter()
-            .flat_map(|symbol| {
-                intervals.clone().into_iter().map(move |interval| (symbol.clone(), interval))
-            })
-            .collect::<Vec<(String, usize)>>()
-    } else {
-        symbol_interval_list.unwrap().to_vec()
-    };
+    let (symbol_interval_list, automatic_symbol_discovery) =
+        match tokio::task::block_in_place(move || {
+            resolve_symbol_interval_list(exchange, market_type, symbol_interval_list)
+        }) {
+            Ok(resolved) => resolved,
+            Err(err) => panic!("{}", err),
+        };
     if symbol_interval_list.is_empty() {
         error!("symbol_interval_list is empty due to fetch_symbols_retry() failure");
         return;
     }
-    let real_symbols: Vec<String> = symbol_interval_list.iter().map(|t| t.0.clone()).collect();
-    let real_intervals: Vec<usize> = symbol_interval_list.iter().map(|t| t.1).collect();
 
     // The stop channel is used by all tokio tasks
     let (stop_ch_tx, stop_ch_rx) = tokio::sync::broadcast::channel::<EmptyStruct>(1);
 
-    // create a thread to discover new symbols
-    let (tx_symbols, rx_symbols) = tokio::sync::mpsc::channel::<Vec<String>>(4);
+    let mut connections = Vec::new();
+    let mut handles = JoinSet::new();
+    for chunk in split_into_connections(exchange, market_type, &symbol_interval_list) {
+        let ws_client = create_ws_client(exchange, market_type, MessageType::Candlestick).await;
+        crawl_candlestick_one_chunk(
+            exchange.to_string(),
+            market_type,
+            ws_client.clone(),
+            chunk.to_vec(),
+            tx.clone(),
+            &mut handles,
+        );
+        let mut symbols: Vec<String> = chunk.iter().map(|t| t.0.clone()).collect();
+        symbols.dedup();
+        connections.push(Connection { ws_client, symbols, num_topics: chunk.len() });
+    }
+    debug!("{} topics, {} connections", symbol_interval_list.len(), connections.len());
+
+    // create a thread to discover new and delisted symbols
     let symbol_discovery_thread = if automatic_symbol_discovery {
         let thread = create_symbol_discovery_thread(
             exchange.to_string(),
             market_type,
-            real_symbols,
+            Subscription::Candlestick(get_candlestick_intervals(exchange, market_type)),
+            connections,
             stop_ch_rx,
-            tx_symbols,
+            tx,
         );
         Some(thread)
     } else {
+        drop(tx);
         None
     };
 
-    if symbol_interval_list.len() <= num_topics_per_connection {
-        let ws_client = create_ws_client(exchange, market_type, MessageType::Candlestick, tx).await;
-        ws_client.subscribe_candlestick(&symbol_interval_list).await;
-        if automatic_symbol_discovery {
-            create_new_symbol_receiver_thread_candlestick(
-                real_intervals,
-                rx_symbols,
-                ws_client.clone(),
+    join_connections(handles, stop_ch_tx, symbol_discovery_thread).await;
+}
+
+// Plans `crawl_candlestick_ext()` without connecting to the exchange.
+pub(crate) async fn plan_candlestick_ext(
+    exchange: &str,
+    market_type: MarketType,
+    symbol_interval_list: Option<&[(String, usize)]>,
+) -> crate::error::Result<CrawlPlan> {
+    let num_topics_per_connection = get_num_subscriptions_per_connection(exchange, market_type);
+    let (symbol_interval_list, symbol_discovery) = tokio::task::block_in_place(|| {
+        resolve_symbol_interval_list(exchange, market_type, symbol_interval_list)
+    })?;
+    if symbol_interval_list.is_empty() {
+        return Err(crate::Error("fetch_symbols_retry() returned no symbols".to_string()));
+    }
+    let num_symbols = symbol_interval_list.iter().map(|t| &t.0).collect::<HashSet<_>>().len();
+
+    let mut connections = Vec::new();
+    for chunk in split_into_connections(exchange, market_type, &symbol_interval_list) {
+        let list = chunk.to_vec();
+        let commands = capture_commands(exchange, market_type, move |ws_client| async move {
+            ws_client.subscribe_candlestick(&list).await
+        })
+        .await?;
+        let mut symbols: Vec<String> = chunk.iter().map(|t| t.0.clone()).collect();
+        symbols.dedup();
+        connections.push(ConnectionPlan { symbols, commands });
+    }
+    Ok(CrawlPlan {
+        exchange: exchange.to_string(),
+        market_type,
+        msg_type: MessageType::Candlestick,
+        num_symbols,
+        topics_per_connection: Some(num_topics_per_connection).filter(|n| *n != usize::MAX),
+        symbol_discovery,
+        connections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        allocate_symbols, capture_commands, crawl_event_one_chunk, create_ws_client_internal,
+        join_connections, run_ws_client, subscribe_with_lock, unused_channel,
+    };
+    use crate::{bounded_channel, supervise, OverflowPolicy, RestartPolicy};
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+    use futures_util::{SinkExt, StreamExt};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    fn to_symbols(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_allocate_symbols_with_capacity() {
+        let symbols = to_symbols(&["A", "B", "C"]);
+        let (assigned, overflow) = allocate_symbols(&[5, 3], 5, 1, &symbols);
+        assert_eq!(vec![Vec::<String>::new(), to_symbols(&["A", "B"])], assigned);
+        assert_eq!(vec![to_symbols(&["C"])], overflow);
+    }
+
+    #[test]
+    fn test_allocate_symbols_overflow() {
+        let symbols = to_symbols(&["A", "B", "C", "D", "E"]);
+        let (assigned, overflow) = allocate_symbols(&[4], 4, 2, &symbols);
+        assert_eq!(vec![Vec::<String>::new()], assigned);
+        assert_eq!(
+            vec![to_symbols(&["A", "B"]), to_symbols(&["C", "D"]), to_symbols(&["E"])],
+            overflow
+        );
+    }
+
+    #[test]
+    fn test_allocate_symbols_unlimited() {
+        let symbols = to_symbols(&["A", "B"]);
+        let (assigned, overflow) = allocate_symbols(&[1024], usize::MAX, 1, &symbols);
+        assert_eq!(vec![symbols], assigned);
+        assert!(overflow.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_capture_commands() {
+        let symbols = to_symbols(&["BTCUSDT", "ETHUSDT"]);
+        let commands = capture_commands("binance", MarketType::Spot, move |ws_client| {
+            subscribe_with_lock(
+                "binance".to_string(),
+                MarketType::Spot,
+                MessageType::Trade,
+                symbols,
+                ws_client,
+            )
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            vec![
+                r#"{"id":9527,"method":"SUBSCRIBE","params":["btcusdt@aggTrade","ethusdt@aggTrade"]}"#
+            ],
+            commands
+        );
+
+        let err = capture_commands("binance", MarketType::Spot, move |ws_client| async move {
+            ws_client.subscribe_l3_orderbook(&[]).await
+        })
+        .await
+        .unwrap_err();
+        assert!(err.0.contains("level3"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_block_stops_reading_when_consumer_stalls() {
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        const NUM_FRAMES: usize = 100_000; // about 100MB in total
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let num_sent = Arc::new(AtomicUsize::new(0));
+        let num_sent_clone = num_sent.clone();
+        let server = tokio::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let frame = format!(
+                r#"{{"stream":"btcusdt@aggTrade","data":{{"e":"aggTrade","E":1676019600000,"s":"BTCUSDT","a":1,"p":"21800.00","q":"0.001","f":1,"l":1,"T":1676019600000,"m":true,"M":true}},"padding":"{}"}}"#,
+                "x".repeat(1024)
             );
-        }
-        ws_client.run().await;
-        ws_client.close().await;
-    } else {
-        // split to chunks
-        let mut chunks: Vec<Vec<(String, usize)>> = Vec::new();
-        {
-            for i in (0..symbol_interval_list.len()).step_by(num_topics_per_connection) {
-                let chunk: Vec<(String, usize)> = symbol_interval_list
-                    [i..(std::cmp::min(i + num_topics_per_connection, symbol_interval_list.len()))]
-                    .to_vec();
-                chunks.push(chunk);
-            }
-        }
-        debug!("{} {} {}", symbol_interval_list.len(), num_topics_per_connection, chunks.len(),);
-        assert!(chunks.len() > 1);
-
-        let mut last_ws_client = None;
-        let mut handles = Vec::new();
-        {
-            let n = chunks.len();
-            for (i, chunk) in chunks.into_iter().enumerate() {
-                last_ws_client = if i == (n - 1) {
-                    let tx_clone = tx.clone();
-                    Some(
-                        create_ws_client(exchange, market_type, MessageType::Candlestick, tx_clone)
-                            .await,
-                    )
-                } else {
-                    None
-                };
-                let ret = crawl_candlestick_one_chunk(
-                    exchange.to_string(),
-                    market_type,
-                    last_ws_client.clone(),
-                    chunk,
-                    tx.clone(),
-                );
-                handles.push(ret.await);
+            for _ in 0..NUM_FRAMES {
+                if ws_stream.send(WsMessage::Text(frame.clone())).await.is_err() {
+                    break;
+                }
+                num_sent_clone.fetch_add(1, Ordering::SeqCst);
             }
-            drop(tx);
-        }
-        if automatic_symbol_discovery && last_ws_client.is_some() {
-            create_new_symbol_receiver_thread_candlestick(
-                real_intervals,
-                rx_symbols,
-                last_ws_client.unwrap(),
-            );
-        }
-        for handle in handles {
-            if let Err(err) = handle.await {
-                panic!("{}", err); // TODO: use tokio::task::JoinSet or futures::stream::FuturesUnordered
+        });
+
+        let (tx, rx) = bounded_channel(8, OverflowPolicy::Block);
+        let ws_client =
+            create_ws_client_internal("binance", MarketType::Spot, unused_channel(), Some(&url))
+                .await;
+        let crawler = tokio::task::spawn(async move {
+            run_ws_client(ws_client.as_ref(), "binance", MarketType::Spot, MessageType::Trade, tx)
+                .await;
+        });
+
+        // nobody consumes rx, so the crawler stops reading and the server
+        // gets blocked by TCP flow control
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(8, rx.len());
+        assert!(num_sent.load(Ordering::SeqCst) < NUM_FRAMES);
+
+        // the crawler exits once the receiver has been dropped
+        drop(rx);
+        tokio::time::timeout(Duration::from_secs(5), crawler).await.unwrap().unwrap();
+        server.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_closes_connections() {
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscribed_tx, subscribed_rx) = tokio::sync::oneshot::channel();
+        let server = tokio::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut subscribed_tx = Some(subscribed_tx);
+            while let Some(Ok(msg)) = ws_stream.next().await {
+                match msg {
+                    WsMessage::Text(_) => _ = subscribed_tx.take().map(|tx| tx.send(())),
+                    WsMessage::Close(_) => break,
+                    _ => (),
+                }
             }
-        }
-    };
-    _ = stop_ch_tx.send(EmptyStruct {});
-    if let Some(thread) = symbol_discovery_thread {
-        _ = thread.await;
+        });
+
+        let (tx, _rx) = bounded_channel(8, OverflowPolicy::Block);
+        let policy = RestartPolicy::default();
+        let crawl = tokio::task::spawn(async move {
+            supervise("test_cancel_closes_connections", &policy, move || {
+                let (url, tx) = (url.clone(), tx.clone());
+                async move {
+                    let ws_client = create_ws_client_internal(
+                        "binance",
+                        MarketType::Spot,
+                        unused_channel(),
+                        Some(&url),
+                    )
+                    .await;
+                    let mut connections = tokio::task::JoinSet::new();
+                    crawl_event_one_chunk(
+                        "binance".to_string(),
+                        MarketType::Spot,
+                        MessageType::Trade,
+                        ws_client,
+                        vec!["BTCUSDT".to_string()],
+                        tx,
+                        &mut connections,
+                    );
+                    let (stop_ch_tx, _) = tokio::sync::broadcast::channel(1);
+                    join_connections(connections, stop_ch_tx, None).await;
+                }
+            })
+            .await;
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), subscribed_rx).await.unwrap().unwrap();
+        crawl.abort();
+        // the server sees the connection closed
+        tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
     }
 }
@@ -1,34 +1,18 @@
-use std::sync::mpsc::Sender;
-
-use crate::{crawlers::utils::crawl_event, msg::Message};
+use crate::buffer::MessageSender;
 use crypto_market_type::MarketType;
 use crypto_msg_type::MessageType;
 use crypto_ws_client::*;
 
-use super::utils::create_conversion_thread;
+use super::utils::{run_ws_client, unused_channel};
 
 const EXCHANGE_NAME: &str = "zb";
 
-pub(crate) async fn crawl_ticker(
-    market_type: MarketType,
-    symbols: Option<&[String]>,
-    tx: Sender<Message>,
-) {
-    if market_type == MarketType::LinearSwap && (symbols.is_none() || symbols.unwrap().is_empty()) {
-        let tx = create_conversion_thread(
-            EXCHANGE_NAME.to_string(),
-            MessageType::Ticker,
-            market_type,
-            tx,
-        );
-        let commands: Vec<String> =
-            vec![r#"{"action": "subscribe","channel": "All.Ticker"}"#.to_string()];
+// All tickers of the swap market
+pub(crate) async fn crawl_ticker(market_type: MarketType, tx: MessageSender) {
+    let commands: Vec<String> =
+        vec![r#"{"action": "subscribe","channel": "All.Ticker"}"#.to_string()];
 
-        let ws_client = ZbSwapWSClient::new(tx, None).await;
-        ws_client.send(&commands).await;
-        ws_client.run().await;
-        ws_client.close().await;
-    } else {
-        crawl_event(EXCHANGE_NAME, MessageType::Ticker, market_type, symbols, tx).await;
-    }
+    let ws_client = ZbSwapWSClient::new(unused_channel(), None).await;
+    ws_client.send(&commands).await;
+    run_ws_client(&ws_client, EXCHANGE_NAME, market_type, MessageType::Ticker, tx).await;
 }
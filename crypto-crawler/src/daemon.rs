@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use log::*;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::{
+    buffer::MessageSender,
+    crawlers::check_msg_type,
+    error::{Error, Result},
+    output::{OutputConfig, OutputWriter},
+    supervisor::{supervise, RestartPolicy},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKeyword {
+    All,
+}
+
+/// Which symbols to crawl, `all` or a list of symbols.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SymbolSelector {
+    Keyword(SymbolKeyword),
+    List(Vec<String>),
+}
+
+impl Default for SymbolSelector {
+    fn default() -> Self {
+        SymbolSelector::Keyword(SymbolKeyword::All)
+    }
+}
+
+impl SymbolSelector {
+    /// Returns the symbols to pass to `crawl_*()` functions.
+    pub fn symbols(&self) -> Option<&[String]> {
+        match self {
+            SymbolSelector::Keyword(SymbolKeyword::All) => None,
+            SymbolSelector::List(symbols) => Some(symbols),
+        }
+    }
+}
+
+/// A crawl job.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobConfig {
+    /// Unique name of the job
+    pub name: String,
+    pub exchange: String,
+    pub market_type: MarketType,
+    pub msg_types: Vec<MessageType>,
+    #[serde(default)]
+    pub symbols: SymbolSelector,
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+/// Jobs run by `Daemon`, in YAML or TOML.
+///
+/// ```yaml
+/// jobs:
+///   - name: binance-spot
+///     exchange: binance
+///     market_type: spot
+///     msg_types: [trade, bbo]
+///     symbols: [BTCUSDT, ETHUSDT]
+///     output:
+///       type: file
+///       dir: ./data
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    pub jobs: Vec<JobConfig>,
+}
+
+impl DaemonConfig {
+    /// Loads a `.yaml`, `.yml` or `.toml` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let config: DaemonConfig = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => {
+                serde_yaml::from_str(&text).map_err(|err| Error(err.to_string()))?
+            }
+            Some("toml") => toml::from_str(&text).map_err(|err| Error(err.to_string()))?,
+            _ => return Err(Error(format!("Unsupported config file {}", path.display()))),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for job in self.jobs.iter() {
+            if !names.insert(job.name.as_str()) {
+                return Err(Error(format!("Duplicated job name {}", job.name)));
+            }
+            if job.msg_types.is_empty() {
+                return Err(Error(format!("Job {} has no msg_types", job.name)));
+            }
+            for msg_type in job.msg_types.iter() {
+                check_msg_type(&job.exchange, job.market_type, *msg_type)
+                    .map_err(|err| Error(format!("Job {}, {}", job.name, err)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct RunningJob {
+    config: JobConfig,
+    tasks: Vec<JoinHandle<()>>,
+    // closes the sender of the job, which aborting tasks can NOT do for
+    // crawls running in blocking threads
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Release);
+        for task in self.tasks.iter() {
+            task.abort();
+        }
+    }
+}
+
+/// Runs crawl jobs in one process.
+///
+/// Crawls which panic or exit are restarted by `supervise()` according to the
+/// `restart` policy of each job. Jobs sharing the same output share one
+/// `OutputWriter`.
+#[derive(Default)]
+pub struct Daemon {
+    jobs: HashMap<String, RunningJob>,
+    outputs: HashMap<OutputConfig, OutputWriter>,
+}
+
+impl Daemon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts, stops and restarts jobs to match `config`, unchanged jobs keep
+    /// running.
+    ///
+    /// Must be called within a tokio runtime. Crawls of RESTful APIs run in
+    /// blocking threads, a stopped job closes its sender so that they exit
+    /// before the next request.
+    pub fn apply(&mut self, config: DaemonConfig) -> Result<()> {
+        config.validate()?;
+        let mut configs: HashMap<String, JobConfig> =
+            config.jobs.into_iter().map(|job| (job.name.clone(), job)).collect();
+
+        let stale: Vec<String> = self
+            .jobs
+            .iter()
+            .filter(|(name, job)| configs.get(*name) != Some(&job.config))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in stale {
+            info!("Stopping job {}", name);
+            self.jobs.remove(&name);
+        }
+        configs.retain(|name, _| !self.jobs.contains_key(name));
+
+        let mut configs: Vec<JobConfig> = configs.into_values().collect();
+        configs.sort_by(|a, b| a.name.cmp(&b.name));
+        for job in configs {
+            info!("Starting job {}", job.name);
+            self.start(job)?;
+        }
+        self.close_unused_outputs();
+        Ok(())
+    }
+
+    /// Names of running jobs.
+    pub fn job_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.jobs.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Stops all jobs and closes all outputs.
+    pub fn shutdown(&mut self) {
+        self.jobs.clear();
+        self.close_unused_outputs();
+    }
+
+    fn start(&mut self, config: JobConfig) -> Result<()> {
+        if !self.outputs.contains_key(&config.output) {
+            let writer = OutputWriter::start(&config.output)?;
+            self.outputs.insert(config.output.clone(), writer);
+        }
+        let (tx, cancelled) = MessageSender::cancellable(self.outputs[&config.output].sender());
+        let tasks = config
+            .msg_types
+            .iter()
+            .map(|msg_type| {
+                let tx = tx.clone();
+                let (name, exchange, market_type, msg_type, symbols) = (
+                    config.name.clone(),
+                    config.exchange.clone(),
+                    config.market_type,
+                    *msg_type,
+                    config.symbols.symbols().map(|symbols| symbols.to_vec()),
+                );
+                let policy = config.restart.clone();
+                tokio::task::spawn(async move {
+                    let task_name = format!("{name} {msg_type}");
+                    supervise(&task_name, &policy, move || {
+                        let (exchange, symbols, tx) =
+                            (exchange.clone(), symbols.clone(), tx.clone());
+                        async move {
+                            crate::crawl(&exchange, market_type, msg_type, symbols.as_deref(), tx)
+                                .await
+                        }
+                    })
+                    .await;
+                })
+            })
+            .collect();
+        self.jobs.insert(config.name.clone(), RunningJob { config, tasks, cancelled });
+        Ok(())
+    }
+
+    fn close_unused_outputs(&mut self) {
+        let used: Vec<&OutputConfig> = self.jobs.values().map(|job| &job.config.output).collect();
+        let unused: Vec<OutputConfig> =
+            self.outputs.keys().filter(|output| !used.contains(output)).cloned().collect();
+        for output in unused {
+            if let Err(err) = self.outputs.remove(&output).unwrap().stop() {
+                error!("Failed to close {:?}, {}", output, err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "
+jobs:
+  - name: binance-spot
+    exchange: binance
+    market_type: spot
+    msg_types: [trade, bbo]
+    symbols: [BTCUSDT, ETHUSDT]
+    output:
+      type: stdout
+  - name: okx-swap
+    exchange: okx
+    market_type: linear_swap
+    msg_types: [l2_event]
+    symbols: all
+    output:
+      type: file
+      dir: ./data
+";
+
+    const TOML: &str = r#"
+[[jobs]]
+name = "binance-spot"
+exchange = "binance"
+market_type = "spot"
+msg_types = ["trade", "bbo"]
+symbols = ["BTCUSDT", "ETHUSDT"]
+output = { type = "stdout" }
+
+[[jobs]]
+name = "okx-swap"
+exchange = "okx"
+market_type = "linear_swap"
+msg_types = ["l2_event"]
+output = { type = "file", dir = "./data" }
+"#;
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir();
+        let yaml_path = dir.join(format!("daemon_{}.yaml", std::process::id()));
+        let toml_path = dir.join(format!("daemon_{}.toml", std::process::id()));
+        std::fs::write(&yaml_path, YAML).unwrap();
+        std::fs::write(&toml_path, TOML).unwrap();
+        let config = DaemonConfig::load(&yaml_path).unwrap();
+        assert_eq!(config, DaemonConfig::load(&toml_path).unwrap());
+        std::fs::remove_file(&yaml_path).unwrap();
+        std::fs::remove_file(&toml_path).unwrap();
+
+        assert_eq!(2, config.jobs.len());
+        assert_eq!(vec![MessageType::Trade, MessageType::BBO], config.jobs[0].msg_types);
+        assert_eq!(
+            Some(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()].as_slice()),
+            config.jobs[0].symbols.symbols()
+        );
+        assert_eq!(None, config.jobs[1].symbols.symbols());
+        assert_eq!(
+            OutputConfig::File { dir: "./data".into(), compression: crate::Compression::Gzip },
+            config.jobs[1].output
+        );
+
+        let mut duplicated = config.clone();
+        duplicated.jobs[1].name = "binance-spot".to_string();
+        assert!(duplicated.validate().is_err());
+
+        let mut unsupported = config.clone();
+        unsupported.jobs[1].msg_types = vec![MessageType::FundingRate];
+        unsupported.jobs[1].exchange = "kraken".to_string();
+        assert!(unsupported.validate().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply() {
+        let job = |name: &str, exchange: &str| JobConfig {
+            name: name.to_string(),
+            exchange: exchange.to_string(),
+            market_type: MarketType::Spot,
+            msg_types: vec![MessageType::Trade],
+            symbols: SymbolSelector::List(vec!["BTCUSDT".to_string()]),
+            output: OutputConfig::Stdout,
+            restart: RestartPolicy::default(),
+        };
+        let mut daemon = Daemon::new();
+        daemon.apply(DaemonConfig { jobs: vec![job("a", "binance"), job("b", "okx")] }).unwrap();
+        assert_eq!(vec!["a", "b"], daemon.job_names());
+        let task_id = daemon.jobs["a"].tasks[0].id();
+
+        daemon.apply(DaemonConfig { jobs: vec![job("a", "binance"), job("c", "okx")] }).unwrap();
+        assert_eq!(vec!["a", "c"], daemon.job_names());
+        // unchanged jobs keep running
+        assert_eq!(task_id, daemon.jobs["a"].tasks[0].id());
+
+        let cancelled = daemon.jobs["a"].cancelled.clone();
+        assert!(!cancelled.load(Ordering::Acquire));
+        daemon.apply(DaemonConfig { jobs: vec![job("a", "huobi")] }).unwrap();
+        assert_eq!(vec!["a"], daemon.job_names());
+        // the sender of a stopped job is closed
+        assert!(cancelled.load(Ordering::Acquire));
+        assert_ne!(task_id, daemon.jobs["a"].tasks[0].id());
+
+        daemon.shutdown();
+        assert!(daemon.outputs.is_empty());
+    }
+}
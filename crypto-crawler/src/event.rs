@@ -0,0 +1,58 @@
+use crypto_market_type::MarketType;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Symbol events detected by the automatic symbol discovery task.
+///
+/// Automatic symbol discovery is enabled when `symbols` is None or empty.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SymbolEvent {
+    /// A new symbol has been listed and subscribed.
+    Listed { exchange: String, market_type: MarketType, symbol: String },
+    /// A symbol has disappeared from the exchange and has been unsubscribed.
+    Delisted { exchange: String, market_type: MarketType, symbol: String },
+}
+
+static SYMBOL_EVENTS: Lazy<broadcast::Sender<SymbolEvent>> =
+    Lazy::new(|| broadcast::channel(1024).0);
+
+/// Subscribe to symbol listing and delisting events of all running crawlers.
+///
+/// Only events emitted after this call are received.
+pub fn subscribe_symbol_events() -> broadcast::Receiver<SymbolEvent> {
+    SYMBOL_EVENTS.subscribe()
+}
+
+pub(crate) fn emit_symbol_event(event: SymbolEvent) {
+    // an error means there is no subscriber, which is fine
+    _ = SYMBOL_EVENTS.send(event);
+}
+
+/// Events emitted by `supervise()` when a supervised task fails.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SupervisorEvent {
+    /// A task has panicked or exited.
+    Failed { task: String, reason: String },
+    /// A task will be restarted after `delay_ms` milliseconds.
+    Restarting { task: String, attempt: u32, delay_ms: u64 },
+    /// A task has been restarted too many times and won't be restarted again.
+    GaveUp { task: String, restarts: usize },
+}
+
+static SUPERVISOR_EVENTS: Lazy<broadcast::Sender<SupervisorEvent>> =
+    Lazy::new(|| broadcast::channel(1024).0);
+
+/// Subscribe to failures and restarts of all supervised tasks.
+///
+/// Only events emitted after this call are received.
+pub fn subscribe_supervisor_events() -> broadcast::Receiver<SupervisorEvent> {
+    SUPERVISOR_EVENTS.subscribe()
+}
+
+pub(crate) fn emit_supervisor_event(event: SupervisorEvent) {
+    // an error means there is no subscriber, which is fine
+    _ = SUPERVISOR_EVENTS.send(event);
+}
@@ -0,0 +1,437 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{TimeZone, Utc};
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use flate2::{bufread::MultiGzDecoder, write::GzEncoder};
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::Message;
+
+// Suffix of in-progress files
+const PART_SUFFIX: &str = ".part";
+
+// Suffix of files being committed
+const TMP_SUFFIX: &str = ".tmp";
+
+// How often to rotate and flush in-progress files
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Input size per read when decompressing a .part file
+const RECOVER_CHUNK_SIZE: usize = 4096;
+
+/// Compression algorithm of files written by `FileSink`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zst",
+        }
+    }
+
+    // Both gzip members and zstd frames can be concatenated.
+    fn encoder(&self, file: File) -> io::Result<Encoder> {
+        let writer = BufWriter::new(file);
+        match self {
+            Compression::Gzip => {
+                Ok(Encoder::Gzip(GzEncoder::new(writer, flate2::Compression::default())))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Encoder::Zstd(zstd::Encoder::new(writer, 0)?)),
+        }
+    }
+
+    // Decompresses as much data as possible, a truncated tail is ignored.
+    fn decode_lossy(&self, path: &Path) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) => {
+                error!("Failed to open {}, {}", path.display(), err);
+                return buf;
+            }
+        };
+        // feed the decoder in small chunks, so that data before a corrupted
+        // tail is returned before the error, and kept in buf
+        let reader = BufReader::with_capacity(RECOVER_CHUNK_SIZE, file);
+        let result = match self {
+            Compression::Gzip => MultiGzDecoder::new(reader).read_to_end(&mut buf),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => match zstd::Decoder::with_buffer(reader) {
+                Ok(mut decoder) => decoder.read_to_end(&mut buf),
+                Err(err) => Err(err),
+            },
+        };
+        if let Err(err) = result {
+            warn!("{} is truncated, {}", path.display(), err);
+        }
+        buf
+    }
+}
+
+enum Encoder {
+    Gzip(GzEncoder<BufWriter<File>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Encoder {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::Gzip(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder,
+        }
+    }
+
+    fn finish(self) -> io::Result<File> {
+        let writer = match self {
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.into_inner().map_err(|err| err.into_error())
+    }
+}
+
+// An in-progress file of one partition
+struct PartFile {
+    hour: String,
+    path: PathBuf, // final path, without the .part suffix
+    encoder: Encoder,
+}
+
+/// Writes messages to compressed TSV files, rotated hourly.
+///
+/// Files are partitioned as
+/// `root_dir/exchange/market_type/msg_type/YYYY-MM-DD-HH.tsv.gz`, each line
+/// is a `Message::to_tsv_string()`. The hour comes from `received_at` in UTC.
+///
+/// Messages are written to a `.part` file first, which is fsynced and renamed
+/// to its final name on rotation. `.part` files left behind by a crash are
+/// recovered by `FileSink::new()`, keeping all complete lines.
+pub struct FileSink {
+    root_dir: PathBuf,
+    compression: Compression,
+    files: HashMap<(String, MarketType, MessageType), PartFile>,
+}
+
+impl FileSink {
+    /// Creates a sink writing to `root_dir`, and finalizes in-progress files
+    /// left by a previous run.
+    pub fn new(root_dir: impl AsRef<Path>, compression: Compression) -> io::Result<Self> {
+        let root_dir = root_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&root_dir)?;
+        let sink = FileSink { root_dir, compression, files: HashMap::new() };
+        sink.recover(&sink.root_dir)?;
+        Ok(sink)
+    }
+
+    /// Writes a message, rotating the file of its partition if the hour has
+    /// changed.
+    pub fn write(&mut self, msg: &Message) -> io::Result<()> {
+        let key = (msg.exchange.clone(), msg.market_type, msg.msg_type);
+        let hour = to_hour(msg.received_at);
+        // late messages of the previous hour go to the current file
+        if self.files.get(&key).map(|f| f.hour < hour).unwrap_or(false) {
+            let part_file = self.files.remove(&key).unwrap();
+            Self::finalize(part_file)?;
+        }
+        if !self.files.contains_key(&key) {
+            let part_file = self.open(msg, hour)?;
+            self.files.insert(key.clone(), part_file);
+        }
+        let part_file = self.files.get_mut(&key).unwrap();
+        let writer = part_file.encoder.writer();
+        writer.write_all(msg.to_tsv_string().as_bytes())?;
+        writer.write_all(b"\n")
+    }
+
+    /// Finalizes files whose hour is earlier than the hour of `now`, which is
+    /// a Unix timestamp in milliseconds.
+    pub fn rotate(&mut self, now: u64) -> io::Result<()> {
+        let hour = to_hour(now);
+        let expired: Vec<_> =
+            self.files.iter().filter(|(_, f)| f.hour < hour).map(|(k, _)| k.clone()).collect();
+        for key in expired {
+            let part_file = self.files.remove(&key).unwrap();
+            Self::finalize(part_file)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes all in-progress files.
+    pub fn flush(&mut self) -> io::Result<()> {
+        for part_file in self.files.values_mut() {
+            part_file.encoder.writer().flush()?;
+        }
+        Ok(())
+    }
+
+    /// Consumes messages until all senders have been dropped, then finalizes
+    /// all files.
+    pub fn run(mut self, rx: Receiver<Message>) -> io::Result<()> {
+        let mut last_flush = Instant::now();
+        loop {
+            match rx.recv_timeout(FLUSH_INTERVAL.saturating_sub(last_flush.elapsed())) {
+                Ok(msg) => self.write(&msg)?,
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            // checked on every message, a busy receiver never times out
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                self.rotate(now_millis())?;
+                self.flush()?;
+                last_flush = Instant::now();
+            }
+        }
+        self.close()
+    }
+
+    /// Finalizes all files.
+    pub fn close(mut self) -> io::Result<()> {
+        for (_, part_file) in self.files.drain() {
+            Self::finalize(part_file)?;
+        }
+        Ok(())
+    }
+
+    fn open(&self, msg: &Message, hour: String) -> io::Result<PartFile> {
+        let dir = self
+            .root_dir
+            .join(&msg.exchange)
+            .join(msg.market_type.to_string())
+            .join(msg.msg_type.to_string());
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.tsv.{}", hour, self.compression.extension()));
+        let part_path = to_part_path(&path);
+        if part_path.exists() {
+            // left by a crash while this sink was running
+            self.recover_file(&part_path)?;
+        }
+        let file = File::create(&part_path)?;
+        let encoder = self.compression.encoder(file)?;
+        Ok(PartFile { hour, path, encoder })
+    }
+
+    fn finalize(part_file: PartFile) -> io::Result<()> {
+        part_file.encoder.finish()?.sync_all()?;
+        commit(&to_part_path(&part_file.path), &part_file.path)
+    }
+
+    // Finalizes all .part files under `dir` recursively.
+    fn recover(&self, dir: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.recover(&path)?;
+            } else if path.extension() == Some(OsStr::new(&PART_SUFFIX[1..])) {
+                self.recover_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Re-compresses complete lines of a .part file and commits it.
+    fn recover_file(&self, part_path: &Path) -> io::Result<()> {
+        warn!("Recovering {}", part_path.display());
+        let mut buf = self.compression.decode_lossy(part_path);
+        let len = buf.iter().rposition(|b| *b == b'\n').map(|i| i + 1).unwrap_or(0);
+        buf.truncate(len);
+
+        let path = part_path.with_extension("");
+        if !buf.is_empty() {
+            let mut tmp_path = path.clone().into_os_string();
+            tmp_path.push(".recovered");
+            let tmp_path = PathBuf::from(tmp_path);
+            let mut encoder = self.compression.encoder(File::create(&tmp_path)?)?;
+            encoder.writer().write_all(&buf)?;
+            encoder.finish()?.sync_all()?;
+            commit(&tmp_path, &path)?;
+        }
+        // a crash before this line duplicates lines instead of losing them
+        fs::remove_file(part_path)
+    }
+}
+
+fn to_part_path(path: &Path) -> PathBuf {
+    with_suffix(path, PART_SUFFIX)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_os_string();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+// Moves a fsynced file to `to`, appending to it if it already exists.
+//
+// Appending writes both files to a temporary file which then replaces `to`,
+// so a crash leaves either the old or the new `to`, never a partially
+// appended one.
+fn commit(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        let tmp_path = with_suffix(to, TMP_SUFFIX);
+        let mut dst = File::create(&tmp_path)?;
+        io::copy(&mut File::open(to)?, &mut dst)?;
+        io::copy(&mut File::open(from)?, &mut dst)?;
+        dst.sync_all()?;
+        fs::rename(&tmp_path, to)?;
+        sync_parent(to)?;
+        // a crash before this line duplicates lines instead of losing them
+        fs::remove_file(from)?;
+    } else {
+        fs::rename(from, to)?;
+    }
+    // make the rename durable
+    sync_parent(to)
+}
+
+fn sync_parent(path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn to_hour(timestamp: u64) -> String {
+    Utc.timestamp_millis_opt(timestamp as i64).unwrap().format("%Y-%m-%d-%H").to_string()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("file_sink_{}_{}", name, std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn new_msg(received_at: u64, json: &str) -> Message {
+        let mut msg = Message::new(
+            "binance".to_string(),
+            MarketType::Spot,
+            MessageType::Trade,
+            json.to_string(),
+        );
+        msg.received_at = received_at;
+        msg
+    }
+
+    fn read_lines(path: &Path) -> Vec<String> {
+        let buf = Compression::Gzip.decode_lossy(path);
+        String::from_utf8(buf).unwrap().lines().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rotate_hourly() {
+        let dir = new_dir("rotate");
+        let (tx, rx) = std::sync::mpsc::channel();
+        // 2023-02-10 08:59:59.999 and 09:00:00.000 UTC
+        tx.send(new_msg(1676019599999, "{}")).unwrap();
+        tx.send(new_msg(1676019600000, "[]")).unwrap();
+        drop(tx);
+        FileSink::new(&dir, Compression::Gzip).unwrap().run(rx).unwrap();
+
+        let partition = dir.join("binance").join("spot").join("trade");
+        assert_eq!(
+            vec!["1676019599999\t\t{}"],
+            read_lines(&partition.join("2023-02-10-08.tsv.gz"))
+        );
+        assert_eq!(
+            vec!["1676019600000\t\t[]"],
+            read_lines(&partition.join("2023-02-10-09.tsv.gz"))
+        );
+        assert_eq!(2, fs::read_dir(&partition).unwrap().count());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_while_busy() {
+        let dir = new_dir("busy");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sink = FileSink::new(&dir, Compression::Gzip).unwrap();
+        let handle = std::thread::spawn(move || sink.run(rx).unwrap());
+        // messages of a past hour keep arriving every 10ms
+        let path = dir.join("binance").join("spot").join("trade").join("2023-02-10-09.tsv.gz");
+        let deadline = Instant::now() + FLUSH_INTERVAL * 3;
+        while !path.exists() && Instant::now() < deadline {
+            tx.send(new_msg(1676019600000, "{}")).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // finalized before the sender is dropped
+        assert!(path.exists());
+        drop(tx);
+        handle.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recover() {
+        let dir = new_dir("recover");
+        let partition = dir.join("binance").join("spot").join("trade");
+
+        // a complete file of the same hour written by a previous run
+        let mut sink = FileSink::new(&dir, Compression::Gzip).unwrap();
+        sink.write(&new_msg(1676019600000, "1")).unwrap();
+        sink.close().unwrap();
+
+        // simulate a crash
+        let mut sink = FileSink::new(&dir, Compression::Gzip).unwrap();
+        sink.write(&new_msg(1676019600001, "2")).unwrap();
+        sink.flush().unwrap();
+        std::mem::forget(sink);
+        let part_path = partition.join("2023-02-10-09.tsv.gz.part");
+        // a torn write
+        let bytes = fs::read(&part_path).unwrap();
+        fs::write(&part_path, &bytes[..bytes.len() - 3]).unwrap();
+
+        FileSink::new(&dir, Compression::Gzip).unwrap();
+        assert!(!part_path.exists());
+        assert_eq!(
+            vec!["1676019600000\t\t1", "1676019600001\t\t2"],
+            read_lines(&partition.join("2023-02-10-09.tsv.gz"))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_commit_append() {
+        let dir = new_dir("commit");
+        fs::create_dir_all(&dir).unwrap();
+        let (from, to) = (dir.join("a.part"), dir.join("a"));
+        fs::write(&to, "1").unwrap();
+        fs::write(&from, "2").unwrap();
+        // left by a crash during a previous commit
+        fs::write(with_suffix(&to, TMP_SUFFIX), "garbage").unwrap();
+
+        commit(&from, &to).unwrap();
+        assert_eq!("12", fs::read_to_string(&to).unwrap());
+        assert!(!from.exists());
+        assert!(!with_suffix(&to, TMP_SUFFIX).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
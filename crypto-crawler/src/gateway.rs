@@ -0,0 +1,265 @@
+use std::{io, net::SocketAddr, sync::Arc};
+
+use futures_util::{SinkExt, StreamExt};
+use log::*;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::broadcast::{self, error::RecvError},
+};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+use crate::msg::{Message, MessageFilter};
+
+// A message and its JSON representation, serialized once for all clients
+struct Item {
+    msg: Message,
+    text: String,
+}
+
+// Subscription state of a client
+#[derive(Default)]
+struct Session {
+    filters: Option<Vec<MessageFilter>>, // None before the first request
+}
+
+impl Session {
+    // Handles a subscription request, returns the reply.
+    fn on_request(&mut self, request: &str) -> String {
+        match serde_json::from_str::<Vec<MessageFilter>>(request) {
+            Ok(filters) => {
+                let reply = serde_json::json!({ "subscribed": filters }).to_string();
+                self.filters = Some(filters);
+                reply
+            }
+            Err(err) => serde_json::json!({ "error": err.to_string() }).to_string(),
+        }
+    }
+
+    fn accepts(&self, msg: &Message) -> bool {
+        match self.filters.as_ref() {
+            Some(filters) => filters.is_empty() || filters.iter().any(|f| f.matches(msg)),
+            None => false,
+        }
+    }
+}
+
+/// Fans out messages to local clients over TCP and WebSocket.
+///
+/// A client sends a JSON array of `MessageFilter`s to subscribe, an empty
+/// array means all messages, and a new array replaces the previous one. The
+/// gateway replies with `{"subscribed":[...]}` or `{"error":"..."}`, then
+/// sends matching messages in JSON, one per line over TCP, or one per text
+/// frame over WebSocket.
+///
+/// Clients which are too slow skip messages instead of blocking others.
+#[derive(Clone)]
+pub struct Gateway {
+    tx: broadcast::Sender<Arc<Item>>,
+}
+
+impl Gateway {
+    /// Creates a gateway, `capacity` is the number of messages buffered for
+    /// each client.
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Gateway { tx }
+    }
+
+    /// Sends a message to all subscribed clients.
+    ///
+    /// `symbol` is extracted if absent, so that clients can filter by symbol.
+    pub fn publish(&self, msg: Message) {
+        if self.tx.receiver_count() > 0 {
+            let msg = msg.with_extracted_symbol();
+            let text = msg.to_string();
+            // fails only if all clients have disconnected
+            _ = self.tx.send(Arc::new(Item { msg, text }));
+        }
+    }
+
+    /// Publishes messages from a crawl until all senders have been dropped.
+    ///
+    /// `rx` is a `std::sync::mpsc::Receiver`, a `MessageReceiver` or a
+    /// `ConflatingReceiver`.
+    pub fn forward(&self, rx: impl IntoIterator<Item = Message>) {
+        for msg in rx {
+            self.publish(msg);
+        }
+    }
+
+    /// Serves clients over TCP.
+    pub async fn serve_tcp(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, addr) = listener.accept().await?;
+            let rx = self.tx.subscribe();
+            tokio::task::spawn(async move {
+                info!("TCP client {} connected", addr);
+                if let Err(err) = handle_tcp(stream, rx).await {
+                    warn!("TCP client {}, {}", addr, err);
+                }
+                info!("TCP client {} disconnected", addr);
+            });
+        }
+    }
+
+    /// Serves clients over WebSocket.
+    pub async fn serve_ws(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, addr) = listener.accept().await?;
+            let rx = self.tx.subscribe();
+            tokio::task::spawn(async move {
+                info!("WebSocket client {} connected", addr);
+                if let Err(err) = handle_ws(stream, addr, rx).await {
+                    warn!("WebSocket client {}, {}", addr, err);
+                }
+                info!("WebSocket client {} disconnected", addr);
+            });
+        }
+    }
+}
+
+async fn handle_tcp(stream: TcpStream, mut rx: broadcast::Receiver<Arc<Item>>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut session = Session::default();
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) => {
+                    let reply = session.on_request(&line);
+                    writer.write_all(format!("{reply}\n").as_bytes()).await?;
+                }
+                None => return Ok(()),
+            },
+            item = rx.recv() => match item {
+                Ok(item) => {
+                    if session.accepts(&item.msg) {
+                        writer.write_all(format!("{}\n", item.text).as_bytes()).await?;
+                    }
+                }
+                Err(RecvError::Lagged(n)) => warn!("A TCP client skipped {} messages", n),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+async fn handle_ws(
+    stream: TcpStream,
+    addr: SocketAddr,
+    mut rx: broadcast::Receiver<Arc<Item>>,
+) -> io::Result<()> {
+    let to_io_error = io::Error::other;
+    let ws_stream = tokio_tungstenite::accept_async(stream).await.map_err(to_io_error)?;
+    let (mut writer, mut reader) = ws_stream.split();
+    let mut session = Session::default();
+    loop {
+        tokio::select! {
+            frame = reader.next() => match frame {
+                Some(Ok(WsMessage::Text(text))) => {
+                    let reply = session.on_request(&text);
+                    writer.send(WsMessage::Text(reply)).await.map_err(to_io_error)?;
+                }
+                Some(Ok(WsMessage::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => (), // pings are answered by tungstenite
+                Some(Err(err)) => return Err(to_io_error(err)),
+            },
+            item = rx.recv() => match item {
+                Ok(item) => {
+                    if session.accepts(&item.msg) {
+                        let frame = WsMessage::Text(item.text.clone());
+                        writer.send(frame).await.map_err(to_io_error)?;
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    warn!("WebSocket client {} skipped {} messages", addr, n)
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+
+    const REQUEST: &str = r#"[{"exchange":"binance","msg_type":"trade"}]"#;
+
+    fn new_msg(exchange: &str, json: &str) -> Message {
+        Message::new(exchange.to_string(), MarketType::Spot, MessageType::Trade, json.to_string())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tcp() {
+        let gateway = Gateway::new(16);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = gateway.clone();
+        tokio::task::spawn(async move { server.serve_tcp(listener).await });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"not json\n").await.unwrap();
+        assert!(lines.next_line().await.unwrap().unwrap().starts_with(r#"{"error":"#));
+        writer.write_all(format!("{REQUEST}\n").as_bytes()).await.unwrap();
+        assert!(lines.next_line().await.unwrap().unwrap().starts_with(r#"{"subscribed":"#));
+
+        gateway.publish(new_msg("okx", "1"));
+        gateway.publish(new_msg("binance", "2"));
+        let msg: Message =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!("binance", msg.exchange);
+        assert_eq!("2", msg.json);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ws() {
+        let gateway = Gateway::new(16);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = gateway.clone();
+        tokio::task::spawn(async move { server.serve_ws(listener).await });
+
+        let (mut ws_stream, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}")).await.unwrap();
+        ws_stream.send(WsMessage::Text(REQUEST.to_string())).await.unwrap();
+        let reply = ws_stream.next().await.unwrap().unwrap().into_text().unwrap();
+        assert!(reply.starts_with(r#"{"subscribed":"#));
+
+        gateway.publish(new_msg("okx", "1"));
+        gateway.publish(new_msg("binance", "2"));
+        let text = ws_stream.next().await.unwrap().unwrap().into_text().unwrap();
+        let msg: Message = serde_json::from_str(&text).unwrap();
+        assert_eq!("binance", msg.exchange);
+        assert_eq!("2", msg.json);
+    }
+
+    #[test]
+    fn test_forward() {
+        let gateway = Gateway::new(16);
+        let mut rx = gateway.tx.subscribe();
+        let (tx, buffered) = crate::bounded_channel(4, crate::OverflowPolicy::Block);
+        tx.send(new_msg("binance", "1")).unwrap();
+        drop(tx);
+        gateway.forward(buffered);
+        assert_eq!("1", rx.try_recv().unwrap().msg.json);
+    }
+
+    #[test]
+    fn test_accepts_symbol() {
+        let mut session = Session::default();
+        session.on_request(r#"[{"exchange":"binance","symbol":"BTCUSDT"}]"#);
+        let json = r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","E":1676019600000,"s":"BTCUSDT","a":1,"p":"21800.00","q":"0.001","f":1,"l":1,"T":1676019600000,"m":true,"M":true}}"#;
+        // as done by publish()
+        let msg = new_msg("binance", json).with_extracted_symbol();
+        assert_eq!(Some("BTCUSDT"), msg.symbol.as_deref());
+        assert!(session.accepts(&msg));
+        let json = json.replace("btcusdt", "ethusdt").replace("BTCUSDT", "ETHUSDT");
+        assert!(!session.accepts(&new_msg("binance", &json).with_extracted_symbol()));
+    }
+}
@@ -213,16 +213,111 @@
 //!     assert!(!messages.is_empty());
 //! }
 //! ```
+//!
+//! ## Bounded buffering
+//!
+//! `std::sync::mpsc::channel()` is unbounded, use `bounded_channel()` to limit
+//! memory usage when the consumer is slow.
+//!
+//! If only the latest state matters, `conflating_channel()` keeps only the
+//! newest message per symbol for `crawl_bbo()`, `crawl_ticker()` and
+//! `crawl_l2_topk()`.
+//!
+//! ```rust
+//! use crypto_crawler::{bounded_channel, crawl_trade, MarketType, OverflowPolicy};
+//!
+//! #[tokio::main(flavor = "multi_thread")]
+//! async fn main() {
+//!     let (tx, rx) = bounded_channel(10000, OverflowPolicy::DropOldest);
+//!     tokio::task::spawn(async move {
+//!         crawl_trade("binance", MarketType::InverseSwap, None, tx).await;
+//!     });
+//!
+//!     let mut messages = Vec::new();
+//!     for msg in rx {
+//!         messages.push(msg);
+//!         break;
+//!     }
+//!     assert!(!messages.is_empty());
+//! }
+//! ```
+//!
+//! ## Write to files
+//!
+//! `FileSink` writes messages to hourly rotated compressed files, partitioned
+//! by exchange, market type and message type. Enable the `zstd` feature for
+//! zstd compression.
+//!
+//! ```rust,no_run
+//! use crypto_crawler::{crawl_trade, Compression, FileSink, MarketType};
+//!
+//! #[tokio::main(flavor = "multi_thread")]
+//! async fn main() {
+//!     let (tx, rx) = std::sync::mpsc::channel();
+//!     tokio::task::spawn(async move {
+//!         crawl_trade("binance", MarketType::Spot, None, tx).await;
+//!     });
+//!
+//!     let sink = FileSink::new("./data", Compression::Gzip).unwrap();
+//!     std::thread::spawn(move || sink.run(rx)).join().unwrap().unwrap();
+//! }
+//! ```
+//!
+//! Use `replay()` or the `replay` binary to emit archived messages again,
+//! either as fast as possible or at the original pace.
+//!
+//! With the `parquet` feature, `ParquetSink` writes messages, and optionally
+//! parsed trades and level2 orderbooks, to Parquet files for analytics.
+//!
+//! For small captures, the `sqlite` feature provides `SqliteSink`, and
+//! `SqliteReader` to query messages by `MessageFilter` and time range.
+//!
+//! ## Fan out to local clients
+//!
+//! `Gateway` and the `gateway` binary serve crawled messages over TCP or
+//! WebSocket, clients subscribe with `MessageFilter`s.
+mod buffer;
+mod conflation;
 mod crawlers;
+mod daemon;
+mod error;
+mod event;
+mod file_sink;
+mod gateway;
 mod msg;
+mod output;
+#[cfg(feature = "parquet")]
+mod parquet_sink;
+mod plan;
+mod replay;
+#[cfg(feature = "sqlite")]
+mod sqlite_sink;
+mod supervisor;
 mod utils;
 
 use std::sync::mpsc::Sender;
 
+pub use buffer::{bounded_channel, MessageReceiver, MessageSender, OverflowPolicy};
+pub use conflation::{conflating_channel, ConflatingReceiver};
 pub use crawlers::fetch_symbols_retry;
 pub use crypto_market_type::MarketType;
 pub use crypto_msg_type::MessageType;
+pub use daemon::{Daemon, DaemonConfig, JobConfig, SymbolKeyword, SymbolSelector};
+pub use error::Error;
+pub use event::{
+    subscribe_supervisor_events, subscribe_symbol_events, SupervisorEvent, SymbolEvent,
+};
+pub use file_sink::{Compression, FileSink};
+pub use gateway::Gateway;
 pub use msg::*;
+pub use output::{OutputConfig, OutputWriter};
+#[cfg(feature = "parquet")]
+pub use parquet_sink::ParquetSink;
+pub use plan::{plan_candlestick, plan_crawl, ConnectionPlan, CrawlPlan};
+pub use replay::{replay, Pace};
+#[cfg(feature = "sqlite")]
+pub use sqlite_sink::{MessageIter, SqliteReader, SqliteSink};
+pub use supervisor::{supervise, RestartPolicy};
 pub use utils::get_hot_spot_symbols;
 
 /// Crawl realtime trades.
@@ -234,19 +329,10 @@ pub async fn crawl_trade(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "binance" => crawlers::binance::crawl_trade(market_type, symbols, tx).await,
-        "bitmex" => crawlers::bitmex::crawl_trade(market_type, symbols, tx).await,
-        "deribit" => crawlers::deribit::crawl_trade(market_type, symbols, tx).await,
-        "bitfinex" | "bitget" | "bithumb" | "bitstamp" | "bitz" | "bybit" | "coinbase_pro"
-        | "dydx" | "ftx" | "gate" | "huobi" | "kraken" | "kucoin" | "mexc" | "okx" | "zb"
-        | "zbg" => {
-            crawlers::crawl_event(exchange, MessageType::Trade, market_type, symbols, tx).await
-        }
-        _ => panic!("{exchange} does NOT have the trade websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::Trade, symbols, tx).await
 }
 
 /// Crawl level2 orderbook update events.
@@ -254,18 +340,10 @@ pub async fn crawl_l2_event(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "bitmex" => crawlers::bitmex::crawl_l2_event(market_type, symbols, tx).await,
-        "huobi" => crawlers::huobi::crawl_l2_event(market_type, symbols, tx).await,
-        "binance" | "bitfinex" | "bitget" | "bithumb" | "bitstamp" | "bitz" | "bybit"
-        | "coinbase_pro" | "deribit" | "dydx" | "ftx" | "gate" | "kraken" | "kucoin" | "mexc"
-        | "okx" | "zb" | "zbg" => {
-            crawlers::crawl_event(exchange, MessageType::L2Event, market_type, symbols, tx).await
-        }
-        _ => panic!("{exchange} does NOT have the incremental level2 websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::L2Event, symbols, tx).await
 }
 
 /// Crawl level3 orderbook update events.
@@ -273,14 +351,10 @@ pub async fn crawl_l3_event(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "bitfinex" | "bitstamp" | "coinbase_pro" | "kucoin" => {
-            crawlers::crawl_event(exchange, MessageType::L3Event, market_type, symbols, tx).await
-        }
-        _ => panic!("{exchange} does NOT have the incremental level3 websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::L3Event, symbols, tx).await
 }
 
 /// Crawl level2 orderbook snapshots through RESTful APIs.
@@ -288,8 +362,9 @@ pub fn crawl_l2_snapshot(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
+    let tx = tx.into();
     crawlers::crawl_snapshot(exchange, market_type, MessageType::L2Snapshot, symbols, tx);
 }
 
@@ -298,17 +373,10 @@ pub async fn crawl_bbo(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "binance" => crawlers::binance::crawl_bbo(market_type, symbols, tx).await,
-        "bitmex" => crawlers::bitmex::crawl_bbo(market_type, symbols, tx).await,
-        "kucoin" => crawlers::kucoin::crawl_bbo(market_type, symbols, tx).await,
-        "deribit" | "ftx" | "gate" | "huobi" | "kraken" | "okx" => {
-            crawlers::crawl_event(exchange, MessageType::BBO, market_type, symbols, tx).await
-        }
-        _ => panic!("{exchange} does NOT have BBO websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::BBO, symbols, tx).await
 }
 
 /// Crawl level2 orderbook top-k snapshots through websocket.
@@ -316,16 +384,10 @@ pub async fn crawl_l2_topk(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "bitmex" => crawlers::bitmex::crawl_l2_topk(market_type, symbols, tx).await,
-        "binance" | "bitget" | "bybit" | "bitstamp" | "deribit" | "gate" | "huobi" | "kucoin"
-        | "mexc" | "okx" | "zb" => {
-            crawlers::crawl_event(exchange, MessageType::L2TopK, market_type, symbols, tx).await
-        }
-        _ => panic!("{exchange} does NOT have the level2 top-k snapshot websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::L2TopK, symbols, tx).await
 }
 
 /// Crawl level3 orderbook snapshots through RESTful APIs.
@@ -333,8 +395,9 @@ pub fn crawl_l3_snapshot(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
+    let tx = tx.into();
     crawlers::crawl_snapshot(exchange, market_type, MessageType::L3Snapshot, symbols, tx)
 }
 
@@ -346,18 +409,10 @@ pub async fn crawl_ticker(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "binance" => crawlers::binance::crawl_ticker(market_type, symbols, tx).await,
-        "bitfinex" | "bitget" | "bithumb" | "bitz" | "bybit" | "coinbase_pro" | "deribit"
-        | "gate" | "huobi" | "kraken" | "kucoin" | "mexc" | "okx" => {
-            crawlers::crawl_event(exchange, MessageType::Ticker, market_type, symbols, tx).await
-        }
-        "zb" => crawlers::zb::crawl_ticker(market_type, symbols, tx).await,
-        "zbg" => crawlers::zbg::crawl_ticker(market_type, symbols, tx).await,
-        _ => panic!("{exchange} does NOT have the ticker websocket channel"),
-    }
+    let tx = tx.into();
+    crawlers::crawl_websocket(exchange, market_type, MessageType::Ticker, symbols, tx).await
 }
 
 /// Crawl perpetual swap funding rates.
@@ -365,8 +420,9 @@ pub async fn crawl_funding_rate(
     exchange: &str,
     market_type: MarketType,
     symbols: Option<&[String]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
+    let tx = tx.into();
     match exchange {
         "binance" => crawlers::binance::crawl_funding_rate(market_type, symbols, tx).await,
         "bitmex" => crawlers::bitmex::crawl_funding_rate(market_type, symbols, tx).await,
@@ -384,25 +440,66 @@ pub async fn crawl_candlestick(
     exchange: &str,
     market_type: MarketType,
     symbol_interval_list: Option<&[(String, usize)]>,
-    tx: Sender<Message>,
+    tx: impl Into<MessageSender>,
 ) {
-    match exchange {
-        "bitmex" => {
-            crawlers::bitmex::crawl_candlestick(market_type, symbol_interval_list, tx).await
-        }
-        "binance" | "bitfinex" | "bitget" | "bitz" | "bybit" | "deribit" | "gate" | "huobi"
-        | "kraken" | "kucoin" | "mexc" | "okx" | "zb" | "zbg" => {
-            crawlers::crawl_candlestick_ext(exchange, market_type, symbol_interval_list, tx).await
-        }
-        _ => panic!("{exchange} does NOT have the candlestick websocket channel"),
-    };
+    let tx = tx.into();
+    crawlers::crawl_candlestick(exchange, market_type, symbol_interval_list, tx).await;
 }
 
 /// Crawl all open interest.
-pub fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: Sender<Message>) {
+pub fn crawl_open_interest(exchange: &str, market_type: MarketType, tx: impl Into<MessageSender>) {
+    let tx = tx.into();
     crawlers::crawl_open_interest(exchange, market_type, tx);
 }
 
+/// Crawl messages of `msg_type` by calling the corresponding `crawl_*()`
+/// function.
+///
+/// Crawlers of RESTful APIs run in a blocking thread. Candlesticks of
+/// `symbols` are crawled at the 1 minute interval.
+pub async fn crawl(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+    symbols: Option<&[String]>,
+    tx: impl Into<MessageSender>,
+) {
+    let tx = tx.into();
+    match msg_type {
+        MessageType::Trade => crawl_trade(exchange, market_type, symbols, tx).await,
+        MessageType::L2Event => crawl_l2_event(exchange, market_type, symbols, tx).await,
+        MessageType::L3Event => crawl_l3_event(exchange, market_type, symbols, tx).await,
+        MessageType::BBO => crawl_bbo(exchange, market_type, symbols, tx).await,
+        MessageType::L2TopK => crawl_l2_topk(exchange, market_type, symbols, tx).await,
+        MessageType::Ticker => crawl_ticker(exchange, market_type, symbols, tx).await,
+        MessageType::FundingRate => crawl_funding_rate(exchange, market_type, symbols, tx).await,
+        MessageType::Candlestick => {
+            let symbol_interval_list: Option<Vec<(String, usize)>> =
+                symbols.map(|symbols| symbols.iter().map(|symbol| (symbol.clone(), 60)).collect());
+            crawl_candlestick(exchange, market_type, symbol_interval_list.as_deref(), tx).await
+        }
+        MessageType::L2Snapshot | MessageType::L3Snapshot | MessageType::OpenInterest => {
+            let exchange = exchange.to_string();
+            let symbols = symbols.map(|symbols| symbols.to_vec());
+            let handle = tokio::task::spawn_blocking(move || match msg_type {
+                MessageType::L2Snapshot => {
+                    crawl_l2_snapshot(&exchange, market_type, symbols.as_deref(), tx)
+                }
+                MessageType::L3Snapshot => {
+                    crawl_l3_snapshot(&exchange, market_type, symbols.as_deref(), tx)
+                }
+                _ => crawl_open_interest(&exchange, market_type, tx),
+            });
+            if let Err(err) = handle.await {
+                if err.is_panic() {
+                    std::panic::resume_unwind(err.into_panic());
+                }
+            }
+        }
+        _ => panic!("Crawling {msg_type} is NOT supported"),
+    }
+}
+
 /// Subscribe to multiple message types of one symbol.
 ///
 /// This API is suitable for client applications such as APP, website, etc.
@@ -0,0 +1,250 @@
+use std::{path::PathBuf, str::FromStr, sync::mpsc::Sender};
+
+use clap::{Args, Parser, Subcommand};
+use crypto_crawler::{
+    crawl, crawl_candlestick, plan_candlestick, plan_crawl, Compression, Daemon, DaemonConfig,
+    MarketType, Message, MessageType, OutputConfig, OutputWriter,
+};
+use log::*;
+
+/// A rock-solid cryprocurrency crawler.
+#[derive(Parser)]
+#[command(name = "crypto-crawler", version)]
+struct Cli {
+    /// Write to hourly rotated files under this directory instead of stdout
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+    /// Compression of output files, gzip or zstd
+    #[arg(long, global = true, default_value = "gzip", value_parser = parse_compression)]
+    compression: Compression,
+    /// Print what would be crawled and the subscription commands, without
+    /// connecting to exchanges
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct CrawlArgs {
+    /// Exchange name, e.g., binance
+    exchange: String,
+    /// Market type, e.g., spot, linear_swap
+    #[arg(value_parser = MarketType::from_str)]
+    market_type: MarketType,
+    /// Trading symbols, all symbols if empty
+    symbols: Vec<String>,
+}
+
+impl CrawlArgs {
+    fn symbols(&self) -> Option<&[String]> {
+        if self.symbols.is_empty() { None } else { Some(&self.symbols) }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Crawl tick-by-tick trades
+    Trade(CrawlArgs),
+    /// Crawl incremental level2 orderbook updates
+    L2Event(CrawlArgs),
+    /// Crawl level2 orderbook snapshots through RESTful APIs
+    L2Snapshot(CrawlArgs),
+    /// Crawl best bid and ask
+    Bbo(CrawlArgs),
+    /// Crawl 24hr rolling window tickers
+    Ticker(CrawlArgs),
+    /// Crawl candlesticks
+    Candlestick {
+        #[command(flatten)]
+        args: CrawlArgs,
+        /// Interval in seconds, applies only if symbols are specified
+        #[arg(long, default_value_t = 60)]
+        interval: usize,
+    },
+    /// Crawl perpetual swap funding rates
+    FundingRate(CrawlArgs),
+    /// Crawl open interests of all symbols
+    OpenInterest {
+        /// Exchange name, e.g., binance
+        exchange: String,
+        /// Market type, e.g., linear_swap
+        #[arg(value_parser = MarketType::from_str)]
+        market_type: MarketType,
+    },
+    /// Run crawl jobs listed in a YAML or TOML file, reload it on SIGHUP
+    Daemon {
+        /// Path of the config file
+        config: PathBuf,
+    },
+}
+
+fn parse_compression(s: &str) -> Result<Compression, String> {
+    match s {
+        "gzip" => Ok(Compression::Gzip),
+        #[cfg(feature = "zstd")]
+        "zstd" => Ok(Compression::Zstd),
+        _ => Err(format!("Unsupported compression {s}")),
+    }
+}
+
+impl Command {
+    // Returns the arguments and message type of a crawl subcommand.
+    fn into_crawl(self) -> (CrawlArgs, MessageType) {
+        match self {
+            Command::Trade(args) => (args, MessageType::Trade),
+            Command::L2Event(args) => (args, MessageType::L2Event),
+            Command::L2Snapshot(args) => (args, MessageType::L2Snapshot),
+            Command::Bbo(args) => (args, MessageType::BBO),
+            Command::Ticker(args) => (args, MessageType::Ticker),
+            Command::FundingRate(args) => (args, MessageType::FundingRate),
+            Command::Candlestick { args, .. } => (args, MessageType::Candlestick),
+            Command::OpenInterest { exchange, market_type } => (
+                CrawlArgs { exchange, market_type, symbols: Vec::new() },
+                MessageType::OpenInterest,
+            ),
+            Command::Daemon { .. } => unreachable!(),
+        }
+    }
+}
+
+fn to_symbol_interval_list(args: &CrawlArgs, interval: usize) -> Option<Vec<(String, usize)>> {
+    args.symbols().map(|symbols| symbols.iter().map(|symbol| (symbol.clone(), interval)).collect())
+}
+
+async fn run(command: Command, tx: Sender<Message>) {
+    match command {
+        Command::Candlestick { args, interval } => {
+            let symbol_interval_list = to_symbol_interval_list(&args, interval);
+            crawl_candlestick(&args.exchange, args.market_type, symbol_interval_list.as_deref(), tx)
+                .await
+        }
+        command => {
+            let (args, msg_type) = command.into_crawl();
+            crawl(&args.exchange, args.market_type, msg_type, args.symbols(), tx).await
+        }
+    }
+}
+
+// Prints plans of crawls instead of running them, returns the exit code.
+async fn dry_run(command: Command) -> i32 {
+    let plans = match command {
+        Command::Daemon { config } => {
+            let config =
+                DaemonConfig::load(config).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            let mut plans = Vec::new();
+            for job in config.jobs.iter() {
+                for msg_type in job.msg_types.iter() {
+                    let symbols = job.symbols.symbols();
+                    let plan = plan_crawl(&job.exchange, job.market_type, *msg_type, symbols).await;
+                    plans.push(plan);
+                }
+            }
+            plans
+        }
+        Command::Candlestick { args, interval } => {
+            let symbol_interval_list = to_symbol_interval_list(&args, interval);
+            vec![
+                plan_candlestick(&args.exchange, args.market_type, symbol_interval_list.as_deref())
+                    .await,
+            ]
+        }
+        command => {
+            let (args, msg_type) = command.into_crawl();
+            vec![plan_crawl(&args.exchange, args.market_type, msg_type, args.symbols()).await]
+        }
+    };
+    let mut code = 0;
+    for plan in plans {
+        match plan {
+            Ok(plan) => println!("{}", serde_json::to_string_pretty(&plan).unwrap()),
+            Err(err) => {
+                eprintln!("{err}");
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+async fn hangup(signal: &mut tokio::signal::unix::Signal) {
+    signal.recv().await;
+}
+
+#[cfg(not(unix))]
+async fn hangup(_signal: &mut ()) {
+    std::future::pending::<()>().await
+}
+
+async fn run_daemon(path: PathBuf) {
+    let config = DaemonConfig::load(&path).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let mut daemon = Daemon::new();
+    daemon.apply(config).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    #[cfg(unix)]
+    let mut signal =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).unwrap();
+    #[cfg(not(unix))]
+    let mut signal = ();
+    loop {
+        tokio::select! {
+            _ = hangup(&mut signal) => {
+                info!("Received SIGHUP, reloading {}", path.display());
+                match DaemonConfig::load(&path).and_then(|config| daemon.apply(config)) {
+                    Ok(()) => info!("Running jobs {:?}", daemon.job_names()),
+                    Err(err) => error!("Failed to reload {}, {}", path.display(), err),
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received Ctrl-C, shutting down");
+                break;
+            }
+        }
+    }
+    daemon.shutdown();
+    // don't wait for crawls running in blocking threads
+    std::process::exit(0);
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+    if cli.dry_run {
+        std::process::exit(dry_run(cli.command).await);
+    }
+    if let Command::Daemon { config } = cli.command {
+        return run_daemon(config).await;
+    }
+
+    let output = match cli.output {
+        Some(dir) => OutputConfig::File { dir, compression: cli.compression },
+        None => OutputConfig::Stdout,
+    };
+    let writer =
+        OutputWriter::start(&output).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    let task = tokio::task::spawn(run(cli.command, writer.sender()));
+
+    tokio::select! {
+        result = task => {
+            if let Err(err) = result {
+                error!("The crawl failed, {}", err);
+            }
+        }
+        _ = tokio::signal::ctrl_c() => info!("Received Ctrl-C, shutting down"),
+    }
+    let code = match writer.stop() {
+        Ok(()) => 0,
+        Err(err) => {
+            error!("Failed to write messages, {}", err);
+            1
+        }
+    };
+    // don't wait for crawls running in blocking threads
+    std::process::exit(code);
+}
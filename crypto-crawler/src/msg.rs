@@ -3,10 +3,35 @@ use crypto_msg_type::MessageType;
 use serde::{Deserialize, Serialize};
 use std::{
     convert::TryInto,
+    io::{ErrorKind, Read, Write},
     str::FromStr,
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use crate::error::{Error, Result};
+
+/// Version of the binary format written by `Message::to_binary()`.
+///
+/// Each record starts with the version byte, followed by the payload length
+/// as a little-endian `u32` and the payload, which is a MessagePack map.
+/// Decoders reject versions newer than this one.
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+// Calls a crypto-msg-parser function, which panics on some unexpected
+// messages, a panic is returned as an error instead of killing the thread.
+pub(crate) fn catch_parser_panic<T, E: std::fmt::Display>(
+    parse: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, String> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(parse)) {
+        Ok(result) => result.map_err(|err| err.to_string()),
+        Err(payload) => Err(payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "the parser panicked".to_string())),
+    }
+}
+
 /// Message represents messages received by crawlers.
 #[derive(Serialize, Deserialize)]
 pub struct Message {
@@ -25,37 +50,69 @@ pub struct Message {
 }
 
 impl Message {
+    /// Creates a message without `symbol`, which is extracted on demand by
+    /// `extract_symbol()` or `with_extracted_symbol()`.
     pub fn new(
         exchange: String,
         market_type: MarketType,
         msg_type: MessageType,
         json: String,
+    ) -> Self {
+        Self::new_internal(exchange, market_type, msg_type, None, json.trim())
+    }
+
+    pub fn new_with_symbol(
+        exchange: String,
+        market_type: MarketType,
+        msg_type: MessageType,
+        symbol: String,
+        json: String,
+    ) -> Self {
+        Self::new_internal(exchange, market_type, msg_type, Some(symbol), json.trim())
+    }
+
+    fn new_internal(
+        exchange: String,
+        market_type: MarketType,
+        msg_type: MessageType,
+        symbol: Option<String>,
+        json: &str,
     ) -> Self {
         Message {
             exchange,
             market_type,
             msg_type,
-            symbol: None,
+            symbol,
             received_at: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_millis()
                 .try_into()
                 .unwrap(),
-            json: json.trim().to_string(),
+            json: json.to_string(),
         }
     }
 
-    pub fn new_with_symbol(
-        exchange: String,
-        market_type: MarketType,
-        msg_type: MessageType,
-        symbol: String,
-        json: String,
-    ) -> Self {
-        let mut msg = Self::new(exchange, market_type, msg_type, json);
-        msg.symbol = Some(symbol);
-        msg
+    /// Returns `symbol` if present, otherwise extracts it from `json`.
+    ///
+    /// Returns None if the message contains multiple symbols or can't be
+    /// parsed. It parses the whole message, so it should NOT be called on
+    /// latency-sensitive paths.
+    pub fn extract_symbol(&self) -> Option<String> {
+        if self.symbol.is_some() {
+            return self.symbol.clone();
+        }
+        catch_parser_panic(|| {
+            crypto_msg_parser::extract_symbol(&self.exchange, self.market_type, &self.json)
+        })
+        .ok()
+        .filter(|symbol| symbol != "ALL" && symbol != "NONE")
+    }
+
+    /// Fills `symbol` by `extract_symbol()`.
+    pub fn with_extracted_symbol(mut self) -> Self {
+        self.symbol = self.extract_symbol();
+        self
     }
 
     /// Convert to a TSV string.
@@ -68,22 +125,115 @@ impl Message {
     }
 
     /// Convert from a TSV string.
-    pub fn from_tsv_string(exchange: &str, market_type: &str, msg_type: &str, s: &str) -> Self {
-        let v: Vec<&str> = s.split('\t').collect();
-        assert_eq!(3, v.len());
-        let market_type = MarketType::from_str(market_type).unwrap();
-        let msg_type = MessageType::from_str(msg_type).unwrap();
+    pub fn from_tsv_string(
+        exchange: &str,
+        market_type: &str,
+        msg_type: &str,
+        s: &str,
+    ) -> Result<Self> {
+        let v: Vec<&str> = s.splitn(3, '\t').collect();
+        if v.len() != 3 {
+            return Err(Error(format!("Expected 3 fields, got {}: {}", v.len(), s)));
+        }
+        let market_type = MarketType::from_str(market_type)
+            .map_err(|_| Error(format!("Unknown market type {market_type}")))?;
+        let msg_type = MessageType::from_str(msg_type)
+            .map_err(|_| Error(format!("Unknown message type {msg_type}")))?;
+        let received_at =
+            v[0].parse::<u64>().map_err(|_| Error(format!("Invalid received_at {}", v[0])))?;
 
         let symbol = if v[1].is_empty() { None } else { Some(v[1].to_string()) };
 
-        Message {
+        Ok(Message {
             exchange: exchange.to_string(),
             market_type,
             msg_type,
             symbol,
-            received_at: v[0].parse::<u64>().unwrap(),
+            received_at,
             json: v[2].to_string(),
+        })
+    }
+
+    /// Encode to a versioned, length-prefixed binary record.
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let payload = rmp_serde::to_vec_named(self)?;
+        let len: u32 = payload
+            .len()
+            .try_into()
+            .map_err(|_| Error(format!("Message too large, {} bytes", payload.len())))?;
+        let mut bytes = Vec::with_capacity(5 + payload.len());
+        bytes.push(BINARY_FORMAT_VERSION);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Decode from a binary record created by `to_binary()`.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self> {
+        let mut reader = bytes;
+        let msg = Self::read_binary(&mut reader)?
+            .ok_or_else(|| Error("Empty binary record".to_string()))?;
+        if !reader.is_empty() {
+            return Err(Error(format!("{} trailing bytes", reader.len())));
+        }
+        Ok(msg)
+    }
+
+    /// Write a binary record to a stream.
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.to_binary()?)?;
+        Ok(())
+    }
+
+    /// Read the next binary record from a stream, returns `None` at the end
+    /// of the stream.
+    pub fn read_binary<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        let mut header = [0u8; 5];
+        match reader.read_exact(&mut header[..1]) {
+            Ok(()) => (),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        reader.read_exact(&mut header[1..])?;
+        let version = header[0];
+        if version == 0 || version > BINARY_FORMAT_VERSION {
+            return Err(Error(format!("Unsupported binary format version {version}")));
+        }
+        let len = u32::from_le_bytes(header[1..].try_into().unwrap()) as usize;
+        // the length is untrusted, so the buffer grows with the bytes actually
+        // read instead of being allocated upfront
+        let mut payload = Vec::new();
+        if reader.take(len as u64).read_to_end(&mut payload)? < len {
+            return Err(Error(format!("Truncated binary record, expected {len} bytes")));
         }
+        Ok(Some(rmp_serde::from_slice(&payload)?))
+    }
+}
+
+/// Selects messages by exchange, market type, message type and symbol.
+///
+/// `None` matches any value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_type: Option<MarketType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg_type: Option<MessageType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+impl MessageFilter {
+    /// Returns true if the message matches this filter.
+    ///
+    /// A message without symbol only matches filters without symbol.
+    pub fn matches(&self, msg: &Message) -> bool {
+        self.exchange.as_ref().map(|x| *x == msg.exchange).unwrap_or(true)
+            && self.market_type.map(|x| x == msg.market_type).unwrap_or(true)
+            && self.msg_type.map(|x| x == msg.msg_type).unwrap_or(true)
+            && self.symbol.as_ref().map(|x| Some(x) == msg.symbol.as_ref()).unwrap_or(true)
     }
 }
 
@@ -92,3 +242,105 @@ impl std::fmt::Display for Message {
         write!(f, "{}", serde_json::to_string(self).unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_parser_panic() {
+        assert_eq!(Ok(1), catch_parser_panic(|| Ok::<i32, String>(1)));
+        assert_eq!(Err("invalid".to_string()), catch_parser_panic(|| Err::<i32, _>("invalid")));
+        let panicked = catch_parser_panic(|| -> std::result::Result<i32, String> {
+            panic!("unexpected {}", "message")
+        });
+        assert_eq!(Err("unexpected message".to_string()), panicked);
+    }
+
+    #[test]
+    fn test_tsv() {
+        let msg = Message::from_tsv_string(
+            "binance",
+            "spot",
+            "trade",
+            "1676019600000\tBTCUSDT\t{\"a\":\"\tb\"}",
+        )
+        .unwrap();
+        assert_eq!(Some("BTCUSDT".to_string()), msg.symbol);
+        assert_eq!("{\"a\":\"\tb\"}", msg.json);
+
+        assert!(Message::from_tsv_string("binance", "spot", "trade", "1676019600000").is_err());
+        assert!(Message::from_tsv_string("binance", "spot", "trade", "abc\t\t{}").is_err());
+        assert!(Message::from_tsv_string("binance", "spot", "unknown", "0\t\t{}").is_err());
+    }
+
+    #[test]
+    fn test_extract_symbol() {
+        let json = r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","E":1676019600000,"s":"BTCUSDT","a":1,"p":"21800.00","q":"0.001","f":1,"l":1,"T":1676019600000,"m":true,"M":true}}"#;
+        let msg =
+            Message::new("binance".to_string(), MarketType::Spot, MessageType::Trade, json.into());
+        assert_eq!(None, msg.symbol);
+        assert_eq!(Some("BTCUSDT".to_string()), msg.extract_symbol());
+        assert_eq!(Some("BTCUSDT".to_string()), msg.with_extracted_symbol().symbol);
+
+        let msg = Message::new("binance".into(), MarketType::Spot, MessageType::Trade, "{}".into());
+        assert_eq!(None, msg.extract_symbol());
+    }
+
+    #[test]
+    fn test_filter() {
+        let msg = Message::new_with_symbol(
+            "binance".to_string(),
+            MarketType::Spot,
+            MessageType::Trade,
+            "BTCUSDT".to_string(),
+            "{}".to_string(),
+        );
+        assert!(MessageFilter::default().matches(&msg));
+        let filter = MessageFilter {
+            exchange: Some("binance".to_string()),
+            msg_type: Some(MessageType::Trade),
+            symbol: Some("BTCUSDT".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&msg));
+        assert!(!MessageFilter { market_type: Some(MarketType::LinearSwap), ..filter.clone() }
+            .matches(&msg));
+        assert!(!MessageFilter { symbol: Some("ETHUSDT".to_string()), ..filter }.matches(&msg));
+    }
+
+    #[test]
+    fn test_binary() {
+        let mut buf = Vec::new();
+        let messages = [
+            Message::new("binance".to_string(), MarketType::Spot, MessageType::Trade, "1".into()),
+            Message::new_with_symbol(
+                "okx".to_string(),
+                MarketType::InverseSwap,
+                MessageType::L2TopK,
+                "BTC-USD-SWAP".to_string(),
+                "2".to_string(),
+            ),
+        ];
+        for msg in messages.iter() {
+            msg.write_binary(&mut buf).unwrap();
+        }
+
+        let mut reader = buf.as_slice();
+        for expected in messages.iter() {
+            let msg = Message::read_binary(&mut reader).unwrap().unwrap();
+            assert_eq!(expected.to_string(), msg.to_string());
+        }
+        assert!(Message::read_binary(&mut reader).unwrap().is_none());
+
+        let mut bytes = messages[0].to_binary().unwrap();
+        assert_eq!(messages[0].to_string(), Message::from_binary(&bytes).unwrap().to_string());
+        assert!(Message::from_binary(&bytes[..bytes.len() - 1]).is_err());
+        bytes[0] = BINARY_FORMAT_VERSION + 1;
+        assert!(Message::from_binary(&bytes).is_err());
+
+        // a corrupted length must not allocate 4GB
+        let bytes = [BINARY_FORMAT_VERSION, 0xff, 0xff, 0xff, 0xff, 0x80];
+        assert!(Message::from_binary(&bytes).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    file_sink::{Compression, FileSink},
+    msg::Message,
+};
+
+// How often to flush and rotate
+const TICK_INTERVAL: Duration = Duration::from_millis(200);
+
+// Max time spent writing pending messages after stopping
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn default_compression() -> Compression {
+    Compression::Gzip
+}
+
+#[cfg(feature = "parquet")]
+fn default_row_group_size() -> usize {
+    65536
+}
+
+/// Where to write crawled messages.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputConfig {
+    /// One JSON message per line.
+    Stdout,
+    /// Hourly rotated files written by `FileSink`.
+    File {
+        dir: PathBuf,
+        #[serde(default = "default_compression")]
+        compression: Compression,
+    },
+    /// A database written by `SqliteSink`.
+    #[cfg(feature = "sqlite")]
+    Sqlite { path: PathBuf },
+    /// Parquet files written by `ParquetSink`.
+    #[cfg(feature = "parquet")]
+    Parquet {
+        dir: PathBuf,
+        #[serde(default = "default_row_group_size")]
+        row_group_size: usize,
+        #[serde(default)]
+        parse_rows: bool,
+    },
+}
+
+enum Sink {
+    Stdout(io::Stdout),
+    File(FileSink),
+    #[cfg(feature = "sqlite")]
+    Sqlite(crate::sqlite_sink::SqliteSink),
+    #[cfg(feature = "parquet")]
+    Parquet(crate::parquet_sink::ParquetSink),
+}
+
+impl Sink {
+    fn open(config: &OutputConfig) -> Result<Self> {
+        let sink = match config {
+            OutputConfig::Stdout => Sink::Stdout(io::stdout()),
+            OutputConfig::File { dir, compression } => {
+                Sink::File(FileSink::new(dir, *compression)?)
+            }
+            #[cfg(feature = "sqlite")]
+            OutputConfig::Sqlite { path } => {
+                Sink::Sqlite(crate::sqlite_sink::SqliteSink::new(path)?)
+            }
+            #[cfg(feature = "parquet")]
+            OutputConfig::Parquet { dir, row_group_size, parse_rows } => Sink::Parquet(
+                crate::parquet_sink::ParquetSink::new(dir, *row_group_size, *parse_rows)?,
+            ),
+        };
+        Ok(sink)
+    }
+
+    fn write(&mut self, msg: &Message) -> Result<()> {
+        match self {
+            Sink::Stdout(stdout) => writeln!(stdout.lock(), "{msg}")?,
+            Sink::File(sink) => sink.write(msg)?,
+            #[cfg(feature = "sqlite")]
+            Sink::Sqlite(sink) => sink.write(msg)?,
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(sink) => sink.write(msg)?,
+        }
+        Ok(())
+    }
+
+    // Called every TICK_INTERVAL.
+    fn tick(&mut self) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        match self {
+            Sink::Stdout(stdout) => stdout.flush()?,
+            Sink::File(sink) => {
+                sink.rotate(now)?;
+                sink.flush()?;
+            }
+            #[cfg(feature = "sqlite")]
+            Sink::Sqlite(sink) => sink.flush()?,
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(sink) => sink.rotate(now)?,
+        }
+        Ok(())
+    }
+
+    fn close(self) -> Result<()> {
+        match self {
+            Sink::Stdout(mut stdout) => stdout.flush()?,
+            Sink::File(sink) => sink.close()?,
+            #[cfg(feature = "sqlite")]
+            Sink::Sqlite(sink) => sink.close()?,
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(sink) => sink.close()?,
+        }
+        Ok(())
+    }
+}
+
+/// Writes messages to an output in a dedicated thread.
+pub struct OutputWriter {
+    tx: Sender<Message>,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Result<()>>,
+}
+
+impl OutputWriter {
+    /// Opens the output and starts the writing thread.
+    pub fn start(config: &OutputConfig) -> Result<Self> {
+        let sink = Sink::open(config)?;
+        let (tx, rx) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            std::thread::spawn(move || write_messages(sink, rx, &stop))
+        };
+        Ok(OutputWriter { tx, stop, thread })
+    }
+
+    /// Returns a sender to pass to `crawl_*()` functions.
+    pub fn sender(&self) -> Sender<Message> {
+        self.tx.clone()
+    }
+
+    /// Writes pending messages and closes the output.
+    ///
+    /// Crawls still holding a sender are not waited for, messages sent by
+    /// them afterwards are discarded.
+    pub fn stop(self) -> Result<()> {
+        self.stop.store(true, Ordering::Release);
+        drop(self.tx);
+        self.thread.join().map_err(|_| Error("The output thread panicked".to_string()))?
+    }
+}
+
+fn write_messages(mut sink: Sink, rx: Receiver<Message>, stop: &AtomicBool) -> Result<()> {
+    let mut last_tick = Instant::now();
+    while !stop.load(Ordering::Acquire) {
+        match rx.recv_timeout(TICK_INTERVAL.saturating_sub(last_tick.elapsed())) {
+            Ok(msg) => sink.write(&msg)?,
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        // checked on every message, a busy receiver never times out
+        if last_tick.elapsed() >= TICK_INTERVAL {
+            sink.tick()?;
+            last_tick = Instant::now();
+        }
+    }
+    // messages received before stopping, running crawls may keep sending, so
+    // stop at a deadline
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while Instant::now() < deadline {
+        match rx.try_recv() {
+            Ok(msg) => sink.write(&msg)?,
+            Err(_) => break,
+        }
+    }
+    let result = sink.close();
+    if let Err(err) = result.as_ref() {
+        error!("Failed to close the output, {}", err);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+
+    #[test]
+    fn test_stop_with_running_crawl() {
+        let dir = std::env::temp_dir().join(format!("output_{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        let config = OutputConfig::File { dir: dir.clone(), compression: Compression::Gzip };
+        let writer = OutputWriter::start(&config).unwrap();
+        let tx = writer.sender();
+        // a crawl which never stops sending
+        let crawl = std::thread::spawn(move || {
+            let new_msg = || {
+                Message::new_with_symbol(
+                    "binance".to_string(),
+                    MarketType::Spot,
+                    MessageType::Trade,
+                    "BTCUSDT".to_string(),
+                    "{}".to_string(),
+                )
+            };
+            while tx.send(new_msg()).is_ok() {}
+        });
+        std::thread::sleep(Duration::from_millis(100));
+
+        let start = Instant::now();
+        writer.stop().unwrap();
+        assert!(start.elapsed() < DRAIN_TIMEOUT * 5);
+        crawl.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tick_while_busy() {
+        let (tx, rx) = channel();
+        let stop = AtomicBool::new(false);
+        let path = std::env::temp_dir().join(format!("output_busy_{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&path);
+        let sink = Sink::File(FileSink::new(&path, Compression::Gzip).unwrap());
+        let file = path.join("binance").join("spot").join("trade").join("2023-02-10-09.tsv.gz");
+        std::thread::scope(|scope| {
+            let writer = scope.spawn(|| write_messages(sink, rx, &stop));
+            // messages of a past hour keep arriving every 10ms
+            let deadline = Instant::now() + TICK_INTERVAL * 10;
+            while !file.exists() && Instant::now() < deadline {
+                let mut msg = Message::new(
+                    "binance".to_string(),
+                    MarketType::Spot,
+                    MessageType::Trade,
+                    "{}".to_string(),
+                );
+                msg.received_at = 1676019600000;
+                tx.send(msg).unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            // rotated before the sender is dropped
+            assert!(file.exists());
+            drop(tx);
+            writer.join().unwrap().unwrap();
+        });
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,586 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{TimeZone, Utc};
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use log::*;
+use parquet::{
+    basic::{Compression, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::{parser::parse_message_type, types::Type},
+};
+
+use crate::{
+    error::Result,
+    msg::{catch_parser_panic, Message},
+};
+
+// Suffix of in-progress files
+const PART_SUFFIX: &str = ".part";
+
+// How often to check for expired partitions
+const ROTATE_INTERVAL: Duration = Duration::from_secs(1);
+
+const MESSAGE_SCHEMA: &str = "
+message message {
+    required binary exchange (UTF8);
+    required binary market_type (UTF8);
+    required binary msg_type (UTF8);
+    optional binary symbol (UTF8);
+    required int64 received_at (TIMESTAMP(MILLIS,true));
+    required binary json (UTF8);
+}";
+
+const TRADE_SCHEMA: &str = "
+message trade {
+    required binary exchange (UTF8);
+    required binary market_type (UTF8);
+    required binary symbol (UTF8);
+    required binary pair (UTF8);
+    required int64 timestamp (TIMESTAMP(MILLIS,true));
+    required binary side (UTF8);
+    required double price;
+    required double quantity_base;
+    required double quantity_quote;
+    optional double quantity_contract;
+    required binary trade_id (UTF8);
+    required int64 received_at (TIMESTAMP(MILLIS,true));
+}";
+
+const L2_SCHEMA: &str = "
+message l2 {
+    required binary exchange (UTF8);
+    required binary market_type (UTF8);
+    required binary msg_type (UTF8);
+    required binary symbol (UTF8);
+    required binary pair (UTF8);
+    required int64 timestamp (TIMESTAMP(MILLIS,true));
+    required boolean snapshot;
+    optional int64 seq_id;
+    optional int64 prev_seq_id;
+    required binary side (UTF8);
+    required double price;
+    required double quantity_base;
+    required double quantity_quote;
+    optional double quantity_contract;
+    required int64 received_at (TIMESTAMP(MILLIS,true));
+}";
+
+// Tables written by `ParquetSink`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Table {
+    /// `Message` envelopes, one row per message.
+    Messages,
+    /// Parsed trades, one row per trade.
+    Trades,
+    /// Parsed level2 orderbooks, one row per price level.
+    L2,
+}
+
+impl Table {
+    fn name(&self) -> &'static str {
+        match self {
+            Table::Messages => "messages",
+            Table::Trades => "trades",
+            Table::L2 => "l2",
+        }
+    }
+
+    fn schema(&self) -> &'static str {
+        match self {
+            Table::Messages => MESSAGE_SCHEMA,
+            Table::Trades => TRADE_SCHEMA,
+            Table::L2 => L2_SCHEMA,
+        }
+    }
+}
+
+enum Value<'a> {
+    Str(&'a str),
+    Int64(i64),
+    Double(f64),
+    Bool(bool),
+    Null,
+}
+
+enum Values {
+    ByteArray(Vec<ByteArray>),
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+    Bool(Vec<bool>),
+}
+
+// Buffered values of a column
+struct Column {
+    values: Values,
+    def_levels: Option<Vec<i16>>, // only for optional columns
+}
+
+impl Column {
+    fn new(field: &Type) -> Self {
+        let values = match field.get_physical_type() {
+            PhysicalType::BYTE_ARRAY => Values::ByteArray(Vec::new()),
+            PhysicalType::INT64 => Values::Int64(Vec::new()),
+            PhysicalType::DOUBLE => Values::Double(Vec::new()),
+            PhysicalType::BOOLEAN => Values::Bool(Vec::new()),
+            physical_type => panic!("Unsupported physical type {physical_type}"),
+        };
+        let def_levels = if field.is_optional() { Some(Vec::new()) } else { None };
+        Column { values, def_levels }
+    }
+
+    fn push(&mut self, value: &Value) {
+        if let Some(def_levels) = self.def_levels.as_mut() {
+            def_levels.push(if matches!(value, Value::Null) { 0 } else { 1 });
+        }
+        match (&mut self.values, value) {
+            (_, Value::Null) => assert!(self.def_levels.is_some(), "The column is required"),
+            (Values::ByteArray(v), Value::Str(s)) => v.push(ByteArray::from(*s)),
+            (Values::Int64(v), Value::Int64(x)) => v.push(*x),
+            (Values::Double(v), Value::Double(x)) => v.push(*x),
+            (Values::Bool(v), Value::Bool(x)) => v.push(*x),
+            _ => panic!("Value type doesn't match the column type"),
+        }
+    }
+
+    fn write(&mut self, writer: &mut ColumnWriter) -> Result<()> {
+        let def_levels = self.def_levels.as_deref();
+        match (&mut self.values, writer) {
+            (Values::ByteArray(v), ColumnWriter::ByteArrayColumnWriter(w)) => {
+                w.write_batch(v, def_levels, None)?;
+                v.clear();
+            }
+            (Values::Int64(v), ColumnWriter::Int64ColumnWriter(w)) => {
+                w.write_batch(v, def_levels, None)?;
+                v.clear();
+            }
+            (Values::Double(v), ColumnWriter::DoubleColumnWriter(w)) => {
+                w.write_batch(v, def_levels, None)?;
+                v.clear();
+            }
+            (Values::Bool(v), ColumnWriter::BoolColumnWriter(w)) => {
+                w.write_batch(v, def_levels, None)?;
+                v.clear();
+            }
+            _ => panic!("Column type doesn't match the schema"),
+        }
+        if let Some(def_levels) = self.def_levels.as_mut() {
+            def_levels.clear();
+        }
+        Ok(())
+    }
+}
+
+// An in-progress file of one partition
+struct PartFile {
+    date: String,
+    path: PathBuf, // final path, without the .part suffix
+    writer: SerializedFileWriter<File>,
+    columns: Vec<Column>,
+    num_rows: usize, // number of buffered rows
+}
+
+impl PartFile {
+    fn push(&mut self, row: &[Value]) {
+        for (column, value) in self.columns.iter_mut().zip(row.iter()) {
+            column.push(value);
+        }
+        self.num_rows += 1;
+    }
+
+    fn write_row_group(&mut self) -> Result<()> {
+        if self.num_rows == 0 {
+            return Ok(());
+        }
+        let mut row_group_writer = self.writer.next_row_group()?;
+        for column in self.columns.iter_mut() {
+            let mut column_writer = row_group_writer.next_column()?.unwrap();
+            column.write(column_writer.untyped())?;
+            column_writer.close()?;
+        }
+        row_group_writer.close()?;
+        self.num_rows = 0;
+        Ok(())
+    }
+
+    fn finalize(mut self) -> Result<()> {
+        self.write_row_group()?;
+        self.writer.close()?;
+        let part_path = to_part_path(&self.path);
+        File::open(&part_path)?.sync_all()?;
+        fs::rename(&part_path, &self.path)?;
+        if let Some(dir) = self.path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes messages to Parquet files for DuckDB, Spark, etc.
+///
+/// Files are partitioned in the Hive style, i.e.,
+/// `root_dir/table/exchange=x/market_type=y/msg_type=z/date=YYYY-MM-DD/`, and
+/// the date comes from `received_at` in UTC.
+///
+/// The `messages` table has one row per `Message`. If `parse_rows` is true,
+/// trades are parsed into the `trades` table and level2 orderbooks into the
+/// `l2` table, one row per price level.
+///
+/// Rows are buffered in memory until `row_group_size` rows have been
+/// collected, then written as one row group to a finished file, so a crash
+/// loses at most the rows buffered in memory. A file is also finalized when
+/// its date changes or the sink is closed. Unfinished files are removed by
+/// `ParquetSink::new()`, use `FileSink` if every message must survive a crash.
+pub struct ParquetSink {
+    root_dir: PathBuf,
+    row_group_size: usize,
+    parse_rows: bool,
+    properties: Arc<WriterProperties>,
+    files: HashMap<(Table, String, MarketType, MessageType), PartFile>,
+}
+
+impl ParquetSink {
+    /// Creates a sink writing to `root_dir`, and removes unfinished files
+    /// left by a previous run.
+    pub fn new(
+        root_dir: impl AsRef<Path>,
+        row_group_size: usize,
+        parse_rows: bool,
+    ) -> Result<Self> {
+        assert!(row_group_size > 0, "row_group_size must be positive");
+        let root_dir = root_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&root_dir)?;
+        remove_part_files(&root_dir)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(row_group_size)
+            .build();
+        Ok(ParquetSink {
+            root_dir,
+            row_group_size,
+            parse_rows,
+            properties: Arc::new(properties),
+            files: HashMap::new(),
+        })
+    }
+
+    /// Writes a message, and its parsed rows if `parse_rows` is true.
+    pub fn write(&mut self, msg: &Message) -> Result<()> {
+        let received_at = msg.received_at as i64;
+        let market_type = msg.market_type.to_string();
+        let msg_type = msg.msg_type.to_string();
+        let symbol = msg.extract_symbol();
+        let symbol = symbol.as_deref().map(Value::Str).unwrap_or(Value::Null);
+        let row = [
+            Value::Str(&msg.exchange),
+            Value::Str(&market_type),
+            Value::Str(&msg_type),
+            symbol,
+            Value::Int64(received_at),
+            Value::Str(&msg.json),
+        ];
+        self.append(Table::Messages, msg, &row)?;
+
+        if !self.parse_rows {
+            return Ok(());
+        }
+        match msg.msg_type {
+            MessageType::Trade => {
+                let trades = match catch_parser_panic(|| {
+                    crypto_msg_parser::parse_trade(&msg.exchange, msg.market_type, &msg.json)
+                }) {
+                    Ok(trades) => trades,
+                    Err(err) => {
+                        warn!("Failed to parse {}, {}", msg.json, err);
+                        return Ok(());
+                    }
+                };
+                for trade in trades {
+                    let side = trade.side.to_string();
+                    let row = [
+                        Value::Str(&trade.exchange),
+                        Value::Str(&market_type),
+                        Value::Str(&trade.symbol),
+                        Value::Str(&trade.pair),
+                        Value::Int64(trade.timestamp),
+                        Value::Str(&side),
+                        Value::Double(trade.price),
+                        Value::Double(trade.quantity_base),
+                        Value::Double(trade.quantity_quote),
+                        trade.quantity_contract.map(Value::Double).unwrap_or(Value::Null),
+                        Value::Str(&trade.trade_id),
+                        Value::Int64(received_at),
+                    ];
+                    self.append(Table::Trades, msg, &row)?;
+                }
+            }
+            MessageType::L2Event | MessageType::L2Snapshot | MessageType::L2TopK => {
+                let orderbooks = match catch_parser_panic(|| {
+                    crypto_msg_parser::parse_l2(
+                        &msg.exchange,
+                        msg.market_type,
+                        &msg.json,
+                        Some(received_at),
+                    )
+                }) {
+                    Ok(orderbooks) => orderbooks,
+                    Err(err) => {
+                        warn!("Failed to parse {}, {}", msg.json, err);
+                        return Ok(());
+                    }
+                };
+                for orderbook in orderbooks {
+                    let levels = orderbook
+                        .bids
+                        .iter()
+                        .map(|order| ("bid", order))
+                        .chain(orderbook.asks.iter().map(|order| ("ask", order)));
+                    for (side, order) in levels {
+                        let row = [
+                            Value::Str(&orderbook.exchange),
+                            Value::Str(&market_type),
+                            Value::Str(&msg_type),
+                            Value::Str(&orderbook.symbol),
+                            Value::Str(&orderbook.pair),
+                            Value::Int64(orderbook.timestamp),
+                            Value::Bool(orderbook.snapshot),
+                            orderbook.seq_id.map(|x| Value::Int64(x as i64)).unwrap_or(Value::Null),
+                            orderbook
+                                .prev_seq_id
+                                .map(|x| Value::Int64(x as i64))
+                                .unwrap_or(Value::Null),
+                            Value::Str(side),
+                            Value::Double(order.price),
+                            Value::Double(order.quantity_base),
+                            Value::Double(order.quantity_quote),
+                            order.quantity_contract.map(Value::Double).unwrap_or(Value::Null),
+                            Value::Int64(received_at),
+                        ];
+                        self.append(Table::L2, msg, &row)?;
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Finalizes files whose date is earlier than the date of `now`, which is
+    /// a Unix timestamp in milliseconds.
+    pub fn rotate(&mut self, now: u64) -> Result<()> {
+        let date = to_date(now);
+        let expired: Vec<_> =
+            self.files.iter().filter(|(_, f)| f.date < date).map(|(k, _)| k.clone()).collect();
+        for key in expired {
+            self.files.remove(&key).unwrap().finalize()?;
+        }
+        Ok(())
+    }
+
+    /// Consumes messages until all senders have been dropped, then finalizes
+    /// all files.
+    pub fn run(mut self, rx: Receiver<Message>) -> Result<()> {
+        let mut last_rotate = Instant::now();
+        loop {
+            match rx.recv_timeout(ROTATE_INTERVAL.saturating_sub(last_rotate.elapsed())) {
+                Ok(msg) => self.write(&msg)?,
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            // checked on every message, a busy receiver never times out
+            if last_rotate.elapsed() >= ROTATE_INTERVAL {
+                self.rotate(now_millis())?;
+                last_rotate = Instant::now();
+            }
+        }
+        self.close()
+    }
+
+    /// Finalizes all files.
+    pub fn close(mut self) -> Result<()> {
+        for (_, part_file) in self.files.drain() {
+            part_file.finalize()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, table: Table, msg: &Message, row: &[Value]) -> Result<()> {
+        let key = (table, msg.exchange.clone(), msg.market_type, msg.msg_type);
+        let date = to_date(msg.received_at);
+        if self.files.get(&key).map(|f| f.date < date).unwrap_or(false) {
+            self.files.remove(&key).unwrap().finalize()?;
+        }
+        if !self.files.contains_key(&key) {
+            let part_file = self.open(table, msg, date)?;
+            self.files.insert(key.clone(), part_file);
+        }
+        let part_file = self.files.get_mut(&key).unwrap();
+        part_file.push(row);
+        if part_file.num_rows >= self.row_group_size {
+            // one row group per file, the next row opens a new file
+            self.files.remove(&key).unwrap().finalize()?;
+        }
+        Ok(())
+    }
+
+    fn open(&self, table: Table, msg: &Message, date: String) -> Result<PartFile> {
+        let dir = self
+            .root_dir
+            .join(table.name())
+            .join(format!("exchange={}", msg.exchange))
+            .join(format!("market_type={}", msg.market_type))
+            .join(format!("msg_type={}", msg.msg_type))
+            .join(format!("date={date}"));
+        fs::create_dir_all(&dir)?;
+        let mut path = dir.join(format!("part-{}.parquet", msg.received_at));
+        let mut i = 1;
+        while path.exists() {
+            path = dir.join(format!("part-{}-{}.parquet", msg.received_at, i));
+            i += 1;
+        }
+
+        let schema = Arc::new(parse_message_type(table.schema())?);
+        let columns = schema.get_fields().iter().map(|field| Column::new(field)).collect();
+        let file = File::create(to_part_path(&path))?;
+        let writer = SerializedFileWriter::new(file, schema, self.properties.clone())?;
+        Ok(PartFile { date, path, writer, columns, num_rows: 0 })
+    }
+}
+
+fn to_part_path(path: &Path) -> PathBuf {
+    let mut s = path.as_os_str().to_os_string();
+    s.push(PART_SUFFIX);
+    PathBuf::from(s)
+}
+
+fn remove_part_files(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_part_files(&path)?;
+        } else if path.to_string_lossy().ends_with(PART_SUFFIX) {
+            warn!("Removing unfinished file {}", path.display());
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn to_date(timestamp: u64) -> String {
+    Utc.timestamp_millis_opt(timestamp as i64).unwrap().format("%Y-%m-%d").to_string()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+    };
+
+    fn new_msg(msg_type: MessageType, received_at: u64, json: &str) -> Message {
+        let mut msg =
+            Message::new("binance".to_string(), MarketType::Spot, msg_type, json.to_string());
+        msg.received_at = received_at;
+        msg
+    }
+
+    fn read_files(dir: &Path) -> Vec<SerializedFileReader<File>> {
+        let mut paths: Vec<PathBuf> =
+            fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| SerializedFileReader::new(File::open(path).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = std::env::temp_dir().join(format!("parquet_sink_{}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        let trade = r#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1676019600000,"s":"BTCUSDT","t":12345,"p":"21800.00","q":"0.001","b":1,"a":2,"T":1676019600000,"m":true,"M":true}}"#;
+        let l2 = r#"{"stream":"btcusdt@depth@100ms","data":{"e":"depthUpdate","E":1676019600000,"s":"BTCUSDT","U":1,"u":2,"b":[["21800.00","1.0"]],"a":[["21801.00","0.5"],["21802.00","0"]]}}"#;
+
+        let mut sink = ParquetSink::new(&dir, 2, true).unwrap();
+        for i in 0..3 {
+            sink.write(&new_msg(MessageType::Trade, 1676019600000 + i, trade)).unwrap();
+        }
+        // the next day
+        sink.write(&new_msg(MessageType::Trade, 1676073600000, trade)).unwrap();
+        sink.write(&new_msg(MessageType::L2Event, 1676073600001, l2)).unwrap();
+        sink.close().unwrap();
+
+        let messages = dir.join("messages/exchange=binance/market_type=spot/msg_type=trade");
+        let readers = read_files(&messages.join("date=2023-02-10"));
+        // a full row group finalizes the file
+        assert_eq!(2, readers.len());
+        let metadata = readers[0].metadata();
+        assert_eq!(2, metadata.file_metadata().num_rows());
+        assert_eq!(1, metadata.num_row_groups());
+        assert_eq!(1, readers[1].metadata().file_metadata().num_rows());
+        let row = readers[0].get_row_iter(None).unwrap().next().unwrap();
+        assert_eq!("binance", row.get_string(0).unwrap());
+        assert_eq!(1676019600000, row.get_timestamp_millis(4).unwrap());
+        assert_eq!(trade, row.get_string(5).unwrap());
+        assert_eq!(1, read_files(&messages.join("date=2023-02-11")).len());
+
+        let trades = dir.join("trades/exchange=binance/market_type=spot/msg_type=trade");
+        let readers = read_files(&trades.join("date=2023-02-10"));
+        let row = readers[0].get_row_iter(None).unwrap().next().unwrap();
+        assert_eq!("BTC/USDT", row.get_string(3).unwrap());
+        assert_eq!("sell", row.get_string(5).unwrap());
+        assert_eq!(21800.0, row.get_double(6).unwrap());
+
+        let l2 = dir.join("l2/exchange=binance/market_type=spot/msg_type=l2_event");
+        let readers = read_files(&l2.join("date=2023-02-11"));
+        let mut num_rows: Vec<i64> =
+            readers.iter().map(|r| r.metadata().file_metadata().num_rows()).collect();
+        num_rows.sort();
+        assert_eq!(vec![1, 2], num_rows);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_while_busy() {
+        let dir = std::env::temp_dir().join(format!("parquet_sink_busy_{}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        let partition =
+            dir.join("messages/exchange=binance/market_type=spot/msg_type=trade/date=2023-02-10");
+        let finalized = || {
+            fs::read_dir(&partition)
+                .into_iter()
+                .flatten()
+                .any(|entry| !entry.unwrap().path().to_string_lossy().ends_with(PART_SUFFIX))
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sink = ParquetSink::new(&dir, 65536, false).unwrap();
+        let handle = std::thread::spawn(move || sink.run(rx).unwrap());
+        // messages of a past day keep arriving every 10ms
+        let deadline = Instant::now() + ROTATE_INTERVAL * 3;
+        while !finalized() && Instant::now() < deadline {
+            tx.send(new_msg(MessageType::Trade, 1676019600000, "{}")).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // rotated before the sender is dropped
+        assert!(finalized());
+        drop(tx);
+        handle.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
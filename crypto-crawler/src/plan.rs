@@ -0,0 +1,90 @@
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crawlers::{self, Route},
+    error::{Error, Result},
+};
+
+/// What a crawl would do, returned by `plan_crawl()` and
+/// `plan_candlestick()`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrawlPlan {
+    pub exchange: String,
+    pub market_type: MarketType,
+    pub msg_type: MessageType,
+    /// Number of symbols, fetched by `fetch_symbols_retry()` if no symbols
+    /// are specified
+    pub num_symbols: usize,
+    /// Max number of topics per connection, None means unlimited
+    pub topics_per_connection: Option<usize>,
+    /// Whether new and delisted symbols would be discovered every hour
+    pub symbol_discovery: bool,
+    /// One per websocket connection
+    pub connections: Vec<ConnectionPlan>,
+}
+
+/// A websocket connection of a `CrawlPlan`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionPlan {
+    pub symbols: Vec<String>,
+    /// Subscription commands, exactly as they would be sent
+    pub commands: Vec<String>,
+}
+
+/// Plans a crawl without running it.
+///
+/// Symbols are fetched through RESTful APIs as `crawl()` does, then split into
+/// connections, and subscription commands are generated by the websocket
+/// clients. The clients connect to a local endpoint which captures the
+/// commands, nothing is sent to the exchange.
+///
+/// Crawls of RESTful APIs, funding rates, and crawls with dedicated logic,
+/// e.g., binance all-market streams, can't be planned.
+pub async fn plan_crawl(
+    exchange: &str,
+    market_type: MarketType,
+    msg_type: MessageType,
+    symbols: Option<&[String]>,
+) -> Result<CrawlPlan> {
+    match msg_type {
+        MessageType::Trade
+        | MessageType::L2Event
+        | MessageType::L3Event
+        | MessageType::BBO
+        | MessageType::L2TopK
+        | MessageType::Ticker => match crawlers::route(exchange, market_type, msg_type, symbols)? {
+            Route::Generic(symbols) => {
+                crawlers::plan_event(exchange, msg_type, market_type, symbols.as_deref()).await
+            }
+            Route::Dedicated => Err(dedicated_crawler(exchange, market_type, msg_type)),
+        },
+        MessageType::Candlestick => {
+            let symbol_interval_list: Option<Vec<(String, usize)>> =
+                symbols.map(|symbols| symbols.iter().map(|symbol| (symbol.clone(), 60)).collect());
+            plan_candlestick(exchange, market_type, symbol_interval_list.as_deref()).await
+        }
+        _ => Err(Error(format!("Planning {msg_type} is NOT supported"))),
+    }
+}
+
+/// Plans a candlestick crawl without running it, see `plan_crawl()`.
+pub async fn plan_candlestick(
+    exchange: &str,
+    market_type: MarketType,
+    symbol_interval_list: Option<&[(String, usize)]>,
+) -> Result<CrawlPlan> {
+    match crawlers::route(exchange, market_type, MessageType::Candlestick, None)? {
+        Route::Generic(_) => {
+            crawlers::plan_candlestick_ext(exchange, market_type, symbol_interval_list).await
+        }
+        Route::Dedicated => Err(dedicated_crawler(exchange, market_type, MessageType::Candlestick)),
+    }
+}
+
+fn dedicated_crawler(exchange: &str, market_type: MarketType, msg_type: MessageType) -> Error {
+    Error(format!(
+        "{exchange} {market_type} {msg_type} has a dedicated crawler, which can't be planned"
+    ))
+}
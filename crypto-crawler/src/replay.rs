@@ -0,0 +1,222 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    ffi::OsStr,
+    fs::File,
+    io::{self, BufRead, BufReader, Lines},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use flate2::read::MultiGzDecoder;
+use log::*;
+
+use crate::{buffer::MessageSender, msg::Message};
+
+/// How fast to replay messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pace {
+    /// Emit messages without waiting.
+    AsFastAsPossible,
+    /// Keep the original intervals between messages, divided by `speed`,
+    /// e.g., 2.0 replays twice as fast.
+    Original { speed: f64 },
+}
+
+// Messages of one file
+struct Source {
+    exchange: String,
+    market_type: MarketType,
+    msg_type: MessageType,
+    path: PathBuf,
+    lines: Lines<Box<dyn BufRead + Send>>,
+}
+
+impl Source {
+    // The path must be exchange/market_type/msg_type/file, the layout of
+    // `FileSink`.
+    fn open(path: &Path) -> io::Result<Self> {
+        let invalid_path =
+            || io::Error::new(io::ErrorKind::InvalidInput, format!("{}", path.display()));
+        let components: Vec<&str> = path
+            .parent()
+            .ok_or_else(invalid_path)?
+            .iter()
+            .rev()
+            .take(3)
+            .map(|s| s.to_str().ok_or_else(invalid_path))
+            .collect::<io::Result<_>>()?;
+        if components.len() != 3 {
+            return Err(invalid_path());
+        }
+        let msg_type = MessageType::from_str(components[0]).map_err(|_| invalid_path())?;
+        let market_type = MarketType::from_str(components[1]).map_err(|_| invalid_path())?;
+
+        let file = File::open(path)?;
+        let reader: Box<dyn BufRead + Send> = match path.extension().and_then(OsStr::to_str) {
+            Some("gz") => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+            #[cfg(feature = "zstd")]
+            Some("zst") => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+            _ => Box::new(BufReader::new(file)),
+        };
+        Ok(Source {
+            exchange: components[2].to_string(),
+            market_type,
+            msg_type,
+            path: path.to_path_buf(),
+            lines: reader.lines(),
+        })
+    }
+
+    fn next_message(&mut self) -> io::Result<Option<Message>> {
+        for line in self.lines.by_ref() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            match Message::from_tsv_string(
+                &self.exchange,
+                &self.market_type.to_string(),
+                &self.msg_type.to_string(),
+                &line,
+            ) {
+                Ok(msg) => return Ok(Some(msg)),
+                Err(err) => warn!("Skipped a corrupted line in {}, {}", self.path.display(), err),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Replays archived files written by `FileSink`.
+///
+/// Messages of all files are merged by `received_at`, assuming each file is
+/// sorted. Plain `.tsv` files are supported too, as long as they are placed
+/// in the `exchange/market_type/msg_type/` layout.
+///
+/// Returns after all messages have been sent, or the receiver has been
+/// dropped.
+pub fn replay(files: &[PathBuf], pace: Pace, tx: impl Into<MessageSender>) -> io::Result<()> {
+    if let Pace::Original { speed } = pace {
+        assert!(speed > 0.0, "speed must be positive");
+    }
+    let tx = tx.into();
+    let mut sources =
+        files.iter().map(|path| Source::open(path)).collect::<io::Result<Vec<_>>>()?;
+
+    let mut heads: Vec<Option<Message>> = Vec::with_capacity(sources.len());
+    let mut heap = BinaryHeap::new();
+    for (i, source) in sources.iter_mut().enumerate() {
+        let msg = source.next_message()?;
+        if let Some(msg) = msg.as_ref() {
+            heap.push(Reverse((msg.received_at, i)));
+        }
+        heads.push(msg);
+    }
+
+    let mut start: Option<(Instant, u64)> = None;
+    while let Some(Reverse((received_at, i))) = heap.pop() {
+        let msg = heads[i].take().unwrap();
+        if let Pace::Original { speed } = pace {
+            let (instant, first) = *start.get_or_insert((Instant::now(), received_at));
+            let elapsed = received_at.saturating_sub(first) as f64 / speed;
+            let deadline = instant + Duration::from_secs_f64(elapsed / 1000.0);
+            let now = Instant::now();
+            if deadline > now {
+                std::thread::sleep(deadline - now);
+            }
+        }
+        if tx.send(msg).is_err() {
+            info!("The receiver has been dropped, stop replaying");
+            return Ok(());
+        }
+
+        let next = sources[i].next_message()?;
+        if let Some(next) = next.as_ref() {
+            if next.received_at < received_at {
+                warn!("{} is not sorted by received_at", sources[i].path.display());
+            }
+            heap.push(Reverse((next.received_at, i)));
+        }
+        heads[i] = next;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_sink::{Compression, FileSink};
+    use std::fs;
+
+    fn new_msg(msg_type: MessageType, received_at: u64) -> Message {
+        let mut msg = Message::new(
+            "binance".to_string(),
+            MarketType::Spot,
+            msg_type,
+            received_at.to_string(),
+        );
+        msg.received_at = received_at;
+        msg
+    }
+
+    fn write_files(name: &str, messages: &[Message]) -> (PathBuf, Vec<PathBuf>) {
+        let dir = std::env::temp_dir().join(format!("replay_{}_{}", name, std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        let mut sink = FileSink::new(&dir, Compression::Gzip).unwrap();
+        for msg in messages {
+            sink.write(msg).unwrap();
+        }
+        sink.close().unwrap();
+        let files = ["trade", "bbo"]
+            .iter()
+            .map(|msg_type| {
+                dir.join("binance").join("spot").join(msg_type).join("2023-02-10-09.tsv.gz")
+            })
+            .collect();
+        (dir, files)
+    }
+
+    #[test]
+    fn test_merge() {
+        let (dir, files) = write_files(
+            "merge",
+            &[
+                new_msg(MessageType::Trade, 1676019600001),
+                new_msg(MessageType::BBO, 1676019600002),
+                new_msg(MessageType::BBO, 1676019600003),
+                new_msg(MessageType::Trade, 1676019600004),
+            ],
+        );
+        let (tx, rx) = std::sync::mpsc::channel();
+        replay(&files, Pace::AsFastAsPossible, tx).unwrap();
+
+        let messages: Vec<Message> = rx.into_iter().collect();
+        let json: Vec<&str> = messages.iter().map(|msg| msg.json.as_str()).collect();
+        assert_eq!(vec!["1676019600001", "1676019600002", "1676019600003", "1676019600004"], json);
+        assert_eq!(MessageType::BBO, messages[1].msg_type);
+        assert_eq!("binance", messages[1].exchange);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pace() {
+        let (dir, files) = write_files(
+            "pace",
+            &[new_msg(MessageType::Trade, 1676019600000), new_msg(MessageType::BBO, 1676019604000)],
+        );
+        let (tx, rx) = std::sync::mpsc::channel();
+        let start = Instant::now();
+        replay(&files, Pace::Original { speed: 40.0 }, tx).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(2, rx.into_iter().count());
+        // 4 seconds at 40x is 100ms, the upper bound leaves room for slow CI
+        // but still fails if the speed is ignored
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(2));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    collections::VecDeque,
+    path::Path,
+    str::FromStr,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use rusqlite::{params, params_from_iter, types::Value, Connection, OpenFlags};
+
+use crate::{
+    error::{Error, Result},
+    msg::{Message, MessageFilter},
+};
+
+// Number of messages per transaction
+const BATCH_SIZE: usize = 1000;
+
+// How often to commit pending messages
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Number of rows fetched per query by `MessageIter`
+const PAGE_SIZE: usize = 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    exchange TEXT NOT NULL,
+    market_type TEXT NOT NULL,
+    msg_type TEXT NOT NULL,
+    symbol TEXT,
+    received_at INTEGER NOT NULL,
+    json TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_idx
+    ON messages (exchange, market_type, msg_type, symbol, received_at);
+CREATE INDEX IF NOT EXISTS messages_received_at_idx ON messages (received_at);
+";
+
+/// Writes messages to a SQLite database, for small captures.
+///
+/// Messages are inserted in batches, call `flush()` to commit pending
+/// messages. Use `SqliteReader` to query them.
+pub struct SqliteSink {
+    conn: Connection,
+    pending: usize, // number of messages in the open transaction
+}
+
+impl SqliteSink {
+    /// Opens or creates a database.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(SqliteSink { conn, pending: 0 })
+    }
+
+    /// Inserts a message.
+    pub fn write(&mut self, msg: &Message) -> Result<()> {
+        if self.pending == 0 {
+            self.conn.execute_batch("BEGIN")?;
+        }
+        self.conn
+            .prepare_cached(
+                "INSERT INTO messages (exchange, market_type, msg_type, symbol, received_at, json)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute(params![
+                msg.exchange,
+                msg.market_type.to_string(),
+                msg.msg_type.to_string(),
+                msg.extract_symbol(),
+                msg.received_at as i64,
+                msg.json,
+            ])?;
+        self.pending += 1;
+        if self.pending >= BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Commits pending messages.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.pending > 0 {
+            self.conn.execute_batch("COMMIT")?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Consumes messages until all senders have been dropped.
+    pub fn run(mut self, rx: Receiver<Message>) -> Result<()> {
+        let mut last_flush = Instant::now();
+        loop {
+            match rx.recv_timeout(FLUSH_INTERVAL.saturating_sub(last_flush.elapsed())) {
+                Ok(msg) => self.write(&msg)?,
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            // checked on every message, a busy receiver never times out
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                self.flush()?;
+                last_flush = Instant::now();
+            }
+        }
+        self.close()
+    }
+
+    /// Commits pending messages and closes the database.
+    pub fn close(mut self) -> Result<()> {
+        self.flush()?;
+        self.conn.close().map_err(|(_, err)| err.into())
+    }
+}
+
+/// Queries messages written by `SqliteSink`.
+pub struct SqliteReader {
+    conn: Connection,
+}
+
+impl SqliteReader {
+    /// Opens a database in read-only mode.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(SqliteReader { conn })
+    }
+
+    /// Returns messages matching `filter` with `received_at` in `[start, end)`,
+    /// in ascending order of `received_at`.
+    ///
+    /// Rows are fetched lazily, page by page.
+    pub fn query(&self, filter: &MessageFilter, start: u64, end: u64) -> MessageIter<'_> {
+        let mut conditions = vec!["received_at < ?".to_string()];
+        let mut values = vec![Value::Integer(end.min(i64::MAX as u64) as i64)];
+        let columns = [
+            ("exchange", filter.exchange.clone()),
+            ("market_type", filter.market_type.map(|x| x.to_string())),
+            ("msg_type", filter.msg_type.map(|x| x.to_string())),
+            ("symbol", filter.symbol.clone()),
+        ];
+        for (column, value) in columns {
+            if let Some(value) = value {
+                conditions.push(format!("{column} = ?"));
+                values.push(Value::Text(value));
+            }
+        }
+        let sql = format!(
+            "SELECT rowid, exchange, market_type, msg_type, symbol, received_at, json
+            FROM messages
+            WHERE {} AND (received_at > ? OR (received_at = ? AND rowid > ?))
+            ORDER BY received_at, rowid LIMIT {}",
+            conditions.join(" AND "),
+            PAGE_SIZE
+        );
+        MessageIter {
+            conn: &self.conn,
+            sql,
+            values,
+            cursor: (start as i64, -1),
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+/// An iterator over messages returned by `SqliteReader::query()`.
+pub struct MessageIter<'a> {
+    conn: &'a Connection,
+    sql: String,
+    values: Vec<Value>,
+    cursor: (i64, i64), // (received_at, rowid) of the last row
+    buffer: VecDeque<Message>,
+    done: bool,
+}
+
+impl<'a> MessageIter<'a> {
+    fn fetch(&mut self) -> Result<()> {
+        let mut stmt = self.conn.prepare_cached(&self.sql)?;
+        let (received_at, rowid) = self.cursor;
+        // the first page includes messages at `start`
+        let lower = if rowid < 0 { received_at - 1 } else { received_at };
+        let mut values = self.values.clone();
+        values.extend([Value::Integer(lower), Value::Integer(received_at), Value::Integer(rowid)]);
+
+        let mut rows = stmt.query(params_from_iter(values.iter()))?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let market_type: String = row.get(2)?;
+            let msg_type: String = row.get(3)?;
+            let msg = Message {
+                exchange: row.get(1)?,
+                market_type: MarketType::from_str(&market_type)
+                    .map_err(|_| Error(format!("Unknown market type {market_type}")))?,
+                msg_type: MessageType::from_str(&msg_type)
+                    .map_err(|_| Error(format!("Unknown message type {msg_type}")))?,
+                symbol: row.get(4)?,
+                received_at: row.get::<_, i64>(5)? as u64,
+                json: row.get(6)?,
+            };
+            self.cursor = (msg.received_at as i64, row.get(0)?);
+            self.buffer.push_back(msg);
+            count += 1;
+        }
+        self.done = count < PAGE_SIZE;
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for MessageIter<'a> {
+    type Item = Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() && !self.done {
+            if let Err(err) = self.fetch() {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_msg(msg_type: MessageType, symbol: &str, received_at: u64) -> Message {
+        let mut msg = Message::new_with_symbol(
+            "binance".to_string(),
+            MarketType::Spot,
+            msg_type,
+            symbol.to_string(),
+            received_at.to_string(),
+        );
+        msg.received_at = received_at;
+        msg
+    }
+
+    #[test]
+    fn test_flush_while_busy() {
+        let path = std::env::temp_dir().join(format!("sqlite_sink_busy_{}.db", std::process::id()));
+        _ = std::fs::remove_file(&path);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let sink = SqliteSink::new(&path).unwrap();
+        let handle = std::thread::spawn(move || sink.run(rx).unwrap());
+        let reader = SqliteReader::open(&path).unwrap();
+        let committed = || reader.query(&MessageFilter::default(), 0, u64::MAX).count() > 0;
+        // fewer messages than BATCH_SIZE, but one every 10ms
+        let deadline = Instant::now() + FLUSH_INTERVAL * 3;
+        while !committed() && Instant::now() < deadline {
+            tx.send(new_msg(MessageType::Trade, "BTCUSDT", 1000)).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // committed before the sender is dropped
+        assert!(committed());
+        drop(tx);
+        handle.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_query() {
+        let path = std::env::temp_dir().join(format!("sqlite_sink_{}.db", std::process::id()));
+        _ = std::fs::remove_file(&path);
+        let mut sink = SqliteSink::new(&path).unwrap();
+        // more than a page of messages with the same timestamp
+        for _ in 0..PAGE_SIZE + 1 {
+            sink.write(&new_msg(MessageType::Trade, "BTCUSDT", 1000)).unwrap();
+        }
+        for i in 0..10 {
+            sink.write(&new_msg(MessageType::Trade, "BTCUSDT", 2000 + i)).unwrap();
+            sink.write(&new_msg(MessageType::Trade, "ETHUSDT", 2000 + i)).unwrap();
+            sink.write(&new_msg(MessageType::BBO, "BTCUSDT", 2000 + i)).unwrap();
+        }
+        sink.close().unwrap();
+
+        let reader = SqliteReader::open(&path).unwrap();
+        let all = MessageFilter::default();
+        assert_eq!(PAGE_SIZE + 31, reader.query(&all, 0, u64::MAX).count());
+        assert_eq!(PAGE_SIZE + 1, reader.query(&all, 1000, 1001).count());
+
+        let filter = MessageFilter {
+            msg_type: Some(MessageType::Trade),
+            symbol: Some("BTCUSDT".to_string()),
+            ..Default::default()
+        };
+        let messages: Vec<Message> =
+            reader.query(&filter, 2003, 2006).collect::<Result<_>>().unwrap();
+        let json: Vec<&str> = messages.iter().map(|msg| msg.json.as_str()).collect();
+        assert_eq!(vec!["2003", "2004", "2005"], json);
+        assert!(messages.iter().all(|msg| filter.matches(msg)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
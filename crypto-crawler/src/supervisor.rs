@@ -0,0 +1,191 @@
+use std::{any::Any, collections::VecDeque, future::Future, time::Duration};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::event::{emit_supervisor_event, SupervisorEvent};
+
+/// How `supervise()` restarts a failed task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Delay before the first restart, doubled after each consecutive failure
+    pub min_backoff_ms: u64,
+    /// Upper bound of the delay, a task running longer than this is considered
+    /// healthy and the delay is reset
+    pub max_backoff_ms: u64,
+    /// Give up if the task has been restarted this many times within
+    /// `period_secs`
+    pub max_restarts: usize,
+    pub period_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            min_backoff_ms: 1000,
+            max_backoff_ms: 60000,
+            max_restarts: 10,
+            period_secs: 600,
+        }
+    }
+}
+
+impl RestartPolicy {
+    fn backoff(&self, consecutive_failures: u32) -> Duration {
+        let delay = self.min_backoff_ms.saturating_mul(1 << consecutive_failures.min(32));
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+}
+
+/// Runs a task created by `make_task`, and creates a new one each time it
+/// panics or exits.
+///
+/// Failures and restarts are reported via `subscribe_supervisor_events()`.
+/// Returns after giving up, dropping the returned future aborts the task.
+///
+/// ```no_run
+/// use crypto_crawler::{crawl_trade, supervise, MarketType, RestartPolicy};
+///
+/// # async fn example() {
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// supervise("binance trade", &RestartPolicy::default(), move || {
+///     let tx = tx.clone();
+///     async move { crawl_trade("binance", MarketType::Spot, None, tx).await }
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn supervise<F, Fut>(name: &str, policy: &RestartPolicy, mut make_task: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let period = Duration::from_secs(policy.period_secs);
+    let mut restarts: VecDeque<Instant> = VecDeque::new();
+    let mut consecutive_failures = 0u32;
+    loop {
+        let started = Instant::now();
+        let task = tokio::task::spawn(make_task());
+        let _guard = AbortOnDrop(task.abort_handle());
+        let reason = match task.await {
+            Ok(()) => "exited".to_string(),
+            Err(err) if err.is_panic() => panic_message(err.into_panic()),
+            Err(err) => err.to_string(),
+        };
+        warn!("Task {} failed, {}", name, reason);
+        emit_supervisor_event(SupervisorEvent::Failed { task: name.to_string(), reason });
+
+        if started.elapsed() >= Duration::from_millis(policy.max_backoff_ms) {
+            consecutive_failures = 0;
+        }
+        let now = Instant::now();
+        while restarts.front().is_some_and(|t| now.duration_since(*t) > period) {
+            restarts.pop_front();
+        }
+        if restarts.len() >= policy.max_restarts {
+            error!("Task {} restarted {} times, giving up", name, restarts.len());
+            emit_supervisor_event(SupervisorEvent::GaveUp {
+                task: name.to_string(),
+                restarts: restarts.len(),
+            });
+            return;
+        }
+        restarts.push_back(now);
+
+        let delay = policy.backoff(consecutive_failures);
+        consecutive_failures += 1;
+        info!("Restarting task {} in {:?}", name, delay);
+        emit_supervisor_event(SupervisorEvent::Restarting {
+            task: name.to_string(),
+            attempt: consecutive_failures,
+            delay_ms: delay.as_millis() as u64,
+        });
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+// Aborts a task when dropped.
+pub(crate) struct AbortOnDrop(pub(crate) tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::subscribe_supervisor_events;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn test_supervise() {
+        let mut events = subscribe_supervisor_events();
+        let policy = RestartPolicy { max_restarts: 3, ..Default::default() };
+        let runs = Arc::new(AtomicUsize::new(0));
+        let started = Instant::now();
+        supervise("test_supervise", &policy, || {
+            let runs = runs.clone();
+            async move {
+                if matches!(runs.fetch_add(1, Ordering::SeqCst), 0 | 2) {
+                    panic!("boom");
+                }
+            }
+        })
+        .await;
+        assert_eq!(4, runs.load(Ordering::SeqCst));
+        // 1s + 2s + 4s
+        assert_eq!(Duration::from_secs(7), started.elapsed());
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match &event {
+                SupervisorEvent::Failed { task, .. }
+                | SupervisorEvent::Restarting { task, .. }
+                | SupervisorEvent::GaveUp { task, .. } => {
+                    if task == "test_supervise" {
+                        received.push(event);
+                    }
+                }
+            }
+        }
+        let failed = |reason: &str| SupervisorEvent::Failed {
+            task: "test_supervise".to_string(),
+            reason: reason.to_string(),
+        };
+        let restarting = |attempt, delay_ms| SupervisorEvent::Restarting {
+            task: "test_supervise".to_string(),
+            attempt,
+            delay_ms,
+        };
+        assert_eq!(
+            vec![
+                failed("boom"),
+                restarting(1, 1000),
+                failed("exited"),
+                restarting(2, 2000),
+                failed("boom"),
+                restarting(3, 4000),
+                failed("exited"),
+                SupervisorEvent::GaveUp { task: "test_supervise".to_string(), restarts: 3 },
+            ],
+            received
+        );
+    }
+}
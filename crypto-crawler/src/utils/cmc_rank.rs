@@ -321,9 +321,9 @@ pub(crate) fn sort_by_cmc_rank(exchange: &str, symbols: &mut [String]) {
     symbols.sort_by_key(|symbol| {
         if let Some(pair) = crypto_pair::normalize_pair(symbol, exchange) {
             let base = pair.split('/').next().unwrap();
-            *CMC_RANKS.get(base).unwrap_or(&u64::max_value())
+            *CMC_RANKS.get(base).unwrap_or(&u64::MAX)
         } else {
-            u64::max_value()
+            u64::MAX
         }
     });
 }
@@ -336,7 +336,7 @@ mod tests {
     fn test_get_cmc_ranks() {
         let mapping = super::get_cmc_ranks(256);
         let mut v = Vec::from_iter(mapping);
-        v.sort_by(|&(_, a), &(_, b)| a.cmp(&b));
+        v.sort_by_key(|&(_, rank)| rank);
         for (coin, rank) in v {
             println!("(\"{coin}\", {rank}),");
         }
@@ -141,7 +141,7 @@ fn create_all_lock_files_rest()
     let mut result: HashMap<String, HashMap<MarketType, Arc<std::sync::Mutex<LockFile>>>> =
         HashMap::new();
     for exchange in EXCHANGES.iter() {
-        let m = result.entry(exchange.to_string()).or_insert_with(HashMap::new);
+        let m = result.entry(exchange.to_string()).or_default();
         let mut market_types = crypto_market_type::get_market_types(exchange);
         if *exchange == "bitmex" {
             market_types.push(MarketType::Unknown);
@@ -171,7 +171,7 @@ fn create_all_lock_files_ws()
     let mut result: HashMap<String, HashMap<MarketType, Arc<tokio::sync::Mutex<LockFile>>>> =
         HashMap::new();
     for exchange in EXCHANGES_WS.iter() {
-        let m = result.entry(exchange.to_string()).or_insert_with(HashMap::new);
+        let m = result.entry(exchange.to_string()).or_default();
         let mut market_types = crypto_market_type::get_market_types(exchange);
         if *exchange == "bitmex" {
             market_types.push(MarketType::Unknown);
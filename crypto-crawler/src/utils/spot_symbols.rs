@@ -21,10 +21,10 @@ pub fn get_hot_spot_symbols(exchange: &str, spot_symbols: &[String]) -> Vec<Stri
         let pair = crypto_pair::normalize_pair(symbol, exchange).unwrap();
         let base_coin = pair.split('/').next().unwrap();
         contract_base_coins.contains(base_coin)
-            || *cmc_ranks.get(base_coin).unwrap_or(&u64::max_value()) <= 100
+            || *cmc_ranks.get(base_coin).unwrap_or(&u64::MAX) <= 100
     };
 
-    spot_symbols.iter().cloned().filter(|symbol| is_hot(symbol)).collect()
+    spot_symbols.iter().filter(|symbol| is_hot(symbol)).cloned().collect()
 }
 
 #[cfg(test)]
@@ -19,7 +19,7 @@ use strum_macros::{Display, EnumString};
 /// ## Linear VS. Inverse
 ///
 /// A market can be inverse or linear.
-
+///
 /// * Linear means USDT-margined, i.e., you can use USDT as collateral
 /// * Inverse means coin-margined, i.e., you can use BTC as collateral.
 /// * Spot market is always linear.
@@ -0,0 +1,407 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use crypto_market_type::MarketType;
+use log::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{error::Result, fetch_markets, fetch_symbols, market::Market};
+
+// How long a process waits for another process which is fetching the same
+// entry, a lock file older than this is considered abandoned.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+// How often the lock holder refreshes the mtime of its lock file, so that a
+// slow fetch is not mistaken for an abandoned lock.
+const LOCK_HEARTBEAT: Duration = Duration::from_secs(10);
+
+// Prefixes of the files written by MarketCache
+const FILE_PREFIXES: [&str; 2] = ["markets_", "symbols_"];
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry<T> {
+    /// Unix timestamp in milliseconds
+    fetched_at: u64,
+    value: T,
+}
+
+impl<T> Entry<T> {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        now_ms().saturating_sub(self.fetched_at) < ttl.as_millis() as u64
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+// Keyed by exchange and market type
+type Entries<T> = Mutex<HashMap<(String, MarketType), Entry<T>>>;
+
+// Refreshes the mtime of the lock file until dropped, then removes it.
+struct FileLock {
+    path: PathBuf,
+    // written into the lock file, so that a lock taken over by another process
+    // is not removed
+    token: String,
+    // the heartbeat thread exits once this is dropped
+    _stop_tx: mpsc::Sender<()>,
+}
+
+impl FileLock {
+    fn try_lock(path: PathBuf) -> Option<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let token = format!("{}-{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+        if Self::create(&path, &token) {
+            return Some(FileLock::new(path, token));
+        }
+        if !is_abandoned(&path) {
+            return None;
+        }
+        // Only one of the processes taking over an abandoned lock file can
+        // rename it, the others fail
+        let stale = path.with_extension(format!("{token}.stale"));
+        fs::rename(&path, &stale).ok()?;
+        if !is_abandoned(&stale) {
+            // another process took it over between the check and the rename,
+            // put it back unless a new lock file has been created meanwhile
+            let _ = fs::hard_link(&stale, &path);
+            let _ = fs::remove_file(&stale);
+            return None;
+        }
+        let _ = fs::remove_file(&stale);
+        if Self::create(&path, &token) {
+            Some(FileLock::new(path, token))
+        } else {
+            None
+        }
+    }
+
+    fn create(path: &Path, token: &str) -> bool {
+        File::options()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(token.as_bytes()))
+            .is_ok()
+    }
+
+    fn new(path: PathBuf, token: String) -> Self {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let lock_path = path.clone();
+        std::thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(LOCK_HEARTBEAT) {
+                if let Ok(file) = File::options().write(true).open(&lock_path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+            }
+        });
+        FileLock { path, token, _stop_tx: stop_tx }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if fs::read_to_string(&self.path).is_ok_and(|token| token == self.token) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn is_abandoned(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map(|modified| modified.elapsed().unwrap_or_default() > LOCK_TIMEOUT)
+        .unwrap_or(false)
+}
+
+/// A cache of `fetch_markets()` and `fetch_symbols()`.
+///
+/// Entries are kept in memory for `ttl`. With `with_dir()`, entries are also
+/// saved as JSON files under the directory, so processes sharing the
+/// directory fetch each entry only once per `ttl`, and while one process is
+/// fetching, the others wait for it instead of hitting the same RESTful API.
+///
+/// If fetching fails, the latest entry is returned even if it has expired, an
+/// error is returned only if there is no entry at all.
+pub struct MarketCache {
+    ttl: Duration,
+    dir: Option<PathBuf>,
+    markets: Entries<Vec<Market>>,
+    symbols: Entries<Vec<String>>,
+}
+
+impl MarketCache {
+    /// Creates an in-memory cache.
+    pub fn new(ttl: Duration) -> Self {
+        MarketCache {
+            ttl,
+            dir: None,
+            markets: Mutex::new(HashMap::new()),
+            symbols: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a cache persisted under `dir`, which is created if it doesn't
+    /// exist.
+    pub fn with_dir(ttl: Duration, dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(MarketCache { dir: Some(dir.as_ref().to_path_buf()), ..Self::new(ttl) })
+    }
+
+    /// Cached `fetch_markets()`.
+    pub fn fetch_markets(&self, exchange: &str, market_type: MarketType) -> Result<Vec<Market>> {
+        self.get(&self.markets, "markets", exchange, market_type, || {
+            fetch_markets(exchange, market_type)
+        })
+    }
+
+    /// Cached `fetch_symbols()`.
+    pub fn fetch_symbols(&self, exchange: &str, market_type: MarketType) -> Result<Vec<String>> {
+        self.get(&self.symbols, "symbols", exchange, market_type, || {
+            fetch_symbols(exchange, market_type)
+        })
+    }
+
+    /// Removes all entries from memory and disk, other files in the directory
+    /// are left untouched.
+    pub fn clear(&self) -> Result<()> {
+        self.markets.lock().unwrap().clear();
+        self.symbols.lock().unwrap().clear();
+        if let Some(dir) = self.dir.as_ref() {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                let is_entry = path.extension().is_some_and(|ext| ext == "json")
+                    && path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+                        FILE_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+                    });
+                if is_entry {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get<T, F>(
+        &self,
+        memory: &Entries<T>,
+        kind: &str,
+        exchange: &str,
+        market_type: MarketType,
+        fetch: F,
+    ) -> Result<T>
+    where
+        T: Clone + Serialize + DeserializeOwned,
+        F: Fn() -> Result<T>,
+    {
+        let key = (exchange.to_string(), market_type);
+        let mut stale = match memory.lock().unwrap().get(&key) {
+            Some(entry) if entry.is_fresh(self.ttl) => return Ok(entry.value.clone()),
+            Some(entry) => Some(entry.clone()),
+            None => None,
+        };
+
+        let path =
+            self.dir.as_ref().map(|dir| dir.join(format!("{kind}_{exchange}_{market_type}.json")));
+        // held until the new entry is written
+        let mut _lock = None;
+        if let Some(path) = path.as_ref() {
+            match self.read_or_lock::<T>(path) {
+                OnDisk::Fresh(entry) => {
+                    let value = entry.value.clone();
+                    memory.lock().unwrap().insert(key, entry);
+                    return Ok(value);
+                }
+                OnDisk::Expired { entry, lock } => {
+                    if stale.as_ref().map(|e| e.fetched_at) < entry.as_ref().map(|e| e.fetched_at) {
+                        stale = entry;
+                    }
+                    _lock = lock;
+                }
+            }
+        }
+
+        match fetch() {
+            Ok(value) => {
+                let entry = Entry { fetched_at: now_ms(), value: value.clone() };
+                if let Some(path) = path.as_ref() {
+                    if let Err(err) = write_entry(path, &entry) {
+                        warn!("Failed to write {}, {}", path.display(), err);
+                    }
+                }
+                memory.lock().unwrap().insert(key, entry);
+                Ok(value)
+            }
+            Err(err) => match stale {
+                Some(entry) => {
+                    warn!(
+                        "Failed to fetch {} {} {}, {}, fall back to the entry fetched at {}",
+                        exchange, market_type, kind, err, entry.fetched_at
+                    );
+                    Ok(entry.value)
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    // Waits until the entry on disk is fresh, or the lock is acquired so that
+    // this process fetches it.
+    fn read_or_lock<T: DeserializeOwned>(&self, path: &Path) -> OnDisk<T> {
+        let lock_path = path.with_extension("lock");
+        let deadline = SystemTime::now() + LOCK_TIMEOUT;
+        loop {
+            let entry = read_entry::<T>(path);
+            if entry.as_ref().is_some_and(|entry| entry.is_fresh(self.ttl)) {
+                return OnDisk::Fresh(entry.unwrap());
+            }
+            if let Some(lock) = FileLock::try_lock(lock_path.clone()) {
+                return OnDisk::Expired { entry, lock: Some(lock) };
+            }
+            if SystemTime::now() > deadline {
+                // the other process is probably stuck, fetch without the lock
+                return OnDisk::Expired { entry, lock: None };
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+enum OnDisk<T> {
+    Fresh(Entry<T>),
+    /// Expired or missing
+    Expired {
+        entry: Option<Entry<T>>,
+        lock: Option<FileLock>,
+    },
+}
+
+fn read_entry<T: DeserializeOwned>(path: &Path) -> Option<Entry<T>> {
+    let text = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            warn!("Ignored corrupted {}, {}", path.display(), err);
+            None
+        }
+    }
+}
+
+// Writes to a temporary file then renames it, so that readers never see a
+// partially written file.
+fn write_entry<T: Serialize>(path: &Path, entry: &Entry<T>) -> Result<()> {
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp_path, serde_json::to_string(entry)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::Error;
+
+    fn get(cache: &MarketCache, fetch: impl Fn() -> Result<Vec<String>>) -> Result<Vec<String>> {
+        cache.get(&cache.symbols, "symbols", "binance", MarketType::Spot, fetch)
+    }
+
+    #[test]
+    fn test_memory_cache() {
+        let cache = MarketCache::new(Duration::from_secs(3600));
+        let calls = Cell::new(0);
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            Ok(vec!["BTCUSDT".to_string()])
+        };
+        assert_eq!(vec!["BTCUSDT"], get(&cache, fetch).unwrap());
+        assert_eq!(vec!["BTCUSDT"], get(&cache, fetch).unwrap());
+        assert_eq!(1, calls.get());
+
+        let expired = MarketCache::new(Duration::ZERO);
+        get(&expired, || Ok(vec!["BTCUSDT".to_string()])).unwrap();
+        // stale while the RESTful API is down
+        let down = || Err(Error("503 Service Unavailable".to_string()));
+        assert_eq!(vec!["BTCUSDT"], get(&expired, down).unwrap());
+        assert!(get(&MarketCache::new(Duration::ZERO), down).is_err());
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("market_cache_{}", std::process::id()));
+        let calls = Cell::new(0);
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            Ok(vec!["BTCUSDT".to_string()])
+        };
+        let cache = MarketCache::with_dir(Duration::from_secs(3600), &dir).unwrap();
+        get(&cache, fetch).unwrap();
+        // another process sharing the same directory
+        let other = MarketCache::with_dir(Duration::from_secs(3600), &dir).unwrap();
+        assert_eq!(vec!["BTCUSDT"], get(&other, fetch).unwrap());
+        assert_eq!(1, calls.get());
+
+        // an expired entry on disk is used if the RESTful API is down
+        let expired = MarketCache::with_dir(Duration::ZERO, &dir).unwrap();
+        let down = || Err(Error("503 Service Unavailable".to_string()));
+        assert_eq!(vec!["BTCUSDT"], get(&expired, down).unwrap());
+
+        // files not written by the cache survive clear()
+        let unrelated = dir.join("config.json");
+        fs::write(&unrelated, "{}").unwrap();
+        cache.clear().unwrap();
+        assert!(get(&expired, down).is_err());
+        assert!(unrelated.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock() {
+        let dir = std::env::temp_dir().join(format!("market_cache_lock_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("symbols_binance_spot.lock");
+
+        let lock = FileLock::try_lock(path.clone()).unwrap();
+        assert!(FileLock::try_lock(path.clone()).is_none());
+        // a lock file older than LOCK_TIMEOUT is taken over
+        let old = SystemTime::now() - LOCK_TIMEOUT * 2;
+        File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+        let taken_over = FileLock::try_lock(path.clone()).unwrap();
+        // the lock taken over is kept
+        drop(lock);
+        assert!(path.exists());
+        drop(taken_over);
+        assert!(!path.exists());
+
+        // only one of the processes taking over an abandoned lock succeeds
+        let lock = FileLock::try_lock(path.clone()).unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+        let barrier = std::sync::Barrier::new(8);
+        let locks: Vec<FileLock> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    s.spawn(|| {
+                        barrier.wait();
+                        FileLock::try_lock(path.clone())
+                    })
+                })
+                .collect();
+            handles.into_iter().filter_map(|handle| handle.join().unwrap()).collect()
+        });
+        assert_eq!(1, locks.len());
+        drop(lock);
+        drop(locks);
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::mpsc::Sender,
+    time::Duration,
+};
+
+use crypto_market_type::MarketType;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    fetch_markets,
+    market::{Market, Precision, QuantityLimit},
+};
+
+/// A difference between two snapshots of markets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum MarketChange {
+    /// A market only exists in the new snapshot.
+    Listed { market: Market },
+    /// A market only exists in the old snapshot.
+    Delisted { market: Market },
+    /// Tick size or lot size has changed.
+    PrecisionChanged {
+        exchange: String,
+        market_type: MarketType,
+        symbol: String,
+        old: Precision,
+        new: Precision,
+    },
+    /// Quantity or notional limits have changed.
+    LimitChanged {
+        exchange: String,
+        market_type: MarketType,
+        symbol: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        old: Option<QuantityLimit>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        new: Option<QuantityLimit>,
+    },
+    /// The market has been activated or deactivated.
+    StatusChanged {
+        exchange: String,
+        market_type: MarketType,
+        symbol: String,
+        old: bool,
+        new: bool,
+    },
+}
+
+// Markets are identified by exchange, market type and symbol
+fn key(market: &Market) -> (String, String, String) {
+    (market.exchange.clone(), market.market_type.to_string(), market.symbol.clone())
+}
+
+/// Compares two snapshots of markets.
+///
+/// Markets are identified by exchange, market type and symbol, changes are
+/// sorted by them. A market may have multiple changes, e.g., both precision
+/// and limits have changed.
+pub fn diff_markets(old: &[Market], new: &[Market]) -> Vec<MarketChange> {
+    let old_markets: BTreeMap<_, &Market> = old.iter().map(|m| (key(m), m)).collect();
+    let new_markets: BTreeMap<_, &Market> = new.iter().map(|m| (key(m), m)).collect();
+    let keys: BTreeSet<_> = old_markets.keys().chain(new_markets.keys()).collect();
+
+    let mut changes = Vec::new();
+    for k in keys {
+        match (old_markets.get(k), new_markets.get(k)) {
+            (Some(market), None) => {
+                changes.push(MarketChange::Delisted { market: (*market).clone() })
+            }
+            (None, Some(market)) => {
+                changes.push(MarketChange::Listed { market: (*market).clone() })
+            }
+            (Some(old), Some(new)) => {
+                let (exchange, market_type, symbol) =
+                    (new.exchange.clone(), new.market_type, new.symbol.clone());
+                if old.precision != new.precision {
+                    changes.push(MarketChange::PrecisionChanged {
+                        exchange: exchange.clone(),
+                        market_type,
+                        symbol: symbol.clone(),
+                        old: old.precision.clone(),
+                        new: new.precision.clone(),
+                    });
+                }
+                if old.quantity_limit != new.quantity_limit {
+                    changes.push(MarketChange::LimitChanged {
+                        exchange: exchange.clone(),
+                        market_type,
+                        symbol: symbol.clone(),
+                        old: old.quantity_limit.clone(),
+                        new: new.quantity_limit.clone(),
+                    });
+                }
+                if old.active != new.active {
+                    changes.push(MarketChange::StatusChanged {
+                        exchange,
+                        market_type,
+                        symbol,
+                        old: old.active,
+                        new: new.active,
+                    });
+                }
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    changes
+}
+
+/// Polls `fetch_markets()` and reports changes between consecutive
+/// snapshots.
+pub struct MarketPoller {
+    exchange: String,
+    market_type: MarketType,
+    snapshot: Option<Vec<Market>>,
+}
+
+impl MarketPoller {
+    pub fn new(exchange: &str, market_type: MarketType) -> Self {
+        MarketPoller { exchange: exchange.to_string(), market_type, snapshot: None }
+    }
+
+    /// Starts from a previously saved snapshot instead of the first poll, so
+    /// that changes during downtime are reported.
+    pub fn with_snapshot(exchange: &str, market_type: MarketType, markets: Vec<Market>) -> Self {
+        MarketPoller { exchange: exchange.to_string(), market_type, snapshot: Some(markets) }
+    }
+
+    /// The latest snapshot.
+    pub fn snapshot(&self) -> Option<&[Market]> {
+        self.snapshot.as_deref()
+    }
+
+    /// Fetches markets and returns changes since the previous poll.
+    ///
+    /// The first poll only takes a snapshot and returns no changes.
+    pub fn poll(&mut self) -> Result<Vec<MarketChange>> {
+        let markets = fetch_markets(&self.exchange, self.market_type)?;
+        Ok(self.update(markets))
+    }
+
+    /// Polls every `interval` and sends changes to `tx`, until `tx` is
+    /// disconnected.
+    ///
+    /// Failed polls are logged and skipped, the next poll compares with the
+    /// latest successful one.
+    pub fn run(mut self, interval: Duration, tx: Sender<MarketChange>) {
+        loop {
+            match self.poll() {
+                Ok(changes) => {
+                    for change in changes {
+                        if tx.send(change).is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to fetch {} {} markets, {}",
+                        self.exchange, self.market_type, err
+                    )
+                }
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    fn update(&mut self, markets: Vec<Market>) -> Vec<MarketChange> {
+        let changes = match self.snapshot.as_ref() {
+            Some(snapshot) => diff_markets(snapshot, &markets),
+            None => Vec::new(),
+        };
+        self.snapshot = Some(markets);
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::Fees;
+
+    fn new_market(symbol: &str, tick_size: f64) -> Market {
+        Market {
+            exchange: "binance".to_string(),
+            market_type: MarketType::Spot,
+            symbol: symbol.to_string(),
+            base_id: symbol[..3].to_string(),
+            quote_id: "USDT".to_string(),
+            settle_id: None,
+            base: symbol[..3].to_string(),
+            quote: "USDT".to_string(),
+            settle: None,
+            active: true,
+            margin: false,
+            fees: Fees { maker: 0.001, taker: 0.001 },
+            precision: Precision::from_f64(tick_size, 0.0001),
+            quantity_limit: None,
+            contract_value: None,
+            delivery_date: None,
+            settlement_type: None,
+            funding_interval: None,
+            max_leverage: None,
+            quanto_multiplier: None,
+            option: None,
+            info: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_markets() {
+        let old = vec![new_market("BTCUSDT", 0.01), new_market("ETHUSDT", 0.01)];
+        let new = vec![new_market("BTCUSDT", 0.1), new_market("SOLUSDT", 0.01)];
+        let changes = diff_markets(&old, &new);
+        assert_eq!(
+            vec![
+                MarketChange::PrecisionChanged {
+                    exchange: "binance".to_string(),
+                    market_type: MarketType::Spot,
+                    symbol: "BTCUSDT".to_string(),
+                    old: Precision::from_f64(0.01, 0.0001),
+                    new: Precision::from_f64(0.1, 0.0001),
+                },
+                MarketChange::Delisted { market: new_market("ETHUSDT", 0.01) },
+                MarketChange::Listed { market: new_market("SOLUSDT", 0.01) },
+            ],
+            changes
+        );
+        assert!(diff_markets(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_limit_and_status_changed() {
+        let old = vec![new_market("BTCUSDT", 0.01)];
+        let mut market = new_market("BTCUSDT", 0.01);
+        let limit = QuantityLimit::from_f64(Some(0.001), None, None, None);
+        market.quantity_limit = Some(limit.clone());
+        market.active = false;
+        let new = vec![market];
+
+        let mut poller = MarketPoller::new("binance", MarketType::Spot);
+        assert!(poller.update(old).is_empty());
+        assert_eq!(
+            vec![
+                MarketChange::LimitChanged {
+                    exchange: "binance".to_string(),
+                    market_type: MarketType::Spot,
+                    symbol: "BTCUSDT".to_string(),
+                    old: None,
+                    new: Some(limit),
+                },
+                MarketChange::StatusChanged {
+                    exchange: "binance".to_string(),
+                    market_type: MarketType::Spot,
+                    symbol: "BTCUSDT".to_string(),
+                    old: true,
+                    new: false,
+                },
+            ],
+            poller.update(new.clone())
+        );
+        assert!(poller.update(new).is_empty());
+    }
+}
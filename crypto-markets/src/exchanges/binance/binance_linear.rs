@@ -87,24 +87,27 @@ fn fetch_linear_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://www.binance.com/en/fee/futureFee
                 fees: Fees { maker: 0.0002, taker: 0.0004 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.pricePrecision as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.quantityPrecision as u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: parse_filter(&m.filters, "LOT_SIZE", "minQty").parse::<f64>().ok(),
-                    max: Some(
-                        parse_filter(&m.filters, "LOT_SIZE", "maxQty").parse::<f64>().unwrap(),
-                    ),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::parse(
+                    parse_filter(&m.filters, "PRICE_FILTER", "tickSize"),
+                    parse_filter(&m.filters, "LOT_SIZE", "stepSize"),
+                ),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(parse_filter(&m.filters, "LOT_SIZE", "minQty")),
+                    Some(parse_filter(&m.filters, "LOT_SIZE", "maxQty")),
+                    None,
+                    None,
+                )),
                 contract_value: Some(1.0),
                 delivery_date: if m.contractType == "PERPETUAL" {
                     None
                 } else {
                     Some(m.deliveryDate)
                 },
+                settlement_type: Some(SettlementType::Linear),
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
             }
         })
@@ -4,10 +4,6 @@ use crate::{error::Result, market::*, Market, MarketType};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-#[derive(Serialize, Deserialize)]
-struct BinanceResponse<T: Sized> {
-    symbols: Vec<T>,
-}
 
 #[derive(Serialize, Deserialize)]
 #[allow(non_snake_case)]
@@ -23,6 +19,7 @@ struct OptionMarket {
     priceScale: i64,
     quantityScale: i64,
     side: String,
+    strikePrice: String,
     makerFeeRate: String,
     takerFeeRate: String,
     expiryDate: u64,
@@ -82,18 +79,30 @@ pub(super) fn fetch_option_markets() -> Result<Vec<Market>> {
                     maker: m.makerFeeRate.parse::<f64>().unwrap(),
                     taker: m.takerFeeRate.parse::<f64>().unwrap(),
                 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.priceScale as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.quantityScale as u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.minQty.parse::<f64>().ok(),
-                    max: Some(m.maxQty.parse::<f64>().unwrap()),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(m.priceScale as u32, m.quantityScale as u32),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(&m.minQty),
+                    Some(&m.maxQty),
+                    None,
+                    None,
+                )),
                 contract_value: Some(1.0),
                 delivery_date: Some(m.expiryDate),
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: Some(OptionSpec {
+                    underlying: format!("{}/{}", base_currency, m.quoteAsset),
+                    strike: m.strikePrice.parse::<f64>().unwrap(),
+                    expiry: m.expiryDate,
+                    option_type: match m.side.as_str() {
+                        "CALL" => OptionType::Call,
+                        "PUT" => OptionType::Put,
+                        side => panic!("Unknown side {side}"),
+                    },
+                    exercise_style: ExerciseStyle::European,
+                }),
                 info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
             }
         })
@@ -62,24 +62,23 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: m.isMarginTradingAllowed,
                 // see https://www.binance.com/en/fee/trading
                 fees: Fees { maker: 0.001, taker: 0.001 },
-                precision: Precision {
-                    tick_size: parse_filter(&m.filters, "PRICE_FILTER", "tickSize")
-                        .parse::<f64>()
-                        .unwrap(),
-                    lot_size: parse_filter(&m.filters, "LOT_SIZE", "stepSize")
-                        .parse::<f64>()
-                        .unwrap(),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: parse_filter(&m.filters, "LOT_SIZE", "minQty").parse::<f64>().ok(),
-                    max: Some(
-                        parse_filter(&m.filters, "LOT_SIZE", "maxQty").parse::<f64>().unwrap(),
-                    ),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::parse(
+                    parse_filter(&m.filters, "PRICE_FILTER", "tickSize"),
+                    parse_filter(&m.filters, "LOT_SIZE", "stepSize"),
+                ),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(parse_filter(&m.filters, "LOT_SIZE", "minQty")),
+                    Some(parse_filter(&m.filters, "LOT_SIZE", "maxQty")),
+                    None,
+                    None,
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
             }
         })
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::utils::http_get;
 use crate::{
     error::Result,
-    market::{Fees, Precision, QuantityLimit},
+    market::{Fees, Precision, QuantityLimit, SettlementType},
     Market, MarketType,
 };
 use serde::{Deserialize, Serialize};
@@ -86,18 +86,20 @@ pub(crate) fn fetch_markets(market_type: MarketType) -> Result<Vec<Market>> {
                 } else {
                     Fees { maker: -0.0002, taker: 0.00075 }
                 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.price_precision as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(8_u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.minimum_order_size.parse::<f64>().ok(),
-                    max: Some(m.maximum_order_size.parse::<f64>().unwrap()),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(m.price_precision as u32, 8_u32),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(&m.minimum_order_size),
+                    Some(&m.maximum_order_size),
+                    None,
+                    None,
+                )),
                 contract_value: if market_type == MarketType::Spot { None } else { Some(1.0) },
                 delivery_date: None,
+                settlement_type: SettlementType::from_market_type(market_type),
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
             }
         })
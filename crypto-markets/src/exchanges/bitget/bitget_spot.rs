@@ -82,22 +82,27 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 maker: m.makerFeeRate.parse::<f64>().unwrap(),
                 taker: m.takerFeeRate.parse::<f64>().unwrap(),
             },
-            precision: Precision {
-                tick_size: 1.0 / (10_i64.pow(m.priceScale.parse::<u32>().unwrap()) as f64),
-                lot_size: 1.0 / (10_i64.pow(m.quantityScale.parse::<u32>().unwrap()) as f64),
-            },
-            quantity_limit: Some(QuantityLimit {
-                min: m.minTradeAmount.parse::<f64>().ok(),
-                max: if m.maxTradeAmount.parse::<f64>().unwrap() > 0.0 {
-                    Some(m.maxTradeAmount.parse::<f64>().unwrap())
+            precision: Precision::from_scale(
+                m.priceScale.parse::<u32>().unwrap(),
+                m.quantityScale.parse::<u32>().unwrap(),
+            ),
+            quantity_limit: Some(QuantityLimit::parse(
+                Some(&m.minTradeAmount),
+                if m.maxTradeAmount.parse::<f64>().unwrap() > 0.0 {
+                    Some(&m.maxTradeAmount)
                 } else {
                     None
                 },
-                notional_min: None,
-                notional_max: None,
-            }),
+                None,
+                None,
+            )),
             contract_value: None,
             delivery_date: None,
+            settlement_type: None,
+            funding_interval: None,
+            max_leverage: None,
+            quanto_multiplier: None,
+            option: None,
             info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
         })
         .collect();
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::{super::utils::http_get, EXCHANGE_NAME};
 use crate::{
     error::{Error, Result},
-    Fees, Market, Precision, QuantityLimit,
+    Fees, Market, Precision, QuantityLimit, SettlementType,
 };
 
 use chrono::DateTime;
@@ -123,7 +123,7 @@ fn to_market(m: SwapMarket) -> Market {
     let delivery_time = if market_type == MarketType::InverseFuture
         || market_type == MarketType::LinearFuture
     {
-        let date = m.symbol.split('_').last().unwrap();
+        let date = m.symbol.split('_').next_back().unwrap();
         debug_assert_eq!(date.len(), 6); // e.g., 230331
         let year = &date[..2];
         let month = &date[2..4];
@@ -152,18 +152,18 @@ fn to_market(m: SwapMarket) -> Market {
             maker: m.makerFeeRate.parse::<f64>().unwrap(),
             taker: m.takerFeeRate.parse::<f64>().unwrap(),
         },
-        precision: Precision {
-            tick_size: 1.0 / (10_i64.pow(m.pricePlace.parse::<u32>().unwrap()) as f64),
-            lot_size: 1.0 / (10_i64.pow(m.volumePlace.parse::<u32>().unwrap()) as f64),
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: m.minTradeNum.parse::<f64>().ok(),
-            max: None,
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::from_scale(
+            m.pricePlace.parse::<u32>().unwrap(),
+            m.volumePlace.parse::<u32>().unwrap(),
+        ),
+        quantity_limit: Some(QuantityLimit::parse(Some(&m.minTradeNum), None, None, None)),
         contract_value: Some(1.0), // TODO:
         delivery_date: delivery_time,
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: None,
+        max_leverage: None,
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
     }
 }
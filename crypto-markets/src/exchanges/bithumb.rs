@@ -115,13 +115,18 @@ fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: false,
                 // see https://www.bitglobal.com/en-us/fee
                 fees: Fees { maker: 0.001, taker: 0.001 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.accuracy[0].parse::<u32>().unwrap()) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.accuracy[1].parse::<u32>().unwrap()) as f64),
-                },
+                precision: Precision::from_scale(
+                    m.accuracy[0].parse::<u32>().unwrap(),
+                    m.accuracy[1].parse::<u32>().unwrap(),
+                ),
                 quantity_limit: None,
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
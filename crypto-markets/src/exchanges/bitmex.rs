@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::utils::http_get;
 use crate::{
     error::Result,
-    market::{Fees, Precision},
+    market::{Fees, Precision, SettlementType},
     Market, MarketType,
 };
 
@@ -49,7 +49,7 @@ pub(crate) fn fetch_markets(market_type: MarketType) -> Result<Vec<Market>> {
                 active: x.state == "Open",
                 margin: true,
                 fees: Fees { maker: x.makerFee, taker: x.takerFee },
-                precision: Precision { tick_size: x.tickSize, lot_size: x.lotSize },
+                precision: Precision::from_f64(x.tickSize, x.lotSize),
                 quantity_limit: None,
                 contract_value: if market_type != MarketType::Spot {
                     if let Some(y) = x.underlyingToSettleMultiplier {
@@ -66,6 +66,29 @@ pub(crate) fn fetch_markets(market_type: MarketType) -> Result<Vec<Market>> {
                 } else {
                     None
                 },
+                settlement_type: if market_type == MarketType::Spot {
+                    None
+                } else if x.isQuanto {
+                    Some(SettlementType::Quanto)
+                } else if x.isInverse {
+                    Some(SettlementType::Inverse)
+                } else {
+                    Some(SettlementType::Linear)
+                },
+                // e.g., 2000-01-01T08:00:00.000Z means 8 hours
+                funding_interval: x.fundingInterval.as_ref().and_then(|interval| {
+                    let interval = DateTime::parse_from_rfc3339(interval).ok()?;
+                    let epoch = DateTime::parse_from_rfc3339("2000-01-01T00:00:00Z").unwrap();
+                    Some((interval - epoch).num_milliseconds() as u64)
+                }),
+                max_leverage: if x.initMargin > 0.0 { Some(1.0 / x.initMargin) } else { None },
+                // multiplier is in the minimum unit of the settlement currency
+                quanto_multiplier: if x.isQuanto {
+                    Some(x.multiplier / settle_currency_unit(&x.settlCurrency))
+                } else {
+                    None
+                },
+                option: None,
                 info,
             }
         })
@@ -73,6 +96,16 @@ pub(crate) fn fetch_markets(market_type: MarketType) -> Result<Vec<Market>> {
     Ok(markets)
 }
 
+// Number of minimum units in one settlement currency, e.g., 1 XBT is 10^8 XBt,
+// i.e., satoshi, and 1 USDT is 10^6 USDt.
+fn settle_currency_unit(settl_currency: &str) -> f64 {
+    match settl_currency {
+        "XBt" => 1e8,
+        "USDt" => 1e6,
+        _ => 1.0,
+    }
+}
+
 // https://bitmex.freshdesk.com/en/support/solutions/articles/13000081130-instrument
 #[derive(Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
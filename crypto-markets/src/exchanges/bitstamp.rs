@@ -73,13 +73,15 @@ fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://www.bitstamp.net/fee-schedule/
                 fees: Fees { maker: 0.005, taker: 0.005 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.base_decimals as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.counter_decimals as u32) as f64),
-                },
+                precision: Precision::from_scale(m.base_decimals as u32, m.counter_decimals as u32),
                 quantity_limit: None,
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -43,7 +43,7 @@ fn fetch_spot_markets_raw() -> Result<Vec<SpotMarket>> {
         Err(Error(txt))
     } else {
         let markets =
-            resp.data.values().cloned().filter(|x| x.status == "1").collect::<Vec<SpotMarket>>();
+            resp.data.values().filter(|x| x.status == "1").cloned().collect::<Vec<SpotMarket>>();
         Ok(markets)
     }
 }
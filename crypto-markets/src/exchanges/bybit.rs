@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use super::utils::http_get;
-use crate::{error::Result, Fees, Market, MarketType, Precision, QuantityLimit};
+use crate::{error::Result, Fees, Market, MarketType, Precision, QuantityLimit, SettlementType};
 
 use chrono::{prelude::*, DateTime};
 use serde::{Deserialize, Serialize};
@@ -170,18 +170,27 @@ fn to_market(raw_market: &BybitMarket) -> Market {
             maker: raw_market.maker_fee.parse::<f64>().unwrap(),
             taker: raw_market.taker_fee.parse::<f64>().unwrap(),
         },
-        precision: Precision {
-            tick_size: raw_market.price_filter.tick_size.parse::<f64>().unwrap(),
-            lot_size: raw_market.lot_size_filter.qty_step,
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: Some(raw_market.lot_size_filter.min_trading_qty),
-            max: Some(raw_market.lot_size_filter.max_trading_qty),
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::parse(
+            &raw_market.price_filter.tick_size,
+            &raw_market.lot_size_filter.qty_step.to_string(),
+        ),
+        quantity_limit: Some(QuantityLimit::from_f64(
+            Some(raw_market.lot_size_filter.min_trading_qty),
+            Some(raw_market.lot_size_filter.max_trading_qty),
+            None,
+            None,
+        )),
         contract_value: Some(1.0),
         delivery_date,
+        settlement_type: if raw_market.quote_currency == "USDT" {
+            Some(SettlementType::Linear)
+        } else {
+            Some(SettlementType::Inverse)
+        },
+        funding_interval: None,
+        max_leverage: Some(raw_market.leverage_filter.max_leverage as f64),
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
@@ -81,18 +81,20 @@ fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: m.margin_enabled,
                 // // see https://pro.coinbase.com/fees, https://pro.coinbase.com/orders/fees
                 fees: Fees { maker: 0.005, taker: 0.005 },
-                precision: Precision {
-                    tick_size: m.quote_increment.parse::<f64>().unwrap(),
-                    lot_size: m.base_increment.parse::<f64>().unwrap(),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: None,
-                    max: None,
-                    notional_min: m.min_market_funds.map(|x| x.parse::<f64>().unwrap()),
-                    notional_max: m.max_market_funds.map(|x| x.parse::<f64>().unwrap()),
-                }),
+                precision: Precision::parse(&m.quote_increment, &m.base_increment),
+                quantity_limit: Some(QuantityLimit::parse(
+                    None,
+                    None,
+                    m.min_market_funds.as_deref(),
+                    m.max_market_funds.as_deref(),
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
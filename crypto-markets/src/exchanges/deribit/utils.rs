@@ -1,7 +1,9 @@
 use super::super::utils::http_get;
 use crate::{
     error::{Error, Result},
-    market::{Fees, Precision, QuantityLimit},
+    market::{
+        ExerciseStyle, Fees, OptionSpec, OptionType, Precision, QuantityLimit, SettlementType,
+    },
     Market,
 };
 
@@ -57,6 +59,8 @@ struct Instrument {
     contract_size: f64, // TODO: why i64 panic ?
     block_trade_commission: f64,
     base_currency: String,
+    option_type: Option<String>,
+    price_index: Option<String>,
 
     #[serde(flatten)]
     extra: HashMap<String, Value>,
@@ -132,6 +136,27 @@ pub(super) fn fetch_option_symbols() -> Result<Vec<String>> {
     fetch_symbols("option")
 }
 
+// Only applicable for options
+fn to_option_spec(raw_market: &Instrument) -> Option<OptionSpec> {
+    let option_type = match raw_market.option_type.as_deref()? {
+        "call" => OptionType::Call,
+        "put" => OptionType::Put,
+        option_type => panic!("Unknown option_type {option_type}"),
+    };
+    // e.g., btc_usd
+    let underlying = match raw_market.price_index.as_ref() {
+        Some(price_index) => price_index.replace('_', "/").to_uppercase(),
+        None => format!("{}/USD", raw_market.base_currency),
+    };
+    Some(OptionSpec {
+        underlying,
+        strike: raw_market.strike?,
+        expiry: raw_market.expiration_timestamp,
+        option_type,
+        exercise_style: ExerciseStyle::European,
+    })
+}
+
 fn to_market(raw_market: &Instrument) -> Market {
     let market_type = if raw_market.kind == "future" {
         if raw_market.instrument_name.ends_with("-PERPETUAL") {
@@ -160,22 +185,24 @@ fn to_market(raw_market: &Instrument) -> Market {
         active: raw_market.is_active,
         margin: true,
         fees: Fees { maker: raw_market.maker_commission, taker: raw_market.taker_commission },
-        precision: Precision {
-            tick_size: raw_market.tick_size,
-            lot_size: raw_market.min_trade_amount,
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: Some(raw_market.min_trade_amount),
-            max: None,
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::from_f64(raw_market.tick_size, raw_market.min_trade_amount),
+        quantity_limit: Some(QuantityLimit::from_f64(
+            Some(raw_market.min_trade_amount),
+            None,
+            None,
+            None,
+        )),
         contract_value: Some(raw_market.contract_size),
         delivery_date: if market_type == MarketType::InverseSwap {
             None
         } else {
             Some(raw_market.expiration_timestamp)
         },
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: None,
+        max_leverage: raw_market.max_leverage.map(|x| x as f64),
+        quanto_multiplier: None,
+        option: to_option_spec(raw_market),
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
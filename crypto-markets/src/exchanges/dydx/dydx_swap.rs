@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use super::super::utils::http_get;
-use crate::{error::Result, Fees, Market, Precision, QuantityLimit};
+use crate::{error::Result, Fees, Market, Precision, QuantityLimit, SettlementType};
 
 use crypto_market_type::MarketType;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,7 @@ struct PerpetualMarket {
     stepSize: String,
     tickSize: String,
     minOrderSize: String,
+    initialMarginFraction: Option<String>,
     #[serde(rename = "type")]
     type_: String,
     #[serde(flatten)]
@@ -38,8 +39,8 @@ fn fetch_markets_raw() -> Result<Vec<PerpetualMarket>> {
     Ok(resp
         .markets
         .values()
-        .cloned()
         .filter(|x| x.status == "ONLINE")
+        .cloned()
         .collect::<Vec<PerpetualMarket>>())
 }
 
@@ -73,18 +74,20 @@ pub(super) fn fetch_linear_swap_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://trade.dydx.exchange/portfolio/fees
                 fees: Fees { maker: 0.0005, taker: 0.0001 },
-                precision: Precision {
-                    tick_size: m.tickSize.parse::<f64>().unwrap(),
-                    lot_size: m.stepSize.parse::<f64>().unwrap(),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.minOrderSize.parse::<f64>().ok(),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::parse(&m.tickSize, &m.stepSize),
+                quantity_limit: Some(QuantityLimit::parse(Some(&m.minOrderSize), None, None, None)),
                 contract_value: Some(1.0),
                 delivery_date: None,
+                settlement_type: Some(SettlementType::Linear),
+                funding_interval: None,
+                max_leverage: m
+                    .initialMarginFraction
+                    .as_ref()
+                    .and_then(|x| x.parse::<f64>().ok())
+                    .filter(|x| *x > 0.0)
+                    .map(|x| 1.0 / x),
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use super::utils::http_get;
-use crate::{error::Result, Fees, Market, MarketType, Precision};
+use crate::{error::Result, Fees, Market, MarketType, Precision, SettlementType};
 
 use chrono::{prelude::*, DateTime};
 use serde::{Deserialize, Serialize};
@@ -183,13 +183,15 @@ fn to_market(raw_market: &FtxMarket) -> Market {
         margin: true,
         // see https://help.ftx.com/hc/en-us/articles/360024479432-Fees
         fees: Fees { maker: 0.0002, taker: 0.0007 },
-        precision: Precision {
-            tick_size: raw_market.priceIncrement,
-            lot_size: raw_market.sizeIncrement,
-        },
+        precision: Precision::from_f64(raw_market.priceIncrement, raw_market.sizeIncrement),
         quantity_limit: None,
         contract_value: if raw_market.type_ == "spot" { None } else { Some(1.0) },
         delivery_date,
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: None,
+        max_leverage: None,
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
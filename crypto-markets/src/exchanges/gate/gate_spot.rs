@@ -64,18 +64,21 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                     maker: raw_market.fee.parse::<f64>().unwrap() / 100_f64,
                     taker: raw_market.fee.parse::<f64>().unwrap() / 100_f64,
                 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(raw_market.precision as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(raw_market.amount_precision as u32) as f64),
-                },
-                quantity_limit: raw_market.min_base_amount.map(|min_base_amount| QuantityLimit {
-                    min: min_base_amount.parse::<f64>().ok(),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(
+                    raw_market.precision as u32,
+                    raw_market.amount_precision as u32,
+                ),
+                quantity_limit: raw_market
+                    .min_base_amount
+                    .as_deref()
+                    .map(|min| QuantityLimit::parse(Some(min), None, None, None)),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use super::super::utils::http_get;
-use crate::{error::Result, Fees, Market, Precision, QuantityLimit};
+use crate::{error::Result, Fees, Market, Precision, QuantityLimit, SettlementType};
 
 use crypto_market_type::MarketType;
 use serde::{Deserialize, Serialize};
@@ -24,6 +24,7 @@ struct SwapMarket {
     order_price_round: String,
     mark_price_round: String,
     funding_rate: String,
+    funding_interval: Option<u64>, // in seconds
     order_size_min: f64,
     order_size_max: f64,
     in_delisting: bool,
@@ -97,18 +98,20 @@ fn to_market(raw_market: &SwapMarket) -> Market {
             maker: raw_market.maker_fee_rate.parse::<f64>().unwrap(),
             taker: raw_market.taker_fee_rate.parse::<f64>().unwrap(),
         },
-        precision: Precision {
-            tick_size: raw_market.order_price_round.parse::<f64>().unwrap(),
-            lot_size: quanto_multiplier,
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: Some(raw_market.order_size_min),
-            max: Some(raw_market.order_size_max),
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::parse(&raw_market.order_price_round, &quanto_multiplier.to_string()),
+        quantity_limit: Some(QuantityLimit::from_f64(
+            Some(raw_market.order_size_min),
+            Some(raw_market.order_size_max),
+            None,
+            None,
+        )),
         contract_value: Some(quanto_multiplier),
         delivery_date: None,
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: raw_market.funding_interval.map(|x| x * 1000),
+        max_leverage: raw_market.leverage_max.parse::<f64>().ok(),
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
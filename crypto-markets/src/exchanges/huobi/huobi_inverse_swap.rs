@@ -1,7 +1,7 @@
 use super::utils::huobi_http_get;
 use crate::{
     error::Result,
-    market::{Fees, Precision},
+    market::{Fees, Precision, SettlementType},
     Market,
 };
 
@@ -72,10 +72,15 @@ pub(super) fn fetch_inverse_swap_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://futures.huobi.com/en-us/swap/fee_rate/
                 fees: Fees { maker: 0.0002, taker: 0.0005 },
-                precision: Precision { tick_size: m.price_tick, lot_size: 1.0 },
+                precision: Precision::from_f64(m.price_tick, 1.0),
                 quantity_limit: None,
                 contract_value: Some(m.contract_size),
                 delivery_date: None,
+                settlement_type: Some(SettlementType::Inverse),
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -1,6 +1,7 @@
 use super::utils::huobi_http_get;
-use crate::error::Result;
+use crate::{error::Result, market::*, Market, MarketType};
 
+use chrono::{NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -48,3 +49,54 @@ pub(super) fn fetch_option_symbols() -> Result<Vec<String>> {
         .collect::<Vec<String>>();
     Ok(symbols)
 }
+
+pub(super) fn fetch_option_markets() -> Result<Vec<Market>> {
+    let markets = fetch_option_markets_raw()?
+        .into_iter()
+        .map(|m| {
+            // options are delivered at 08:00 UTC of delivery_date, e.g., 20200508
+            let expiry = {
+                let date = NaiveDate::parse_from_str(&m.delivery_date, "%Y%m%d").unwrap();
+                Utc.from_utc_datetime(&date.and_hms_opt(8, 0, 0).unwrap()).timestamp_millis() as u64
+            };
+            Market {
+                exchange: "huobi".to_string(),
+                market_type: MarketType::EuropeanOption,
+                symbol: m.contract_code.clone(),
+                base_id: m.symbol.clone(),
+                quote_id: m.quote_asset.clone(),
+                settle_id: Some(m.trade_partition.clone()),
+                base: m.symbol.to_uppercase(),
+                quote: m.quote_asset.to_uppercase(),
+                settle: Some(m.trade_partition.to_uppercase()),
+                active: m.contract_status == 1,
+                margin: true,
+                // see https://futures.huobi.com/en-us/contract/fee_rate/
+                fees: Fees { maker: 0.0002, taker: 0.0004 },
+                precision: Precision::from_f64(m.price_tick, 1.0),
+                quantity_limit: None,
+                contract_value: Some(m.contract_size),
+                delivery_date: Some(expiry),
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: Some(OptionSpec {
+                    underlying: format!("{}/{}", m.symbol, m.quote_asset).to_uppercase(),
+                    strike: m.exercise_price,
+                    expiry,
+                    option_type: match m.option_right_type.as_str() {
+                        "C" => OptionType::Call,
+                        "P" => OptionType::Put,
+                        option_right_type => {
+                            panic!("Unknown option_right_type {option_right_type}")
+                        }
+                    },
+                    exercise_style: ExerciseStyle::European,
+                }),
+                info: serde_json::to_value(&m).unwrap().as_object().unwrap().clone(),
+            }
+        })
+        .collect::<Vec<Market>>();
+    Ok(markets)
+}
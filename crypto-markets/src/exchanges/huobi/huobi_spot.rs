@@ -77,18 +77,23 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://www.huobi.com/en-us/fee/
                 fees: Fees { maker: 0.002, taker: 0.002 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.price_precision as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.amount_precision as u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: Some(m.limit_order_min_order_amt),
-                    max: Some(m.limit_order_max_order_amt),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(
+                    m.price_precision as u32,
+                    m.amount_precision as u32,
+                ),
+                quantity_limit: Some(QuantityLimit::from_f64(
+                    Some(m.limit_order_min_order_amt),
+                    Some(m.limit_order_max_order_amt),
+                    None,
+                    None,
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -25,7 +25,7 @@ pub(crate) fn fetch_markets(market_type: MarketType) -> Result<Vec<Market>> {
         MarketType::InverseFuture => huobi_future::fetch_inverse_future_markets(),
         MarketType::InverseSwap => huobi_inverse_swap::fetch_inverse_swap_markets(),
         MarketType::LinearSwap => huobi_linear_swap::fetch_linear_swap_markets(),
-        MarketType::EuropeanOption => Ok(Vec::new()),
+        MarketType::EuropeanOption => huobi_option::fetch_option_markets(),
         _ => panic!("Unsupported market_type: {market_type}"),
     }
 }
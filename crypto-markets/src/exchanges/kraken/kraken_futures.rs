@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::super::utils::http_get;
 use crate::{
     error::{Error, Result},
-    Fees, Market, MarketType, Precision, QuantityLimit,
+    Fees, Market, MarketType, Precision, QuantityLimit, SettlementType,
 };
 
 use chrono::DateTime;
@@ -128,15 +128,16 @@ fn fetch_futures_markets() -> Result<Vec<Market>> {
                 let pos = m.symbol.find("usd").unwrap();
                 (m.symbol[3..pos].to_string(), "usd".to_string())
             };
+            let market_type = if m.symbol.starts_with("fi_") {
+                MarketType::InverseFuture
+            } else if m.symbol.starts_with("pi_") {
+                MarketType::InverseSwap
+            } else {
+                MarketType::Unknown
+            };
             Market {
                 exchange: "kraken".to_string(),
-                market_type: if m.symbol.starts_with("fi_") {
-                    MarketType::InverseFuture
-                } else if m.symbol.starts_with("pi_") {
-                    MarketType::InverseSwap
-                } else {
-                    MarketType::Unknown
-                },
+                market_type,
                 symbol: m.symbol,
                 base_id: base_id.clone(),
                 quote_id,
@@ -148,17 +149,17 @@ fn fetch_futures_markets() -> Result<Vec<Market>> {
                 margin: true,
                 // see https://futures.kraken.com/derivatives/api/v3/feeschedules
                 fees: Fees { maker: 0.0002, taker: 0.0005 },
-                precision: Precision { tick_size: m.tickSize, lot_size: 1.0 },
-                quantity_limit: Some(QuantityLimit {
-                    min: Some(1.0),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_f64(m.tickSize, 1.0),
+                quantity_limit: Some(QuantityLimit::from_f64(Some(1.0), None, None, None)),
                 contract_value: Some(m.contractSize),
                 delivery_date: m
                     .lastTradingTime
                     .map(|x| DateTime::parse_from_rfc3339(&x).unwrap().timestamp_millis() as u64),
+                settlement_type: SettlementType::from_market_type(market_type),
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
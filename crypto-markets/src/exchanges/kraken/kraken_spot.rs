@@ -29,11 +29,6 @@ struct SpotMarket {
     ordermin: String,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Response {
-    result: HashMap<String, SpotMarket>,
-}
-
 fn check_error_in_body(resp: String) -> Result<String> {
     let obj = serde_json::from_str::<HashMap<String, Value>>(&resp);
     if obj.is_err() {
@@ -104,18 +99,15 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: false,
                 // see https://support.kraken.com/hc/en-us/articles/360000526126-What-are-Maker-and-Taker-fees-
                 fees: Fees { maker: 0.0016, taker: 0.0026 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.pair_decimals as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.lot_decimals as u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.ordermin.parse::<f64>().ok(),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(m.pair_decimals as u32, m.lot_decimals as u32),
+                quantity_limit: Some(QuantityLimit::parse(Some(&m.ordermin), None, None, None)),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
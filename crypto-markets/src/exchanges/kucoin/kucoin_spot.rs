@@ -82,18 +82,20 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: m.isMarginEnabled,
                 // see https://www.bitstamp.net/fee-schedule/
                 fees: Fees { maker: 0.005, taker: 0.005 },
-                precision: Precision {
-                    tick_size: m.priceIncrement.parse::<f64>().unwrap(),
-                    lot_size: m.baseIncrement.parse::<f64>().unwrap(),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.baseMinSize.parse::<f64>().ok(),
-                    max: Some(m.baseMaxSize.parse::<f64>().unwrap()),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::parse(&m.priceIncrement, &m.baseIncrement),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(&m.baseMinSize),
+                    Some(&m.baseMaxSize),
+                    None,
+                    None,
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
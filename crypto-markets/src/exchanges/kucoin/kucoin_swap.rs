@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::super::utils::http_get;
 use crate::{
     error::{Error, Result},
-    Fees, Market, Precision,
+    Fees, Market, Precision, SettlementType,
 };
 
 use crypto_market_type::MarketType;
@@ -44,6 +44,7 @@ struct SwapMarket {
     status: String,
     fundingFeeRate: Option<f64>,
     predictedFundingFeeRate: Option<f64>,
+    fundingRateGranularity: Option<u64>, // in milliseconds
     openInterest: String,
     #[serde(flatten)]
     extra: HashMap<String, Value>,
@@ -134,10 +135,25 @@ fn to_market(raw_market: &SwapMarket) -> Market {
         active: raw_market.status == "Open",
         margin: true,
         fees: Fees { maker: raw_market.makerFeeRate, taker: raw_market.takerFeeRate },
-        precision: Precision { tick_size: raw_market.tickSize, lot_size: raw_market.lotSize },
+        precision: Precision::from_f64(raw_market.tickSize, raw_market.lotSize),
         quantity_limit: None,
         contract_value: Some(raw_market.multiplier.abs()),
         delivery_date: raw_market.expireDate,
+        settlement_type: if raw_market.isQuanto {
+            Some(SettlementType::Quanto)
+        } else if raw_market.isInverse {
+            Some(SettlementType::Inverse)
+        } else {
+            Some(SettlementType::Linear)
+        },
+        funding_interval: raw_market.fundingRateGranularity,
+        max_leverage: if raw_market.initialMargin > 0.0 {
+            Some(1.0 / raw_market.initialMargin)
+        } else {
+            None
+        },
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
@@ -69,18 +69,20 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                     maker: m.maker_fee_rate.parse::<f64>().unwrap(),
                     taker: m.taker_fee_rate.parse::<f64>().unwrap(),
                 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.price_scale) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.quantity_scale) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.min_amount.parse::<f64>().ok(),
-                    max: Some(m.max_amount.parse::<f64>().unwrap()),
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(m.price_scale, m.quantity_scale),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(&m.min_amount),
+                    Some(&m.max_amount),
+                    None,
+                    None,
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
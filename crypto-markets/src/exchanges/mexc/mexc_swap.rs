@@ -1,5 +1,5 @@
 use super::utils::mexc_http_get;
-use crate::{error::Result, Fees, Market, Precision, QuantityLimit};
+use crate::{error::Result, Fees, Market, Precision, QuantityLimit, SettlementType};
 
 use crypto_market_type::MarketType;
 use serde::{Deserialize, Serialize};
@@ -99,18 +99,20 @@ fn to_market(raw_market: &SwapMarket) -> Market {
         active: raw_market.state == 0 && !raw_market.isHidden,
         margin: true,
         fees: Fees { maker: raw_market.makerFeeRate, taker: raw_market.takerFeeRate },
-        precision: Precision {
-            tick_size: raw_market.priceUnit,
-            lot_size: raw_market.volUnit as f64,
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: Some(raw_market.minVol as f64),
-            max: Some(raw_market.maxVol as f64),
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::from_f64(raw_market.priceUnit, raw_market.volUnit as f64),
+        quantity_limit: Some(QuantityLimit::from_f64(
+            Some(raw_market.minVol as f64),
+            Some(raw_market.maxVol as f64),
+            None,
+            None,
+        )),
         contract_value: Some(raw_market.contractSize),
         delivery_date: None,
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: None,
+        max_leverage: Some(raw_market.maxLeverage as f64),
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use super::utils::http_get;
 use crate::{
     error::Result,
-    market::{Fees, Precision, QuantityLimit},
+    market::{
+        ExerciseStyle, Fees, OptionSpec, OptionType, Precision, QuantityLimit, SettlementType,
+    },
     Market,
 };
 
@@ -69,6 +71,25 @@ struct RawMarket {
 }
 
 impl RawMarket {
+    // Only applicable for options
+    fn to_option_spec(&self) -> Option<OptionSpec> {
+        if self.instType != "OPTION" {
+            return None;
+        }
+        let option_type = match self.optType.as_str() {
+            "C" => OptionType::Call,
+            "P" => OptionType::Put,
+            opt_type => panic!("Unknown optType {opt_type}"),
+        };
+        Some(OptionSpec {
+            underlying: self.uly.replace('-', "/"),
+            strike: self.stk.parse::<f64>().unwrap(),
+            expiry: self.expTime.parse::<u64>().unwrap(),
+            option_type,
+            exercise_style: ExerciseStyle::European,
+        })
+    }
+
     fn to_market(&self) -> Market {
         let pair = crypto_pair::normalize_pair(self.instId.as_str(), "okx").unwrap();
         let (base, quote) = {
@@ -116,16 +137,8 @@ impl RawMarket {
                 maker: if self.instType == "SPOT" { 0.0008 } else { 0.0002 },
                 taker: if self.instType == "SPOT" { 0.001 } else { 0.0005 },
             },
-            precision: Precision {
-                tick_size: self.tickSz.parse::<f64>().unwrap(),
-                lot_size: self.lotSz.parse::<f64>().unwrap(),
-            },
-            quantity_limit: Some(QuantityLimit {
-                min: self.minSz.parse::<f64>().ok(),
-                max: None,
-                notional_min: None,
-                notional_max: None,
-            }),
+            precision: Precision::parse(&self.tickSz, &self.lotSz),
+            quantity_limit: Some(QuantityLimit::parse(Some(&self.minSz), None, None, None)),
             contract_value: if self.instType == "SPOT" {
                 None
             } else {
@@ -136,6 +149,11 @@ impl RawMarket {
             } else {
                 None
             },
+            settlement_type: SettlementType::from_market_type(market_type),
+            funding_interval: None,
+            max_leverage: self.lever.parse::<f64>().ok(),
+            quanto_multiplier: None,
+            option: self.to_option_spec(),
             info: serde_json::to_value(self).unwrap().as_object().unwrap().clone(),
         }
     }
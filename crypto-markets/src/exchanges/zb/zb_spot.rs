@@ -66,18 +66,15 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: false,
                 // see https://www.zb.com/help/rate/6
                 fees: Fees { maker: 0.002, taker: 0.002 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.priceScale) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.amountScale) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: Some(m.minAmount),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(m.priceScale, m.amountScale),
+                quantity_limit: Some(QuantityLimit::from_f64(Some(m.minAmount), None, None, None)),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::super::utils::http_get;
 use crate::{
     error::{Error, Result},
-    Fees, Market, Precision, QuantityLimit,
+    Fees, Market, Precision, QuantityLimit, SettlementType,
 };
 
 use crypto_market_type::MarketType;
@@ -93,18 +93,20 @@ fn to_market(raw_market: &SwapMarket) -> Market {
         margin: true,
         // see https://www.zb.com/help/rate/20
         fees: Fees { maker: 0.0005, taker: 0.00075 },
-        precision: Precision {
-            tick_size: 1.0 / (10_i64.pow(raw_market.priceDecimal) as f64),
-            lot_size: 1.0 / (10_i64.pow(raw_market.amountDecimal) as f64),
-        },
-        quantity_limit: Some(QuantityLimit {
-            min: raw_market.minAmount.parse::<f64>().ok(),
-            max: Some(raw_market.maxAmount.parse::<f64>().unwrap()),
-            notional_min: None,
-            notional_max: None,
-        }),
+        precision: Precision::from_scale(raw_market.priceDecimal, raw_market.amountDecimal),
+        quantity_limit: Some(QuantityLimit::parse(
+            Some(&raw_market.minAmount),
+            Some(&raw_market.maxAmount),
+            None,
+            None,
+        )),
         contract_value: Some(1.0),
         delivery_date: None,
+        settlement_type: Some(SettlementType::Linear),
+        funding_interval: None,
+        max_leverage: None,
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
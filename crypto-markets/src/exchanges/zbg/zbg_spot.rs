@@ -84,18 +84,23 @@ pub(super) fn fetch_spot_markets() -> Result<Vec<Market>> {
                 margin: false,
                 // TODO: need to find zbg spot fees
                 fees: Fees { maker: 0.002, taker: 0.002 },
-                precision: Precision {
-                    tick_size: 1.0 / (10_i64.pow(m.price_precision as u32) as f64),
-                    lot_size: 1.0 / (10_i64.pow(m.amount_precision as u32) as f64),
-                },
-                quantity_limit: Some(QuantityLimit {
-                    min: m.min_order_amt.parse::<f64>().ok(),
-                    max: None,
-                    notional_min: None,
-                    notional_max: None,
-                }),
+                precision: Precision::from_scale(
+                    m.price_precision as u32,
+                    m.amount_precision as u32,
+                ),
+                quantity_limit: Some(QuantityLimit::parse(
+                    Some(&m.min_order_amt),
+                    None,
+                    None,
+                    None,
+                )),
                 contract_value: None,
                 delivery_date: None,
+                settlement_type: None,
+                funding_interval: None,
+                max_leverage: None,
+                quanto_multiplier: None,
+                option: None,
                 info,
             }
         })
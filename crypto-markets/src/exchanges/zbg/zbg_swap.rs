@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use super::super::utils::http_get;
 use crate::{
     error::{Error, Result},
-    Fees, Market, Precision,
+    Fees, Market, Precision, SettlementType,
 };
 
 use crypto_market_type::MarketType;
@@ -111,13 +111,15 @@ fn to_market(raw_market: &SwapMarket) -> Market {
             maker: raw_market.makerFeeRatio.parse::<f64>().unwrap(),
             taker: raw_market.takerFeeRatio.parse::<f64>().unwrap(),
         },
-        precision: Precision {
-            tick_size: raw_market.priceTick.parse::<f64>().unwrap(),
-            lot_size: raw_market.lotSize.parse::<f64>().unwrap(),
-        },
+        precision: Precision::parse(&raw_market.priceTick, &raw_market.lotSize),
         quantity_limit: None,
         contract_value: Some(raw_market.contractUnit.parse::<f64>().unwrap()),
         delivery_date: None,
+        settlement_type: SettlementType::from_market_type(market_type),
+        funding_interval: None,
+        max_leverage: None,
+        quanto_multiplier: None,
+        option: None,
         info: serde_json::to_value(raw_market).unwrap().as_object().unwrap().clone(),
     }
 }
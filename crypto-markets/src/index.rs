@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use crypto_market_type::{get_market_types, MarketType};
+use log::*;
+
+use crate::{
+    cache::MarketCache, error::Result, fetch_markets, market::Market, SUPPORTED_EXCHANGES,
+};
+
+/// Markets of multiple exchanges, indexed by normalized pair and by exchange
+/// symbol.
+///
+/// A normalized pair is `BASE/QUOTE` in uppercase, e.g., `BTC/USDT`, built from
+/// `Market.base` and `Market.quote`.
+pub struct MarketIndex {
+    markets: Vec<Market>,
+    by_pair: HashMap<String, Vec<usize>>,
+    by_symbol: HashMap<(String, MarketType, String), usize>,
+}
+
+fn to_pair(market: &Market) -> String {
+    format!("{}/{}", market.base, market.quote).to_uppercase()
+}
+
+impl MarketIndex {
+    pub fn new(markets: Vec<Market>) -> Self {
+        let mut by_pair: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_symbol = HashMap::new();
+        for (i, market) in markets.iter().enumerate() {
+            by_pair.entry(to_pair(market)).or_default().push(i);
+            by_symbol
+                .insert((market.exchange.clone(), market.market_type, market.symbol.clone()), i);
+        }
+        MarketIndex { markets, by_pair, by_symbol }
+    }
+
+    /// Fetches markets of all supported exchanges, of `market_type` only if
+    /// specified.
+    ///
+    /// Exchanges which fail are logged and skipped.
+    pub fn fetch(market_type: Option<MarketType>) -> Self {
+        Self::fetch_with(market_type, fetch_markets)
+    }
+
+    /// Same as `fetch()`, but through a `MarketCache`.
+    pub fn fetch_cached(cache: &MarketCache, market_type: Option<MarketType>) -> Self {
+        Self::fetch_with(market_type, |exchange, market_type| {
+            cache.fetch_markets(exchange, market_type)
+        })
+    }
+
+    fn fetch_with(
+        market_type: Option<MarketType>,
+        fetch: impl Fn(&str, MarketType) -> Result<Vec<Market>>,
+    ) -> Self {
+        let mut markets = Vec::new();
+        for exchange in SUPPORTED_EXCHANGES.iter() {
+            for m_type in get_market_types(exchange) {
+                if m_type == MarketType::Unknown || market_type.is_some_and(|t| t != m_type) {
+                    continue;
+                }
+                match fetch(exchange, m_type) {
+                    Ok(list) => markets.extend(list),
+                    Err(err) => warn!("Failed to fetch {} {} markets, {}", exchange, m_type, err),
+                }
+            }
+        }
+        Self::new(markets)
+    }
+
+    /// All markets.
+    pub fn markets(&self) -> &[Market] {
+        &self.markets
+    }
+
+    /// Markets trading `pair` across exchanges, of `market_type` only if
+    /// specified.
+    ///
+    /// `pair` is case-insensitive, e.g., `BTC/USDT` or `btc/usdt`.
+    pub fn find(&self, pair: &str, market_type: Option<MarketType>) -> Vec<&Market> {
+        self.by_pair
+            .get(&pair.to_uppercase())
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|i| &self.markets[*i])
+                    .filter(|m| market_type.is_none_or(|t| t == m.market_type))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The market of an exchange-specific symbol.
+    pub fn get(&self, exchange: &str, market_type: MarketType, symbol: &str) -> Option<&Market> {
+        self.by_symbol
+            .get(&(exchange.to_string(), market_type, symbol.to_string()))
+            .map(|i| &self.markets[*i])
+    }
+
+    /// Converts an exchange-specific symbol to a normalized pair.
+    pub fn pair(&self, exchange: &str, market_type: MarketType, symbol: &str) -> Option<String> {
+        self.get(exchange, market_type, symbol).map(to_pair)
+    }
+}
+
+/// Finds markets trading `pair` across all supported exchanges, see
+/// `MarketIndex::find()`.
+pub fn find_markets(pair: &str, market_type: Option<MarketType>) -> Vec<Market> {
+    MarketIndex::fetch(market_type).find(pair, market_type).into_iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{Fees, Precision};
+
+    fn new_market(exchange: &str, market_type: MarketType, symbol: &str, base: &str) -> Market {
+        Market {
+            exchange: exchange.to_string(),
+            market_type,
+            symbol: symbol.to_string(),
+            base_id: base.to_string(),
+            quote_id: "USDT".to_string(),
+            settle_id: None,
+            base: base.to_string(),
+            quote: "USDT".to_string(),
+            settle: None,
+            active: true,
+            margin: false,
+            fees: Fees { maker: 0.001, taker: 0.001 },
+            precision: Precision::from_f64(0.01, 0.0001),
+            quantity_limit: None,
+            contract_value: None,
+            delivery_date: None,
+            settlement_type: None,
+            funding_interval: None,
+            max_leverage: None,
+            quanto_multiplier: None,
+            option: None,
+            info: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_market_index() {
+        let index = MarketIndex::new(vec![
+            new_market("binance", MarketType::Spot, "BTCUSDT", "BTC"),
+            new_market("binance", MarketType::LinearSwap, "BTCUSDT", "BTC"),
+            new_market("okx", MarketType::Spot, "BTC-USDT", "BTC"),
+            new_market("okx", MarketType::Spot, "ETH-USDT", "ETH"),
+        ]);
+
+        let keys = |markets: Vec<&Market>| -> Vec<(String, MarketType)> {
+            markets.into_iter().map(|m| (m.exchange.clone(), m.market_type)).collect()
+        };
+        assert_eq!(3, index.find("BTC/USDT", None).len());
+        assert_eq!(
+            vec![("binance".to_string(), MarketType::Spot), ("okx".to_string(), MarketType::Spot)],
+            keys(index.find("btc/usdt", Some(MarketType::Spot)))
+        );
+        assert!(index.find("BTC/USD", None).is_empty());
+
+        assert_eq!(Some("ETH/USDT".to_string()), index.pair("okx", MarketType::Spot, "ETH-USDT"));
+        assert_eq!(None, index.pair("okx", MarketType::LinearSwap, "ETH-USDT"));
+    }
+}
@@ -11,16 +11,52 @@
 //! println!("{}", serde_json::to_string_pretty(&markets).unwrap())
 //! ```
 
+mod cache;
+mod change;
 mod error;
 mod exchanges;
+mod index;
 mod market;
+mod option_chain;
 
 use crypto_market_type::MarketType;
+pub use cache::MarketCache;
+pub use change::{diff_markets, MarketChange, MarketPoller};
 pub use error::Error;
-pub use market::{Fees, Market, Precision, QuantityLimit};
+pub use index::{find_markets, MarketIndex};
+pub use market::{
+    ExerciseStyle, Fees, Market, OptionSpec, OptionType, Precision, QuantityLimit, SettlementType,
+};
+pub use option_chain::{fetch_option_chains, group_option_chains, OptionChain};
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
 
 use error::Result;
 
+/// Exchanges supported by `fetch_symbols()` and `fetch_markets()`.
+pub const SUPPORTED_EXCHANGES: &[&str] = &[
+    "binance",
+    "bitfinex",
+    "bitget",
+    "bithumb",
+    "bitmex",
+    "bitstamp",
+    "bitz",
+    "bybit",
+    "coinbase_pro",
+    "deribit",
+    "dydx",
+    "ftx",
+    "gate",
+    "huobi",
+    "kraken",
+    "kucoin",
+    "mexc",
+    "okx",
+    "zb",
+    "zbg",
+];
+
 /// Fetch trading symbols.
 pub fn fetch_symbols(exchange: &str, market_type: MarketType) -> Result<Vec<String>> {
     match exchange {
@@ -1,24 +1,461 @@
-use crypto_market_type::MarketType;
-use crypto_markets::fetch_markets;
-use std::{env, str::FromStr};
+use std::{path::PathBuf, str::FromStr, time::Duration};
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        println!("Usage: crypto-markets <exchange> <market_type>");
-        return;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crypto_market_type::{get_market_types, MarketType};
+use crypto_markets::{
+    diff_markets, fetch_markets, fetch_symbols, find_markets, Market, MarketChange, MarketPoller,
+    QuantityLimit, SUPPORTED_EXCHANGES,
+};
+
+/// Fetch trading markets from cryptocurrency exchanges.
+///
+/// `crypto-markets <EXCHANGE> <MARKET_TYPE>` prints markets in pretty JSON.
+#[derive(Parser)]
+#[command(name = "crypto-markets", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Exchange name, e.g., binance
+    exchange: Option<String>,
+    /// Market type, e.g., spot, linear_swap
+    market_type: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print markets
+    Markets {
+        #[command(flatten)]
+        selector: Selector,
+        #[command(flatten)]
+        filter: Filter,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Print trading symbols only
+    Symbols {
+        #[command(flatten)]
+        selector: Selector,
+        #[command(flatten)]
+        filter: Filter,
+        /// Output format, text prints one symbol per line
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Compare two snapshots saved by `markets --format json` or `--format
+    /// jsonl`, and print listings, delistings, precision, limit and status
+    /// changes
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Find markets trading a normalized pair across all exchanges
+    Find {
+        /// Normalized pair, e.g., BTC/USDT
+        pair: String,
+        /// Market type, all market types if not specified
+        #[arg(long, value_parser = MarketType::from_str)]
+        market_type: Option<MarketType>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Poll markets periodically and print changes as they happen
+    Watch {
+        /// Exchange name, e.g., binance
+        exchange: String,
+        /// Market type, e.g., spot, linear_swap
+        #[arg(value_parser = MarketType::from_str)]
+        market_type: MarketType,
+        /// Polling interval in seconds
+        #[arg(long, default_value_t = 300)]
+        interval: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+#[derive(Args)]
+struct Selector {
+    /// Exchange name, or all for all exchanges
+    exchange: String,
+    /// Market type, or all for all market types of the exchange
+    #[arg(default_value = "all")]
+    market_type: String,
+}
+
+#[derive(Args, Default)]
+struct Filter {
+    /// Unified base currency, e.g., BTC
+    #[arg(long)]
+    base: Option<String>,
+    /// Unified quote currency, e.g., USDT
+    #[arg(long)]
+    quote: Option<String>,
+    /// Unified settlement currency, e.g., USDT
+    #[arg(long)]
+    settle: Option<String>,
+    /// Market status, true or false
+    #[arg(long)]
+    active: Option<bool>,
+}
+
+impl Filter {
+    fn is_empty(&self) -> bool {
+        self.base.is_none()
+            && self.quote.is_none()
+            && self.settle.is_none()
+            && self.active.is_none()
+    }
+
+    fn matches(&self, market: &Market) -> bool {
+        let eq = |expected: &Option<String>, actual: Option<&String>| match expected {
+            Some(expected) => actual.is_some_and(|actual| actual.eq_ignore_ascii_case(expected)),
+            None => true,
+        };
+        eq(&self.base, Some(&market.base))
+            && eq(&self.quote, Some(&market.quote))
+            && eq(&self.settle, market.settle.as_ref())
+            && self.active.is_none_or(|active| active == market.active)
     }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Jsonl,
+    Csv,
+}
+
+const CSV_HEADER: &str = "exchange,market_type,symbol,base,quote,settle,active,margin,\
+                          maker_fee,taker_fee,tick_size,lot_size,min_quantity,max_quantity,\
+                          notional_min,notional_max,contract_value,delivery_date";
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn to_csv_row(m: &Market) -> String {
+    let opt = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let limit = m.quantity_limit.as_ref();
+    [
+        csv_field(&m.exchange),
+        m.market_type.to_string(),
+        csv_field(&m.symbol),
+        csv_field(&m.base),
+        csv_field(&m.quote),
+        csv_field(m.settle.as_deref().unwrap_or_default()),
+        m.active.to_string(),
+        m.margin.to_string(),
+        m.fees.maker.to_string(),
+        m.fees.taker.to_string(),
+        m.precision.tick_size.to_string(),
+        m.precision.lot_size.to_string(),
+        opt(limit.and_then(|l| l.min)),
+        opt(limit.and_then(|l| l.max)),
+        opt(limit.and_then(|l| l.notional_min)),
+        opt(limit.and_then(|l| l.notional_max)),
+        opt(m.contract_value),
+        m.delivery_date.map(|v| v.to_string()).unwrap_or_default(),
+    ]
+    .join(",")
+}
 
-    let exchange: &str = &args[1];
-    let market_type = MarketType::from_str(&args[2]);
-    if market_type.is_err() {
-        println!("Unknown market type: {}", &args[2]);
-        return;
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+// Expands `all` into exchanges and market types.
+fn select(selector: &Selector) -> Vec<(String, MarketType)> {
+    let exchanges: Vec<String> = if selector.exchange == "all" {
+        SUPPORTED_EXCHANGES.iter().map(|s| s.to_string()).collect()
+    } else if SUPPORTED_EXCHANGES.contains(&selector.exchange.as_str()) {
+        vec![selector.exchange.clone()]
+    } else {
+        exit_with_error(&format!("Unsupported exchange {}", selector.exchange))
+    };
+    let mut pairs = Vec::new();
+    for exchange in exchanges {
+        if selector.market_type == "all" {
+            for market_type in get_market_types(&exchange) {
+                if market_type != MarketType::Unknown {
+                    pairs.push((exchange.clone(), market_type));
+                }
+            }
+        } else {
+            let market_type = MarketType::from_str(&selector.market_type).unwrap_or_else(|_| {
+                exit_with_error(&format!("Unknown market type {}", selector.market_type))
+            });
+            pairs.push((exchange, market_type));
+        }
     }
+    pairs
+}
 
-    let resp = fetch_markets(exchange, market_type.unwrap());
-    match resp {
-        Ok(markets) => println!("{}", serde_json::to_string_pretty(&markets).unwrap()),
-        Err(err) => println!("{err}"),
+// Fetches markets of all selected exchanges and market types, failures are
+// reported and skipped.
+fn fetch_all(selector: &Selector, filter: &Filter) -> (Vec<Market>, bool) {
+    let mut markets = Vec::new();
+    let mut ok = true;
+    for (exchange, market_type) in select(selector) {
+        match fetch_markets(&exchange, market_type) {
+            Ok(list) => markets.extend(list.into_iter().filter(|m| filter.matches(m))),
+            Err(err) => {
+                eprintln!("Failed to fetch {exchange} {market_type} markets, {err}");
+                ok = false;
+            }
+        }
+    }
+    (markets, ok)
+}
+
+fn print_markets(markets: &[Market], format: Format) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(markets).unwrap()),
+        Format::Jsonl => {
+            for market in markets {
+                println!("{}", serde_json::to_string(market).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("{CSV_HEADER}");
+            for market in markets {
+                println!("{}", to_csv_row(market));
+            }
+        }
+        Format::Text => {
+            for market in markets {
+                println!("{} {} {}", market.exchange, market.market_type, market.symbol);
+            }
+        }
+    }
+}
+
+fn print_symbols(selector: &Selector, filter: &Filter, format: Format) -> bool {
+    // (exchange, market_type, symbol)
+    let (rows, ok): (Vec<(String, MarketType, String)>, bool) = if filter.is_empty() {
+        let mut rows = Vec::new();
+        let mut ok = true;
+        for (exchange, market_type) in select(selector) {
+            match fetch_symbols(&exchange, market_type) {
+                Ok(symbols) => rows.extend(
+                    symbols.into_iter().map(|symbol| (exchange.clone(), market_type, symbol)),
+                ),
+                Err(err) => {
+                    eprintln!("Failed to fetch {exchange} {market_type} symbols, {err}");
+                    ok = false;
+                }
+            }
+        }
+        (rows, ok)
+    } else {
+        let (markets, ok) = fetch_all(selector, filter);
+        (markets.into_iter().map(|m| (m.exchange, m.market_type, m.symbol)).collect(), ok)
+    };
+
+    let to_json = |(exchange, market_type, symbol): &(String, MarketType, String)| serde_json::json!({"exchange": exchange, "market_type": market_type, "symbol": symbol});
+    match format {
+        Format::Text => {
+            for (_, _, symbol) in rows.iter() {
+                println!("{symbol}");
+            }
+        }
+        Format::Json => {
+            let array: Vec<serde_json::Value> = rows.iter().map(to_json).collect();
+            println!("{}", serde_json::to_string_pretty(&array).unwrap());
+        }
+        Format::Jsonl => {
+            for row in rows.iter() {
+                println!("{}", to_json(row));
+            }
+        }
+        Format::Csv => {
+            println!("exchange,market_type,symbol");
+            for (exchange, market_type, symbol) in rows.iter() {
+                println!("{},{},{}", csv_field(exchange), market_type, csv_field(symbol));
+            }
+        }
+    }
+    ok
+}
+
+// Reads a JSON array or JSON lines of markets.
+fn load_snapshot(path: &PathBuf) -> Vec<Market> {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("{}: {err}", path.display())));
+    let result = if text.trim_start().starts_with('[') {
+        serde_json::from_str(&text)
+    } else {
+        text.lines().filter(|line| !line.trim().is_empty()).map(serde_json::from_str).collect()
+    };
+    result.unwrap_or_else(|err| exit_with_error(&format!("{}: {err}", path.display())))
+}
+
+fn format_limit(limit: Option<&QuantityLimit>) -> String {
+    let fields = limit.map(|l| {
+        [
+            ("min", l.min),
+            ("max", l.max),
+            ("notional_min", l.notional_min),
+            ("notional_max", l.notional_max),
+        ]
+        .into_iter()
+        .filter_map(|(name, v)| v.map(|v| format!("{name}={v}")))
+        .collect::<Vec<String>>()
+    });
+    match fields {
+        Some(fields) if !fields.is_empty() => fields.join(" "),
+        _ => "none".to_string(),
+    }
+}
+
+const CHANGES_CSV_HEADER: &str = "change,exchange,market_type,symbol,field,old,new";
+
+// One row per changed field, listings and delistings have no field.
+fn to_csv_rows(change: &MarketChange) -> Vec<String> {
+    let row = |prefix: &str, field: &str, old: &str, new: &str| {
+        format!("{prefix},{field},{},{}", csv_field(old), csv_field(new))
+    };
+    let prefix = |change: &str, exchange: &str, market_type: &MarketType, symbol: &str| {
+        format!("{change},{},{market_type},{}", csv_field(exchange), csv_field(symbol))
+    };
+    match change {
+        MarketChange::Listed { market } => {
+            let prefix = prefix("listed", &market.exchange, &market.market_type, &market.symbol);
+            vec![row(&prefix, "", "", "")]
+        }
+        MarketChange::Delisted { market } => {
+            let prefix = prefix("delisted", &market.exchange, &market.market_type, &market.symbol);
+            vec![row(&prefix, "", "", "")]
+        }
+        MarketChange::PrecisionChanged { exchange, market_type, symbol, old, new } => {
+            let prefix = prefix("precision_changed", exchange, market_type, symbol);
+            [("tick_size", old.tick_size, new.tick_size), ("lot_size", old.lot_size, new.lot_size)]
+                .into_iter()
+                .filter(|(_, old, new)| old != new)
+                .map(|(field, old, new)| row(&prefix, field, &old.to_string(), &new.to_string()))
+                .collect()
+        }
+        MarketChange::LimitChanged { exchange, market_type, symbol, old, new } => {
+            let prefix = prefix("limit_changed", exchange, market_type, symbol);
+            vec![row(&prefix, "limit", &format_limit(old.as_ref()), &format_limit(new.as_ref()))]
+        }
+        MarketChange::StatusChanged { exchange, market_type, symbol, old, new } => {
+            let prefix = prefix("status_changed", exchange, market_type, symbol);
+            vec![row(&prefix, "active", &old.to_string(), &new.to_string())]
+        }
+    }
+}
+
+// `header` is false when changes are printed one by one by `watch`.
+fn print_changes(changes: &[MarketChange], format: Format, header: bool) {
+    match format {
+        Format::Csv => {
+            if header {
+                println!("{CHANGES_CSV_HEADER}");
+            }
+            for change in changes {
+                for row in to_csv_rows(change) {
+                    println!("{row}");
+                }
+            }
+        }
+        Format::Text => {
+            for change in changes {
+                match change {
+                    MarketChange::Listed { market } => {
+                        println!("+ {} {} {}", market.exchange, market.market_type, market.symbol)
+                    }
+                    MarketChange::Delisted { market } => {
+                        println!("- {} {} {}", market.exchange, market.market_type, market.symbol)
+                    }
+                    MarketChange::PrecisionChanged { exchange, market_type, symbol, old, new } => {
+                        println!(
+                            "~ {exchange} {market_type} {symbol} tick_size {} -> {}, lot_size {} \
+                             -> {}",
+                            old.tick_size, new.tick_size, old.lot_size, new.lot_size
+                        )
+                    }
+                    MarketChange::LimitChanged { exchange, market_type, symbol, old, new } => {
+                        println!(
+                            "~ {exchange} {market_type} {symbol} limit {} -> {}",
+                            format_limit(old.as_ref()),
+                            format_limit(new.as_ref())
+                        )
+                    }
+                    MarketChange::StatusChanged { exchange, market_type, symbol, old, new } => {
+                        println!("~ {exchange} {market_type} {symbol} active {old} -> {new}")
+                    }
+                }
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(changes).unwrap()),
+        Format::Jsonl => {
+            for change in changes {
+                println!("{}", serde_json::to_string(change).unwrap());
+            }
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let ok = match cli.command {
+        Some(Command::Markets { selector, filter, format }) => {
+            let (markets, ok) = fetch_all(&selector, &filter);
+            print_markets(&markets, format);
+            ok
+        }
+        Some(Command::Symbols { selector, filter, format }) => {
+            print_symbols(&selector, &filter, format)
+        }
+        Some(Command::Diff { old, new, format }) => {
+            let changes = diff_markets(&load_snapshot(&old), &load_snapshot(&new));
+            print_changes(&changes, format, true);
+            true
+        }
+        Some(Command::Find { pair, market_type, format }) => {
+            print_markets(&find_markets(&pair, market_type), format);
+            true
+        }
+        Some(Command::Watch { exchange, market_type, interval, format }) => {
+            if !SUPPORTED_EXCHANGES.contains(&exchange.as_str()) {
+                exit_with_error(&format!("Unsupported exchange {exchange}"));
+            }
+            let format = if format == Format::Json { Format::Jsonl } else { format };
+            if format == Format::Csv {
+                println!("{CHANGES_CSV_HEADER}");
+            }
+            let (tx, rx) = std::sync::mpsc::channel();
+            let poller = MarketPoller::new(&exchange, market_type);
+            std::thread::spawn(move || poller.run(Duration::from_secs(interval), tx));
+            for change in rx {
+                print_changes(&[change], format, false);
+            }
+            true
+        }
+        None => match (cli.exchange, cli.market_type) {
+            (Some(exchange), Some(market_type)) => {
+                let selector = Selector { exchange, market_type };
+                let (markets, ok) = fetch_all(&selector, &Filter::default());
+                print_markets(&markets, Format::Json);
+                ok
+            }
+            _ => exit_with_error("Usage: crypto-markets <EXCHANGE> <MARKET_TYPE>, see --help"),
+        },
+    };
+    if !ok {
+        std::process::exit(1);
     }
 }
@@ -1,22 +1,83 @@
 use crypto_market_type::MarketType;
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
-#[derive(Clone, Serialize, Deserialize)]
+use crate::error::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Fees {
     pub maker: f64,
     pub taker: f64,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+/// Built with `Precision::parse()`, `Precision::from_scale()` or
+/// `Precision::from_f64()`, so that the `decimal` feature can add fields.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Precision {
     /// the minimum price change, see https://en.wikipedia.org/wiki/Tick_size
     pub tick_size: f64,
     /// the minimum quantity change
     pub lot_size: f64,
+    /// Exact `tick_size`, parsed from the original string of the exchange
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_size_decimal: Option<Decimal>,
+    /// Exact `lot_size`, parsed from the original string of the exchange
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lot_size_decimal: Option<Decimal>,
+}
+
+// Accepts both plain and scientific notations, e.g., 0.0001 and 1e-4.
+#[cfg(feature = "decimal")]
+fn parse_decimal(s: &str) -> Option<Decimal> {
+    use std::str::FromStr;
+    let s = s.trim();
+    Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s)).ok()
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq)]
+impl Precision {
+    /// Parses tick size and lot size from strings of the exchange.
+    ///
+    /// Panics if either string is not a number.
+    pub fn parse(tick_size: &str, lot_size: &str) -> Self {
+        Precision {
+            tick_size: tick_size.parse::<f64>().unwrap(),
+            lot_size: lot_size.parse::<f64>().unwrap(),
+            #[cfg(feature = "decimal")]
+            tick_size_decimal: parse_decimal(tick_size),
+            #[cfg(feature = "decimal")]
+            lot_size_decimal: parse_decimal(lot_size),
+        }
+    }
+
+    /// Tick size and lot size are `10^-price_scale` and `10^-quantity_scale`.
+    pub fn from_scale(price_scale: u32, quantity_scale: u32) -> Self {
+        Precision {
+            tick_size: 1.0 / (10_i64.pow(price_scale) as f64),
+            lot_size: 1.0 / (10_i64.pow(quantity_scale) as f64),
+            #[cfg(feature = "decimal")]
+            tick_size_decimal: Some(Decimal::new(1, price_scale)),
+            #[cfg(feature = "decimal")]
+            lot_size_decimal: Some(Decimal::new(1, quantity_scale)),
+        }
+    }
+
+    /// For exchanges which return numbers instead of strings, numbers are
+    /// converted to the shortest strings which round-trip, which are the same
+    /// as the original JSON numbers in most cases.
+    pub fn from_f64(tick_size: f64, lot_size: f64) -> Self {
+        Self::parse(&tick_size.to_string(), &lot_size.to_string())
+    }
+}
+
+/// Built with `QuantityLimit::parse()` or `QuantityLimit::from_f64()`, so that
+/// the `decimal` feature can add fields.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
 pub struct QuantityLimit {
     /// Minimum base quantity
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,10 +91,122 @@ pub struct QuantityLimit {
     /// Notional maximum size
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notional_max: Option<f64>,
+    /// Exact `min`
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_decimal: Option<Decimal>,
+    /// Exact `max`
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_decimal: Option<Decimal>,
+    /// Exact `notional_min`
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notional_min_decimal: Option<Decimal>,
+    /// Exact `notional_max`
+    #[cfg(feature = "decimal")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notional_max_decimal: Option<Decimal>,
+}
+
+impl QuantityLimit {
+    /// Parses limits from strings of the exchange, invalid strings are
+    /// treated as None.
+    pub fn parse(
+        min: Option<&str>,
+        max: Option<&str>,
+        notional_min: Option<&str>,
+        notional_max: Option<&str>,
+    ) -> Self {
+        let to_f64 = |s: Option<&str>| s.and_then(|s| s.parse::<f64>().ok());
+        QuantityLimit {
+            min: to_f64(min),
+            max: to_f64(max),
+            notional_min: to_f64(notional_min),
+            notional_max: to_f64(notional_max),
+            #[cfg(feature = "decimal")]
+            min_decimal: min.and_then(parse_decimal),
+            #[cfg(feature = "decimal")]
+            max_decimal: max.and_then(parse_decimal),
+            #[cfg(feature = "decimal")]
+            notional_min_decimal: notional_min.and_then(parse_decimal),
+            #[cfg(feature = "decimal")]
+            notional_max_decimal: notional_max.and_then(parse_decimal),
+        }
+    }
+
+    /// For exchanges which return numbers instead of strings, see
+    /// `Precision::from_f64()`.
+    pub fn from_f64(
+        min: Option<f64>,
+        max: Option<f64>,
+        notional_min: Option<f64>,
+        notional_max: Option<f64>,
+    ) -> Self {
+        let to_string = |v: Option<f64>| v.map(|v| v.to_string());
+        Self::parse(
+            to_string(min).as_deref(),
+            to_string(max).as_deref(),
+            to_string(notional_min).as_deref(),
+            to_string(notional_max).as_deref(),
+        )
+    }
+}
+
+/// How a contract is margined and settled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettlementType {
+    /// margined and settled in quote currency, e.g., BTC/USDT settled in USDT
+    Linear,
+    /// margined and settled in base currency, e.g., BTC/USD settled in BTC
+    Inverse,
+    /// margined and settled in a third currency, at a fixed rate to quote
+    /// currency, e.g., ETH/USD settled in BTC
+    Quanto,
+}
+
+impl SettlementType {
+    /// Derived from market type, None for spot and option markets.
+    pub(crate) fn from_market_type(market_type: MarketType) -> Option<Self> {
+        match market_type {
+            MarketType::LinearFuture | MarketType::LinearSwap => Some(SettlementType::Linear),
+            MarketType::InverseFuture | MarketType::InverseSwap => Some(SettlementType::Inverse),
+            MarketType::QuantoFuture | MarketType::QuantoSwap => Some(SettlementType::Quanto),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExerciseStyle {
+    European,
+    American,
+}
+
+/// Contract specification of an option market.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OptionSpec {
+    /// unified uppercase pair of the underlying index, e.g., BTC/USD
+    pub underlying: String,
+    /// strike price, in quote currency of the underlying
+    pub strike: f64,
+    /// expiry, unix timestamp in milliseconds
+    pub expiry: u64,
+    pub option_type: OptionType,
+    pub exercise_style: ExerciseStyle,
 }
 
 /// Market contains all information about a market
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Market {
     /// exchange name
     pub exchange: String,
@@ -79,6 +252,349 @@ pub struct Market {
     /// future and option markets.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delivery_date: Option<u64>,
+    /// How the contract is margined and settled, only applicable for future
+    /// and swap markets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlement_type: Option<SettlementType>,
+    /// Funding interval in milliseconds, only applicable for swap markets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub funding_interval: Option<u64>,
+    /// Maximum leverage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_leverage: Option<f64>,
+    /// Value of one contract in settlement currency per one point of price,
+    /// only applicable for quanto markets, e.g., 0.000001 XBT for BitMEX
+    /// ETHUSD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quanto_multiplier: Option<f64>,
+    /// Option contract specification, only applicable for option markets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub option: Option<OptionSpec>,
     /// the original JSON string retrieved from the exchange
     pub info: Map<String, Value>,
 }
+
+#[derive(Clone, Copy)]
+enum Rounding {
+    Nearest,
+    Down,
+    Up,
+}
+
+// Rounds `value` to a multiple of `step`. Float noise is removed so that the
+// result prints the same as a decimal multiple of `step`, and the result of
+// rounding down (up) is never greater (less) than `value`.
+fn round_to_step(value: f64, step: f64, rounding: Rounding) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    let decimals = step.to_string().split_once('.').map(|(_, x)| x.len() as i32).unwrap_or(0);
+    let scale = 10_f64.powi(decimals);
+    let multiple = |steps: f64| (steps * step * scale).round() / scale;
+    let steps = (value / step).round();
+    // value / step is off by float noise, e.g., 0.003 / 0.001 is
+    // 2.9999999999999996, so the neighbouring multiples are checked too
+    match rounding {
+        Rounding::Nearest => multiple(steps),
+        Rounding::Down => [steps + 1.0, steps, steps - 1.0]
+            .into_iter()
+            .map(multiple)
+            .find(|x| *x <= value)
+            .unwrap_or(value),
+        Rounding::Up => [steps - 1.0, steps, steps + 1.0]
+            .into_iter()
+            .map(multiple)
+            .find(|x| *x >= value)
+            .unwrap_or(value),
+    }
+}
+
+// Rounds in Decimal, `value` is converted from its shortest string, returns
+// None if `value` is not representable, e.g., NaN.
+#[cfg(feature = "decimal")]
+fn round_to_decimal_step(value: f64, step: Decimal, rounding: Rounding) -> Option<f64> {
+    use rust_decimal::{prelude::ToPrimitive, RoundingStrategy};
+
+    if step <= Decimal::ZERO {
+        return None;
+    }
+    let strategy = match rounding {
+        Rounding::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        Rounding::Down => RoundingStrategy::ToNegativeInfinity,
+        Rounding::Up => RoundingStrategy::ToPositiveInfinity,
+    };
+    let value = parse_decimal(&value.to_string())?;
+    let steps = value.checked_div(step)?.round_dp_with_strategy(0, strategy);
+    steps.checked_mul(step)?.to_f64()
+}
+
+impl Precision {
+    fn round_price(&self, price: f64, rounding: Rounding) -> f64 {
+        #[cfg(feature = "decimal")]
+        if let Some(rounded) =
+            self.tick_size_decimal.and_then(|step| round_to_decimal_step(price, step, rounding))
+        {
+            return rounded;
+        }
+        round_to_step(price, self.tick_size, rounding)
+    }
+
+    fn round_quantity(&self, quantity: f64, rounding: Rounding) -> f64 {
+        #[cfg(feature = "decimal")]
+        if let Some(rounded) =
+            self.lot_size_decimal.and_then(|step| round_to_decimal_step(quantity, step, rounding))
+        {
+            return rounded;
+        }
+        round_to_step(quantity, self.lot_size, rounding)
+    }
+}
+
+impl Market {
+    /// Rounds `price` to the nearest multiple of `tick_size`.
+    pub fn round_price(&self, price: f64) -> f64 {
+        self.precision.round_price(price, Rounding::Nearest)
+    }
+
+    /// Rounds `quantity` down to a multiple of `lot_size`.
+    pub fn floor_quantity(&self, quantity: f64) -> f64 {
+        self.precision.round_quantity(quantity, Rounding::Down)
+    }
+
+    /// Rounds `quantity` up to a multiple of `lot_size`.
+    pub fn ceil_quantity(&self, quantity: f64) -> f64 {
+        self.precision.round_quantity(quantity, Rounding::Up)
+    }
+
+    /// Converts a number of contracts to base quantity at `price`.
+    ///
+    /// `contract_value` is in base currency for linear markets and in quote
+    /// currency for inverse markets, spot markets have no contracts so
+    /// `contracts` is returned as is.
+    pub fn contracts_to_quantity(&self, contracts: f64, price: f64) -> Result<f64> {
+        let contract_value = self.contract_value()?;
+        if self.is_inverse() {
+            Ok(contracts * contract_value / price)
+        } else {
+            Ok(contracts * contract_value)
+        }
+    }
+
+    /// Converts base quantity to a number of contracts at `price`, the
+    /// inverse of `contracts_to_quantity()`.
+    ///
+    /// The result is not rounded, use `floor_quantity()` or
+    /// `ceil_quantity()` if needed.
+    pub fn quantity_to_contracts(&self, quantity: f64, price: f64) -> Result<f64> {
+        let contract_value = self.contract_value()?;
+        if self.is_inverse() {
+            Ok(quantity * price / contract_value)
+        } else {
+            Ok(quantity / contract_value)
+        }
+    }
+
+    /// Checks an order against `quantity_limit`.
+    ///
+    /// `quantity` is in the same unit as `lot_size`, i.e., number of
+    /// contracts for contract markets, and notional is `price` times the base
+    /// quantity of `quantity`, see `contracts_to_quantity()`.
+    pub fn validate_order(&self, price: f64, quantity: f64) -> Result<()> {
+        if price.is_nan() || price <= 0.0 {
+            return Err(Error(format!("{} price {} is not positive", self.name(), price)));
+        }
+        if quantity.is_nan() || quantity <= 0.0 {
+            return Err(Error(format!("{} quantity {} is not positive", self.name(), quantity)));
+        }
+        let limit = match self.quantity_limit.as_ref() {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let mut checks = vec![("quantity", quantity, limit.min, limit.max)];
+        if limit.notional_min.is_some() || limit.notional_max.is_some() {
+            let notional = price * self.contracts_to_quantity(quantity, price)?;
+            checks.push(("notional", notional, limit.notional_min, limit.notional_max));
+        }
+        for (field, value, min, max) in checks {
+            if let Some(min) = min.filter(|min| value < *min) {
+                return Err(Error(format!(
+                    "{} {} {} is less than the minimum {}",
+                    self.name(),
+                    field,
+                    value,
+                    min
+                )));
+            }
+            if let Some(max) = max.filter(|max| value > *max) {
+                return Err(Error(format!(
+                    "{} {} {} is greater than the maximum {}",
+                    self.name(),
+                    field,
+                    value,
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_inverse(&self) -> bool {
+        matches!(self.market_type, MarketType::InverseFuture | MarketType::InverseSwap)
+    }
+
+    fn contract_value(&self) -> Result<f64> {
+        match self.market_type {
+            MarketType::Spot => Ok(1.0),
+            MarketType::InverseFuture
+            | MarketType::InverseSwap
+            | MarketType::LinearFuture
+            | MarketType::LinearSwap
+            | MarketType::EuropeanOption => self
+                .contract_value
+                .ok_or_else(|| Error(format!("{} has no contract_value", self.name()))),
+            _ => Err(Error(format!(
+                "Converting contracts of {} markets is NOT supported",
+                self.market_type
+            ))),
+        }
+    }
+
+    // Used in error messages, e.g., binance spot BTCUSDT
+    fn name(&self) -> String {
+        format!("{} {} {}", self.exchange, self.market_type, self.symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_market(market_type: MarketType, contract_value: Option<f64>) -> Market {
+        Market {
+            exchange: "binance".to_string(),
+            market_type,
+            symbol: "BTCUSDT".to_string(),
+            base_id: "BTC".to_string(),
+            quote_id: "USDT".to_string(),
+            settle_id: None,
+            base: "BTC".to_string(),
+            quote: "USDT".to_string(),
+            settle: None,
+            active: true,
+            margin: false,
+            fees: Fees { maker: 0.001, taker: 0.001 },
+            precision: Precision::from_f64(0.1, 0.001),
+            quantity_limit: Some(QuantityLimit::from_f64(
+                Some(0.001),
+                Some(100.0),
+                Some(10.0),
+                None,
+            )),
+            contract_value,
+            delivery_date: None,
+            settlement_type: None,
+            funding_interval: None,
+            max_leverage: None,
+            quanto_multiplier: None,
+            option: None,
+            info: Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_round() {
+        let market = new_market(MarketType::Spot, None);
+        assert_eq!(20000.3, market.round_price(20000.26));
+        assert_eq!(20000.2, market.round_price(20000.24));
+        assert_eq!(0.3, market.round_price(0.1 + 0.2));
+        assert_eq!(1.234, market.floor_quantity(1.2349));
+        assert_eq!(1.235, market.ceil_quantity(1.2341));
+        // already a multiple of lot_size
+        assert_eq!(0.003, market.floor_quantity(0.001 * 3.0));
+        assert_eq!(0.003, market.ceil_quantity(0.001 * 3.0));
+        // never rounds down to a greater value or up to a less value
+        assert_eq!(1.234, market.floor_quantity(1.235 - 1e-12));
+        assert_eq!(1.235, market.ceil_quantity(1.234 + 1e-12));
+        assert!(market.floor_quantity(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn test_contracts() {
+        let spot = new_market(MarketType::Spot, None);
+        assert_eq!(2.0, spot.contracts_to_quantity(2.0, 20000.0).unwrap());
+
+        let linear = new_market(MarketType::LinearSwap, Some(0.01));
+        assert_eq!(0.02, linear.contracts_to_quantity(2.0, 20000.0).unwrap());
+        assert_eq!(2.0, linear.quantity_to_contracts(0.02, 20000.0).unwrap());
+
+        let inverse = new_market(MarketType::InverseSwap, Some(100.0));
+        assert_eq!(0.01, inverse.contracts_to_quantity(2.0, 20000.0).unwrap());
+        assert_eq!(2.0, inverse.quantity_to_contracts(0.01, 20000.0).unwrap());
+
+        assert!(new_market(MarketType::LinearSwap, None).contracts_to_quantity(1.0, 1.0).is_err());
+        assert!(new_market(MarketType::QuantoSwap, Some(1.0))
+            .quantity_to_contracts(1.0, 1.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_validate_order() {
+        let market = new_market(MarketType::Spot, None);
+        assert!(market.validate_order(20000.0, 0.01).is_ok());
+        assert_eq!(
+            "binance spot BTCUSDT quantity 0.0001 is less than the minimum 0.001",
+            market.validate_order(20000.0, 0.0001).unwrap_err().0
+        );
+        assert_eq!(
+            "binance spot BTCUSDT quantity 200 is greater than the maximum 100",
+            market.validate_order(20000.0, 200.0).unwrap_err().0
+        );
+        assert_eq!(
+            "binance spot BTCUSDT notional 2 is less than the minimum 10",
+            market.validate_order(1000.0, 0.002).unwrap_err().0
+        );
+        assert!(market.validate_order(0.0, 1.0).is_err());
+        assert!(market.validate_order(20000.0, f64::NAN).is_err());
+
+        // 1 contract of 0.01 BTC at 500 is 5 USDT
+        let linear = new_market(MarketType::LinearSwap, Some(0.01));
+        assert!(linear.validate_order(20000.0, 1.0).is_ok());
+        assert_eq!(
+            "binance linear_swap BTCUSDT notional 5 is less than the minimum 10",
+            linear.validate_order(500.0, 1.0).unwrap_err().0
+        );
+        assert!(new_market(MarketType::LinearSwap, None).validate_order(20000.0, 1.0).is_err());
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal_precision() {
+        use std::str::FromStr;
+
+        let precision = Precision::parse("0.00001000", "1e-8");
+        assert_eq!(Some(Decimal::from_str("0.00001").unwrap()), precision.tick_size_decimal);
+        assert_eq!(Some(Decimal::from_str("0.00000001").unwrap()), precision.lot_size_decimal);
+
+        let precision = Precision::from_scale(2, 3);
+        assert_eq!(Some(Decimal::from_str("0.01").unwrap()), precision.tick_size_decimal);
+        assert_eq!(Some(Decimal::from_str("0.001").unwrap()), precision.lot_size_decimal);
+
+        // 0.1 is not exact in f64
+        let precision = Precision::from_f64(0.1, 0.3);
+        assert_eq!(Some(Decimal::from_str("0.1").unwrap()), precision.tick_size_decimal);
+        assert_eq!(Some(Decimal::from_str("0.3").unwrap()), precision.lot_size_decimal);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn test_decimal_quantity_limit() {
+        use std::str::FromStr;
+
+        let limit = QuantityLimit::parse(Some("0.001"), Some("invalid"), None, Some("9000000"));
+        assert_eq!(Some(0.001), limit.min);
+        assert_eq!(None, limit.max);
+        assert_eq!(None, limit.max_decimal);
+        assert_eq!(Some(Decimal::from_str("0.001").unwrap()), limit.min_decimal);
+        assert_eq!(Some(Decimal::from(9000000)), limit.notional_max_decimal);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crypto_market_type::{get_market_types, MarketType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    fetch_markets,
+    market::Market,
+};
+
+/// Option markets of an exchange with the same underlying and expiry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OptionChain {
+    /// exchange name
+    pub exchange: String,
+    /// unified uppercase pair of the underlying index, e.g., BTC/USD
+    pub underlying: String,
+    /// expiry, unix timestamp in milliseconds
+    pub expiry: u64,
+    /// sorted by strike, the call goes before the put of the same strike
+    pub options: Vec<Market>,
+}
+
+/// Groups option markets by exchange, underlying and expiry, markets without
+/// `Market.option` are ignored.
+///
+/// Chains are sorted by exchange, underlying and expiry.
+pub fn group_option_chains(markets: Vec<Market>) -> Vec<OptionChain> {
+    let mut groups: BTreeMap<(String, String, u64), Vec<Market>> = BTreeMap::new();
+    for market in markets {
+        if let Some(option) = market.option.as_ref() {
+            let key = (market.exchange.clone(), option.underlying.clone(), option.expiry);
+            groups.entry(key).or_default().push(market);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|((exchange, underlying, expiry), mut options)| {
+            options.sort_by(|x, y| {
+                let (x, y) = (x.option.as_ref().unwrap(), y.option.as_ref().unwrap());
+                x.strike.total_cmp(&y.strike).then(x.option_type.cmp(&y.option_type))
+            });
+            OptionChain { exchange, underlying, expiry, options }
+        })
+        .collect()
+}
+
+/// Fetches all option chains of an exchange.
+pub fn fetch_option_chains(exchange: &str) -> Result<Vec<OptionChain>> {
+    if !get_market_types(exchange).contains(&MarketType::EuropeanOption) {
+        return Err(Error(format!("{exchange} does NOT have option markets")));
+    }
+    let markets = fetch_markets(exchange, MarketType::EuropeanOption)?;
+    Ok(group_option_chains(markets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{ExerciseStyle, Fees, OptionSpec, OptionType, Precision};
+
+    fn new_option(symbol: &str, expiry: u64, strike: f64, option_type: OptionType) -> Market {
+        Market {
+            exchange: "deribit".to_string(),
+            market_type: MarketType::EuropeanOption,
+            symbol: symbol.to_string(),
+            base_id: "BTC".to_string(),
+            quote_id: "BTC".to_string(),
+            settle_id: Some("BTC".to_string()),
+            base: "BTC".to_string(),
+            quote: "BTC".to_string(),
+            settle: Some("BTC".to_string()),
+            active: true,
+            margin: true,
+            fees: Fees { maker: 0.0003, taker: 0.0003 },
+            precision: Precision::from_f64(0.0005, 0.1),
+            quantity_limit: None,
+            contract_value: Some(1.0),
+            delivery_date: Some(expiry),
+            settlement_type: None,
+            funding_interval: None,
+            max_leverage: None,
+            quanto_multiplier: None,
+            option: Some(OptionSpec {
+                underlying: "BTC/USD".to_string(),
+                strike,
+                expiry,
+                option_type,
+                exercise_style: ExerciseStyle::European,
+            }),
+            info: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_option_chains() {
+        let mut future = new_option("BTC-30JUN23", 1688112000000, 0.0, OptionType::Call);
+        future.market_type = MarketType::InverseFuture;
+        future.option = None;
+        let chains = group_option_chains(vec![
+            new_option("BTC-30JUN23-30000-P", 1688112000000, 30000.0, OptionType::Put),
+            new_option("BTC-30JUN23-30000-C", 1688112000000, 30000.0, OptionType::Call),
+            new_option("BTC-30JUN23-25000-C", 1688112000000, 25000.0, OptionType::Call),
+            new_option("BTC-31MAR23-30000-C", 1680249600000, 30000.0, OptionType::Call),
+            future,
+        ]);
+
+        let symbols = |chain: &OptionChain| -> Vec<String> {
+            chain.options.iter().map(|m| m.symbol.clone()).collect()
+        };
+        assert_eq!(2, chains.len());
+        assert_eq!(1680249600000, chains[0].expiry);
+        assert_eq!(vec!["BTC-31MAR23-30000-C"], symbols(&chains[0]));
+        assert_eq!("BTC/USD", chains[1].underlying);
+        assert_eq!(
+            vec!["BTC-30JUN23-25000-C", "BTC-30JUN23-30000-C", "BTC-30JUN23-30000-P"],
+            symbols(&chains[1])
+        );
+        assert!(fetch_option_chains("bitstamp").is_err());
+    }
+}
@@ -136,7 +136,7 @@ fn fetch_linear_swap_markets() {
 
     let btcusdt = markets.iter().find(|m| m.symbol == "BTCUSDT").unwrap().clone();
     assert_eq!(btcusdt.contract_value, Some(1.0));
-    assert_eq!(btcusdt.precision.tick_size, 0.01);
+    assert_eq!(btcusdt.precision.tick_size, 0.1);
     assert_eq!(btcusdt.precision.lot_size, 0.001);
     let quantity_limit = btcusdt.quantity_limit.unwrap();
     assert_eq!(quantity_limit.min.unwrap(), 0.001);
@@ -156,6 +156,10 @@ fn fetch_option_markets() {
     let quantity_limit = btcusd.quantity_limit.unwrap();
     assert_eq!(quantity_limit.min.unwrap(), 0.0002);
     assert_eq!(quantity_limit.max, Some(10000.0));
+    let option = btcusd.option.unwrap();
+    assert_eq!(option.underlying, "BTC/USDT");
+    assert_eq!(option.expiry, btcusd.delivery_date.unwrap());
+    assert!(option.strike > 0.0);
 }
 
 #[test_case(MarketType::InverseFuture)]
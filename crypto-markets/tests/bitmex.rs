@@ -1,5 +1,5 @@
 use crypto_market_type::MarketType;
-use crypto_markets::{fetch_markets, fetch_symbols};
+use crypto_markets::{fetch_markets, fetch_symbols, SettlementType};
 use crypto_pair::get_market_type;
 
 #[macro_use]
@@ -118,6 +118,20 @@ fn fetch_inverse_swap_markets() {
     assert_eq!(xbtusd.precision.lot_size, 100.0);
     assert_eq!(xbtusd.contract_value, Some(1.0));
     assert!(xbtusd.quantity_limit.is_none());
+    assert_eq!(xbtusd.settlement_type, Some(SettlementType::Inverse));
+    assert_eq!(xbtusd.funding_interval, Some(8 * 3600 * 1000));
+    assert!(xbtusd.max_leverage.unwrap() >= 100.0);
+    assert!(xbtusd.quanto_multiplier.is_none());
+}
+
+#[test]
+fn fetch_quanto_swap_markets() {
+    let markets = fetch_markets(EXCHANGE_NAME, MarketType::QuantoSwap).unwrap();
+    assert!(!markets.is_empty());
+
+    let ethusd = markets.iter().find(|m| m.symbol == "ETHUSD").unwrap();
+    assert_eq!(ethusd.settlement_type, Some(SettlementType::Quanto));
+    assert_eq!(ethusd.quanto_multiplier, Some(0.000001));
 }
 
 #[test]
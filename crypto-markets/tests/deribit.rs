@@ -97,6 +97,10 @@ fn fetch_option_markets() {
     let quantity_limit = btcusd.quantity_limit.unwrap();
     assert_eq!(quantity_limit.min.unwrap(), 0.1);
     assert_eq!(quantity_limit.max, None);
+    let option = btcusd.option.unwrap();
+    assert_eq!(option.underlying, "BTC/USD");
+    assert_eq!(option.expiry, btcusd.delivery_date.unwrap());
+    assert!(option.strike > 0.0);
 }
 
 #[test_case(MarketType::InverseFuture)]
@@ -1,5 +1,5 @@
 use crypto_market_type::{get_market_types, MarketType};
-use crypto_markets::{fetch_markets, fetch_symbols};
+use crypto_markets::{fetch_markets, fetch_symbols, SettlementType};
 use test_case::test_case;
 
 #[macro_use]
@@ -105,6 +105,9 @@ fn fetch_linear_swap_markets() {
     assert_eq!(btc_usdt.contract_value, Some(0.0001));
     assert_eq!(btc_usdt.precision.tick_size, 0.1);
     assert_eq!(btc_usdt.precision.lot_size, 0.0001);
+    assert_eq!(btc_usdt.settlement_type, Some(SettlementType::Linear));
+    assert_eq!(btc_usdt.funding_interval, Some(8 * 3600 * 1000));
+    assert!(btc_usdt.max_leverage.is_some());
     let quantity_limit = btc_usdt.quantity_limit.unwrap();
     assert_eq!(quantity_limit.min.unwrap(), 1.0);
     assert_eq!(quantity_limit.max, Some(1000000.0));
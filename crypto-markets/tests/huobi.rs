@@ -115,6 +115,18 @@ fn fetch_linear_swap_markets() {
     assert!(btc_usdt.quantity_limit.is_none());
 }
 
+#[test]
+fn fetch_option_markets() {
+    let markets = fetch_markets(EXCHANGE_NAME, MarketType::EuropeanOption).unwrap();
+    assert!(!markets.is_empty());
+
+    let btc_usdt = markets.iter().find(|m| m.symbol.starts_with("BTC-USDT-")).unwrap().clone();
+    let option = btc_usdt.option.unwrap();
+    assert_eq!(option.underlying, "BTC/USDT");
+    assert_eq!(option.expiry, btc_usdt.delivery_date.unwrap());
+    assert!(option.strike > 0.0);
+}
+
 #[test_case(MarketType::InverseFuture)]
 #[test_case(MarketType::InverseSwap)]
 #[test_case(MarketType::LinearSwap)]
@@ -1,5 +1,5 @@
 use crypto_market_type::{get_market_types, MarketType};
-use crypto_markets::{fetch_markets, fetch_symbols};
+use crypto_markets::{fetch_markets, fetch_symbols, SettlementType};
 use crypto_pair::get_market_type;
 use test_case::test_case;
 
@@ -124,6 +124,8 @@ fn fetch_inverse_swap_markets() {
     assert!(!markets.is_empty());
 
     let btc_usd = markets.iter().find(|m| m.symbol == "BTC-USD-SWAP").unwrap().clone();
+    assert_eq!(btc_usd.settlement_type, Some(SettlementType::Inverse));
+    assert!(btc_usd.max_leverage.is_some());
     assert_eq!(btc_usd.precision.tick_size, 0.1);
     assert_eq!(btc_usd.precision.lot_size, 1.0);
     let quantity_limit = btc_usd.quantity_limit.unwrap();
@@ -155,6 +157,10 @@ fn fetch_option_markets() {
     let quantity_limit = btc_usd.quantity_limit.unwrap();
     assert_eq!(quantity_limit.min.unwrap(), 1.0);
     assert_eq!(quantity_limit.max, None);
+    let option = btc_usd.option.unwrap();
+    assert_eq!(option.underlying, "BTC/USD");
+    assert_eq!(option.expiry, btc_usd.delivery_date.unwrap());
+    assert!(option.strike > 0.0);
 }
 
 // #[test_case(MarketType::InverseFuture)]
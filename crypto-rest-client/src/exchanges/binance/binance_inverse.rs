@@ -8,6 +8,7 @@ const BASE_URL: &str = "https://dapi.binance.com";
 ///
 /// * REST API doc: <https://binance-docs.github.io/apidocs/delivery/en/>
 /// * Trading at: <https://www.binance.com/en/delivery/btcusd_perpetual>
+///
 /// Rate Limits: <https://binance-docs.github.io/apidocs/delivery/en/#limits>
 ///   * 2400 request weight per minute
 pub struct BinanceInverseRestClient {
@@ -0,0 +1,190 @@
+use std::{
+    panic::AssertUnwindSafe,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use clap::{Args, Parser, Subcommand};
+use crypto_market_type::MarketType;
+use crypto_rest_client::*;
+
+/// Call public RESTful APIs of cryptocurrency exchanges.
+///
+/// The response is printed to stdout, errors and latency are printed to
+/// stderr, and the exit code is non-zero on failure.
+#[derive(Parser)]
+#[command(name = "crypto-rest-client", version)]
+struct Cli {
+    /// Pretty print JSON responses
+    #[arg(long, global = true)]
+    pretty: bool,
+    /// Retry this many times on failure, 0 means unlimited
+    #[arg(long, global = true)]
+    retry: Option<u64>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Market {
+    /// Exchange name, e.g., binance
+    exchange: String,
+    /// Market type, e.g., spot, linear_swap
+    #[arg(value_parser = MarketType::from_str)]
+    market_type: MarketType,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch a level2 orderbook snapshot
+    L2Snapshot {
+        #[command(flatten)]
+        market: Market,
+        symbol: String,
+    },
+    /// Fetch a level3 orderbook snapshot
+    L3Snapshot {
+        #[command(flatten)]
+        market: Market,
+        symbol: String,
+    },
+    /// Fetch open interest, of all symbols if no symbol is specified
+    OpenInterest {
+        #[command(flatten)]
+        market: Market,
+        symbol: Option<String>,
+    },
+    /// Fetch the long/short ratio
+    LongShortRatio {
+        #[command(flatten)]
+        market: Market,
+        symbol: String,
+    },
+    /// Fetch the most recent trades
+    Trades {
+        #[command(flatten)]
+        market: Market,
+        symbol: String,
+    },
+}
+
+// Calls the exchange specific `fetch_trades()` with default parameters.
+fn fetch_trades(exchange: &str, market_type: MarketType, symbol: &str) -> Result<String, Error> {
+    match (exchange, market_type) {
+        ("binance", MarketType::Spot) => {
+            BinanceSpotRestClient::fetch_agg_trades(symbol, None, None, None)
+        }
+        ("binance", MarketType::InverseFuture | MarketType::InverseSwap) => {
+            BinanceInverseRestClient::fetch_agg_trades(symbol, None, None, None)
+        }
+        ("binance", MarketType::LinearFuture | MarketType::LinearSwap) => {
+            BinanceLinearRestClient::fetch_agg_trades(symbol, None, None, None)
+        }
+        ("binance", MarketType::EuropeanOption) => {
+            BinanceOptionRestClient::fetch_trades(symbol, None)
+        }
+        ("bitfinex", _) => BitfinexRestClient::fetch_trades(symbol, None, None, None, None),
+        ("bithumb", _) => BithumbRestClient::fetch_trades(symbol),
+        ("bitmex", _) => BitmexRestClient::fetch_trades(symbol, None),
+        ("bitstamp", _) => BitstampRestClient::fetch_trades(symbol, None),
+        ("coinbase_pro", _) => CoinbaseProRestClient::fetch_trades(symbol),
+        ("deribit", _) => DeribitRestClient::fetch_trades(symbol),
+        ("huobi", MarketType::Spot) => HuobiSpotRestClient::fetch_trades(symbol),
+        ("huobi", MarketType::InverseFuture) => HuobiFutureRestClient::fetch_trades(symbol),
+        ("huobi", MarketType::InverseSwap) => HuobiInverseSwapRestClient::fetch_trades(symbol),
+        ("huobi", MarketType::LinearSwap) => HuobiLinearSwapRestClient::fetch_trades(symbol),
+        ("huobi", MarketType::EuropeanOption) => HuobiOptionRestClient::fetch_trades(symbol),
+        ("kraken", MarketType::Spot) => KrakenSpotRestClient::fetch_trades(symbol, None),
+        ("kraken", MarketType::InverseFuture | MarketType::InverseSwap) => {
+            KrakenFuturesRestClient::fetch_trades(symbol, None)
+        }
+        ("mexc", MarketType::Spot) => MexcSpotRestClient::fetch_trades(symbol),
+        ("mexc", MarketType::LinearSwap | MarketType::InverseSwap) => {
+            MexcSwapRestClient::fetch_trades(symbol)
+        }
+        ("okx", _) => OkxRestClient::fetch_trades(symbol),
+        _ => Err(Error(format!("{exchange} {market_type} does NOT have a trades RESTful API"))),
+    }
+}
+
+// Unsupported exchanges and market types panic in the library, the panic is
+// reported as an error instead of a backtrace.
+fn call(f: impl FnOnce() -> Result<String, Error>) -> Result<String, Error> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    std::panic::set_hook(hook);
+    result.unwrap_or_else(|payload| {
+        let msg = payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "panicked".to_string());
+        Err(Error(msg))
+    })
+}
+
+// `retry` None means no retry; Some(0) means retry unlimited times; Some(n)
+// means retry n times, the same as `fetch_l2_snapshot()`.
+fn retriable(retry: Option<u64>, f: impl Fn() -> Result<String, Error>) -> Result<String, Error> {
+    let retry_count = match retry {
+        None => 1,
+        Some(0) => u64::MAX,
+        Some(n) => n,
+    };
+    let cooldown_time = Duration::from_secs(2);
+    let mut i = 1;
+    loop {
+        match f() {
+            Err(err) if i < retry_count => {
+                eprintln!("Attempt {i} failed, {err}, retry in {} ms", cooldown_time.as_millis());
+                std::thread::sleep(cooldown_time);
+                i += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn pretty_print(resp: String) -> String {
+    match serde_json::from_str::<serde_json::Value>(&resp) {
+        Ok(json) => serde_json::to_string_pretty(&json).unwrap(),
+        Err(_) => resp,
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let start = Instant::now();
+    // panics are not retried, see `call()`
+    let result = call(|| {
+        retriable(cli.retry, || match &cli.command {
+            Command::L2Snapshot { market, symbol } => {
+                fetch_l2_snapshot(&market.exchange, market.market_type, symbol, None)
+            }
+            Command::L3Snapshot { market, symbol } => {
+                fetch_l3_snapshot(&market.exchange, market.market_type, symbol, None)
+            }
+            Command::OpenInterest { market, symbol } => {
+                fetch_open_interest(&market.exchange, market.market_type, symbol.as_deref())
+            }
+            Command::LongShortRatio { market, symbol } => {
+                fetch_long_short_ratio(&market.exchange, market.market_type, symbol)
+            }
+            Command::Trades { market, symbol } => {
+                fetch_trades(&market.exchange, market.market_type, symbol)
+            }
+        })
+    });
+    let elapsed = start.elapsed().as_millis();
+    match result {
+        Ok(resp) => {
+            eprintln!("Succeeded in {elapsed} ms");
+            println!("{}", if cli.pretty { pretty_print(resp) } else { resp });
+        }
+        Err(err) => {
+            eprintln!("Failed in {elapsed} ms, {err}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crypto_ws_client::bench::is_normal_message;
+use serde_json::Value;
+
+const BINANCE_L2: &str = r#"{"stream":"btcusdt@depth@100ms","data":{"e":"depthUpdate","E":1676000000000,"s":"BTCUSDT","U":33446516960,"u":33446517037,"b":[["21800.00000000","1.20000000"],["21799.99000000","0.01000000"],["21799.50000000","0.45000000"],["21799.00000000","0.00000000"],["21798.12000000","2.00000000"]],"a":[["21800.01000000","0.31000000"],["21800.02000000","0.00000000"],["21800.50000000","0.90000000"],["21801.00000000","1.00000000"],["21802.00000000","3.10000000"]]}}"#;
+
+const BITMEX_L2: &str = r#"{"table":"orderBookL2_25","action":"update","data":[{"symbol":"XBTUSD","id":8799978150,"side":"Sell","size":10500,"price":21850.0,"timestamp":"2023-02-10T00:00:00.000Z"},{"symbol":"XBTUSD","id":8799978200,"side":"Buy","size":42000,"price":21800.0,"timestamp":"2023-02-10T00:00:00.000Z"}]}"#;
+
+const COINBASE_PRO_L2: &str = r#"{"type":"l2update","product_id":"BTC-USD","changes":[["buy","21800.00","0.10000000"],["sell","21800.01","0.00000000"]],"time":"2023-02-10T00:00:00.000000Z"}"#;
+
+const DERIBIT_L2: &str = r#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-PERPETUAL.100ms","data":{"type":"change","timestamp":1676000000000,"prev_change_id":55089711123,"instrument_name":"BTC-PERPETUAL","change_id":55089711180,"bids":[["change",21800.0,12000.0],["delete",21799.5,0.0]],"asks":[["new",21800.5,3000.0]]}}}"#;
+
+const HUOBI_L2: &str = r#"{"ch":"market.btcusdt.mbp.20","ts":1676000000000,"tick":{"seqNum":162283922121,"prevSeqNum":162283922110,"bids":[[21800.0,1.2],[21799.99,0.01],[21799.5,0.45]],"asks":[[21800.01,0.31],[21800.5,0.9],[21801.0,1.0]]}}"#;
+
+const OKX_L2: &str = r#"{"arg":{"channel":"books-l2-tbt","instId":"BTC-USDT"},"action":"update","data":[{"asks":[["21800.1","0.5","0","2"],["21801","0","0","0"]],"bids":[["21800","1.2","0","3"],["21799.5","0","0","0"]],"ts":"1676000000000","checksum":-1512345678}]}"#;
+
+const MESSAGES: &[(&str, &str)] = &[
+    ("binance", BINANCE_L2),
+    ("bitmex", BITMEX_L2),
+    ("coinbase_pro", COINBASE_PRO_L2),
+    ("deribit", DERIBIT_L2),
+    ("huobi", HUOBI_L2),
+    ("okx", OKX_L2),
+];
+
+// What handlers used to do for every message before the fast path.
+fn full_parse(msg: &str) -> bool {
+    let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
+    !obj.is_empty()
+}
+
+fn bench_handle_message(c: &mut Criterion) {
+    for (exchange, msg) in MESSAGES {
+        assert!(is_normal_message(exchange, msg));
+
+        let mut group = c.benchmark_group(format!("handle_message/{exchange}"));
+        group.bench_function("full_parse", |b| b.iter(|| full_parse(black_box(msg))));
+        group.bench_function("fast_path", |b| {
+            b.iter(|| is_normal_message(black_box(exchange), black_box(msg)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_handle_message);
+criterion_main!(benches);
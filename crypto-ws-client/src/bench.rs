@@ -0,0 +1,25 @@
+//! Internal APIs used by benchmarks, NOT part of the public API.
+
+use crate::{
+    clients::{
+        binance::BinanceMessageHandler, bitmex::BitmexMessageHandler,
+        coinbase_pro::CoinbaseProMessageHandler, deribit::DeribitMessageHandler,
+        huobi::HuobiMessageHandler, okx::OkxMessageHandler,
+    },
+    common::message_handler::{MessageHandler, MiscMessage},
+};
+
+/// Classifies a message with the message handler of `exchange`, returns true
+/// if it is a data message.
+pub fn is_normal_message(exchange: &str, msg: &str) -> bool {
+    let misc_msg = match exchange {
+        "binance" => BinanceMessageHandler {}.handle_message(msg),
+        "bitmex" => BitmexMessageHandler {}.handle_message(msg),
+        "coinbase_pro" => CoinbaseProMessageHandler {}.handle_message(msg),
+        "deribit" => DeribitMessageHandler {}.handle_message(msg),
+        "huobi" => HuobiMessageHandler {}.handle_message(msg),
+        "okx" => OkxMessageHandler {}.handle_message(msg),
+        _ => panic!("Unsupported exchange {exchange}"),
+    };
+    matches!(misc_msg, MiscMessage::Normal)
+}
@@ -7,10 +7,10 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
-        utils::ensure_frame_size,
+        utils::{ensure_frame_size, first_key_is},
         ws_client_internal::WSClientInternal,
     },
-    WSClient,
+    FrameCallback, WSClient,
 };
 use log::*;
 use serde_json::Value;
@@ -159,12 +159,16 @@ impl<const URL: char> WSClient for BinanceWSClient<URL> {
         self.client.run().await;
     }
 
+    async fn run_with_callback(&self, callback: FrameCallback) {
+        self.client.run_with_callback(callback).await;
+    }
+
     async fn close(&self) {
         self.client.close().await;
     }
 }
 
-struct BinanceMessageHandler {}
+pub(crate) struct BinanceMessageHandler {}
 struct BinanceCommandTranslator {
     market_type: char,
 }
@@ -208,6 +212,11 @@ impl BinanceCommandTranslator {
 
 impl MessageHandler for BinanceMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if first_key_is(msg, "stream") {
+            return MiscMessage::Normal;
+        }
+
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
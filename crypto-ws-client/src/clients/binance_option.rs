@@ -9,6 +9,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_key_is,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -90,6 +91,11 @@ impl MessageHandler for BinanceOptionMessageHandler {
             return MiscMessage::Pong;
         }
 
+        // fast path for data messages
+        if first_key_is(msg, "stream") {
+            return MiscMessage::Normal;
+        }
+
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -150,7 +150,7 @@ impl MessageHandler for BitfinexMessageHandler {
                     MiscMessage::Other
                 }
                 "info" => {
-                    if obj.get("version").is_some() {
+                    if obj.contains_key("version") {
                         // 1 for operative, 0 for maintenance
                         let status = obj
                             .get("platform")
@@ -226,10 +226,9 @@ impl MessageHandler for BitfinexMessageHandler {
             }
         } else {
             debug_assert!(txt.starts_with('['));
-            let arr = serde_json::from_str::<Vec<Value>>(txt).unwrap();
-            if arr.is_empty() {
+            if txt == "[]" {
                 MiscMessage::Other // ignore empty array
-            } else if arr.len() == 2 && arr[1].as_str().unwrap_or("null") == "hb" {
+            } else if txt.ends_with(r#","hb"]"#) {
                 // If there is no activity in the channel for 15 seconds, the Websocket server
                 // will send you a heartbeat message in this format.
                 // see <https://docs.bitfinex.com/docs/ws-general#heartbeating>
@@ -11,7 +11,7 @@ use serde_json::Value;
 use crate::common::{
     command_translator::CommandTranslator,
     message_handler::{MessageHandler, MiscMessage},
-    utils::ensure_frame_size,
+    utils::{ensure_frame_size, first_key_is},
 };
 
 pub(crate) const EXCHANGE_NAME: &str = "bitget";
@@ -90,6 +90,10 @@ impl MessageHandler for BitgetMessageHandler {
             // see https://bitgetlimited.github.io/apidoc/en/spot/#connect
             return MiscMessage::Pong;
         }
+        // fast path for data messages
+        if first_key_is(msg, "action") {
+            return MiscMessage::Normal;
+        }
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -59,6 +59,10 @@ struct BithumbCommandTranslator {}
 
 impl MessageHandler for BithumbMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if msg.starts_with(r#"{"code":"00007""#) {
+            return MiscMessage::Normal;
+        }
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         let code = obj.get("code").unwrap().as_str().unwrap();
         let code = code.parse::<i64>().unwrap();
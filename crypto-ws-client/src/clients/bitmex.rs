@@ -9,6 +9,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_key_is,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -54,7 +55,7 @@ panic_ticker!(BitmexWSClient);
 
 impl_ws_client_trait!(BitmexWSClient);
 
-struct BitmexMessageHandler {}
+pub(crate) struct BitmexMessageHandler {}
 struct BitmexCommandTranslator {}
 
 impl BitmexCommandTranslator {
@@ -88,6 +89,10 @@ impl MessageHandler for BitmexMessageHandler {
         if msg == "pong" {
             return MiscMessage::Pong;
         }
+        // fast path for data messages
+        if first_key_is(msg, "table") {
+            return MiscMessage::Normal;
+        }
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
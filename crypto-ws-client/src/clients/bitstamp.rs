@@ -58,6 +58,11 @@ struct BitstampCommandTranslator {}
 
 impl MessageHandler for BitstampMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages, all control messages have a `bts:` event
+        if msg.starts_with('{') && !msg.contains(r#""bts:"#) {
+            return MiscMessage::Normal;
+        }
+
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -63,6 +63,10 @@ impl MessageHandler for BitzMessageHandler {
         if msg == "pong" {
             return MiscMessage::Pong;
         }
+        // fast path for data messages
+        if msg.contains(r#""action":"Pushdata."#) {
+            return MiscMessage::Normal;
+        }
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         if obj.contains_key("action")
@@ -4,7 +4,10 @@ use log::*;
 use serde_json::Value;
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::common::message_handler::{MessageHandler, MiscMessage};
+use crate::common::{
+    message_handler::{MessageHandler, MiscMessage},
+    utils::first_key_is,
+};
 
 pub(super) const EXCHANGE_NAME: &str = "bybit";
 
@@ -24,6 +27,11 @@ pub(super) struct BybitMessageHandler {}
 
 impl MessageHandler for BybitMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if first_key_is(msg, "topic") {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         if obj.contains_key("topic") && obj.contains_key("data") {
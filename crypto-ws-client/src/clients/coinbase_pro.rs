@@ -9,6 +9,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_str_value,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -52,11 +53,18 @@ panic_l2_topk!(CoinbaseProWSClient);
 
 impl_ws_client_trait!(CoinbaseProWSClient);
 
-struct CoinbaseProMessageHandler {}
+pub(crate) struct CoinbaseProMessageHandler {}
 struct CoinbaseProCommandTranslator {}
 
 impl MessageHandler for CoinbaseProMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if let Some(msg_type) = first_str_value(msg, "type") {
+            if !matches!(msg_type, "error" | "subscriptions" | "heartbeat") {
+                return MiscMessage::Normal;
+            }
+        }
+
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -188,4 +196,23 @@ mod tests {
             commands[0]
         );
     }
+
+    #[test]
+    fn test_handle_message() {
+        use crate::common::message_handler::{MessageHandler, MiscMessage};
+
+        let mut handler = super::CoinbaseProMessageHandler {};
+        assert!(matches!(
+            handler.handle_message(r#"{"type":"l2update","product_id":"BTC-USD","changes":[]}"#),
+            MiscMessage::Normal
+        ));
+        assert!(matches!(
+            handler.handle_message(r#"{"type":"subscriptions","channels":[]}"#),
+            MiscMessage::Other
+        ));
+        assert!(matches!(
+            handler.handle_message(r#"{"product_id":"BTC-USD","type":"heartbeat"}"#),
+            MiscMessage::Other
+        ));
+    }
 }
@@ -224,6 +224,10 @@ macro_rules! impl_ws_client_trait {
                 self.client.run().await;
             }
 
+            async fn run_with_callback(&self, callback: crate::FrameCallback) {
+                self.client.run_with_callback(callback).await;
+            }
+
             async fn close(&self) {
                 self.client.close().await;
             }
@@ -63,7 +63,7 @@ panic_l3_orderbook!(DeribitWSClient);
 
 impl_ws_client_trait!(DeribitWSClient);
 
-struct DeribitMessageHandler {}
+pub(crate) struct DeribitMessageHandler {}
 struct DeribitCommandTranslator {}
 
 impl DeribitCommandTranslator {
@@ -98,6 +98,11 @@ impl DeribitCommandTranslator {
 
 impl MessageHandler for DeribitMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if msg.starts_with(r#"{"jsonrpc":"2.0","method":"subscription","#) {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         if obj.contains_key("error") {
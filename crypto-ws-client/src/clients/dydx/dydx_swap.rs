@@ -9,6 +9,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_str_value,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -54,6 +55,11 @@ struct DydxCommandTranslator {}
 
 impl MessageHandler for DydxMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if matches!(first_str_value(msg, "type"), Some("channel_data" | "subscribed")) {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         match obj.get("type").unwrap().as_str().unwrap() {
@@ -9,6 +9,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_key_is,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -56,6 +57,11 @@ struct FtxCommandTranslator {}
 
 impl MessageHandler for FtxMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if first_key_is(msg, "channel") {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         let msg_type = obj.get("type").unwrap().as_str().unwrap();
 
@@ -15,8 +15,24 @@ pub(super) const EXCHANGE_NAME: &str = "gate";
 pub(super) struct GateMessageHandler<const MARKET_TYPE: char> {}
 pub(super) struct GateCommandTranslator<const MARKET_TYPE: char> {}
 
+// Fast path for data messages, only the fields before `result`, i.e., `time`,
+// `channel`, `event` and `error`, are scanned, and a message with a non-null
+// `error` goes to the slow path.
+fn is_data_message(msg: &str) -> bool {
+    let head = match msg.find(r#""result""#) {
+        Some(pos) => &msg[..pos],
+        None => return false,
+    };
+    (head.contains(r#""event":"update""#) || head.contains(r#""event":"all""#))
+        && (!head.contains(r#""error""#) || head.contains(r#""error":null"#))
+}
+
 impl<const MARKET_TYPE: char> MessageHandler for GateMessageHandler<MARKET_TYPE> {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        if is_data_message(msg) {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         // https://www.gate.io/docs/apiv4/ws/en/#server-response
@@ -186,6 +202,25 @@ impl<const MARKET_TYPE: char> CommandTranslator for GateCommandTranslator<MARKET
 mod tests {
     use crate::common::command_translator::CommandTranslator;
 
+    #[test]
+    fn test_is_data_message() {
+        assert!(super::is_data_message(
+            r#"{"time":1676000000,"channel":"spot.trades","event":"update","result":{"id":1,"currency_pair":"BTC_USDT"}}"#
+        ));
+        assert!(super::is_data_message(
+            r#"{"time":1676000000,"channel":"futures.order_book","event":"all","error":null,"result":{"contract":"BTC_USD"}}"#
+        ));
+        // errors are checked before the event
+        assert!(!super::is_data_message(
+            r#"{"time":1676000000,"channel":"futures.trades","event":"update","error":{"code":2,"message":"unknown contract"},"result":null}"#
+        ));
+        // the event must be in the header, not in the payload
+        assert!(!super::is_data_message(
+            r#"{"time":1676000000,"channel":"spot.trades","event":"subscribe","result":{"event":"update"}}"#
+        ));
+        assert!(!super::is_data_message(r#"{"time":1676000000,"channel":"spot.pong"}"#));
+    }
+
     #[test]
     fn test_spot() {
         let translator = super::GateCommandTranslator::<'S'> {};
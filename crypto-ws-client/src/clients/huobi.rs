@@ -9,9 +9,10 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_key_is,
         ws_client_internal::WSClientInternal,
     },
-    WSClient,
+    FrameCallback, WSClient,
 };
 
 pub(crate) const EXCHANGE_NAME: &str = "huobi";
@@ -181,12 +182,16 @@ impl<const URL: char> WSClient for HuobiWSClient<URL> {
         self.client.run().await;
     }
 
+    async fn run_with_callback(&self, callback: FrameCallback) {
+        self.client.run_with_callback(callback).await;
+    }
+
     async fn close(&self) {
         self.client.close().await;
     }
 }
 
-struct HuobiMessageHandler {}
+pub(crate) struct HuobiMessageHandler {}
 struct HuobiCommandTranslator {}
 
 impl HuobiCommandTranslator {
@@ -219,6 +224,11 @@ impl HuobiCommandTranslator {
 
 impl MessageHandler for HuobiMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if first_key_is(msg, "ch") {
+            return MiscMessage::Normal;
+        }
+
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -347,4 +357,27 @@ mod tests {
         assert_eq!(r#"{"sub":"market.btcusdt.trade.detail","id":"crypto-ws-client"}"#, commands[0]);
         assert_eq!(r#"{"sub":"market.btcusdt.bbo","id":"crypto-ws-client"}"#, commands[1]);
     }
+
+    #[test]
+    fn test_handle_message() {
+        use crate::common::message_handler::{MessageHandler, MiscMessage};
+
+        let mut handler = super::HuobiMessageHandler {};
+        assert!(matches!(
+            handler.handle_message(
+                r#"{"ch":"market.btcusdt.trade.detail","ts":1676000000000,"tick":{"data":[]}}"#
+            ),
+            MiscMessage::Normal
+        ));
+        assert!(matches!(
+            handler.handle_message(r#"{"ping":1676000000000}"#),
+            MiscMessage::WebSocket(_)
+        ));
+        assert!(matches!(
+            handler.handle_message(
+                r#"{"id":"9527","status":"ok","subbed":"market.btcusdt.trade.detail","ts":1}"#
+            ),
+            MiscMessage::Other
+        ));
+    }
 }
@@ -10,6 +10,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_str_value,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -68,6 +69,13 @@ impl KrakenCommandTranslator {
 
 impl MessageHandler for KrakenMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if let Some(feed) = first_str_value(msg, "feed") {
+            if feed != "heartbeat" && msg.contains(r#""product_id""#) {
+                return MiscMessage::Normal;
+            }
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
 
         if obj.contains_key("event") {
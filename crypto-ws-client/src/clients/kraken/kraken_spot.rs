@@ -57,6 +57,11 @@ struct KrakenCommandTranslator {}
 
 impl MessageHandler for KrakenMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // data messages are arrays
+        if msg.starts_with('[') {
+            return MiscMessage::Normal;
+        }
+
         let resp = serde_json::from_str::<Value>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
@@ -153,7 +158,7 @@ impl KrakenCommandTranslator {
     ) -> Vec<(Vec<String>, usize)> {
         let mut map = HashMap::<usize, Vec<String>>::new();
         for task in symbol_interval_list {
-            let v = map.entry(task.1).or_insert_with(Vec::new);
+            let v = map.entry(task.1).or_default();
             v.push(task.0.clone());
         }
         let mut result = Vec::new();
@@ -9,7 +9,10 @@ use reqwest::{header, Result};
 use serde_json::Value;
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::common::message_handler::{MessageHandler, MiscMessage};
+use crate::common::{
+    message_handler::{MessageHandler, MiscMessage},
+    utils::first_str_value,
+};
 
 pub(super) const EXCHANGE_NAME: &str = "kucoin";
 
@@ -105,6 +108,11 @@ pub(super) struct KucoinMessageHandler {}
 
 impl MessageHandler for KucoinMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if first_str_value(msg, "type") == Some("message") {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         let msg_type = obj.get("type").unwrap().as_str().unwrap();
         match msg_type {
@@ -10,6 +10,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_str_value,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -58,17 +59,24 @@ impl MessageHandler for MexcMessageHandler {
         if msg == "pong" {
             return MiscMessage::Pong;
         }
+        // fast path for data messages
+        match first_str_value(msg, "channel") {
+            Some("push.deal" | "push.depth" | "push.limit.depth" | "push.kline")
+                if msg.contains(r#""symbol""#) =>
+            {
+                return MiscMessage::Normal;
+            }
+            Some("push.overview") => return MiscMessage::Normal,
+            _ => (),
+        }
         if let Ok(obj) = serde_json::from_str::<HashMap<String, Value>>(msg) {
             if obj.contains_key("channel") && obj.contains_key("data") {
                 let channel = obj.get("channel").unwrap().as_str().unwrap();
                 match channel {
-                    "push.deal" | "push.depth" | "push.limit.depth" | "push.kline" => {
-                        if obj.contains_key("symbol") {
-                            MiscMessage::Normal
-                        } else {
-                            warn!("Received {} from {}", msg, EXCHANGE_NAME);
-                            MiscMessage::Other
-                        }
+                    "push.deal" | "push.depth" | "push.limit.depth" | "push.kline"
+                        if obj.contains_key("symbol") =>
+                    {
+                        MiscMessage::Normal
                     }
                     "push.overview" => MiscMessage::Normal,
                     _ => {
@@ -10,6 +10,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_str_value,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -57,6 +58,13 @@ struct MexcCommandTranslator {}
 
 impl MessageHandler for MexcMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages
+        if let Some(channel) = first_str_value(msg, "channel") {
+            if channel.starts_with("push.") && msg.contains(r#""symbol""#) {
+                return MiscMessage::Normal;
+            }
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         if obj.contains_key("channel") && obj.contains_key("data") && obj.contains_key("ts") {
             let channel = obj.get("channel").unwrap().as_str().unwrap();
@@ -22,3 +22,135 @@ pub(super) mod mexc;
 pub(super) mod okx;
 pub(super) mod zb;
 pub(super) mod zbg;
+
+use std::sync::mpsc::Sender;
+
+use crypto_market_type::MarketType;
+
+use self::{
+    binance::*, binance_option::*, bitfinex::*, bitget::*, bithumb::*, bitmex::*, bitstamp::*,
+    bitz::*, bybit::*, coinbase_pro::*, deribit::*, dydx::*, ftx::*, gate::*, huobi::*, kraken::*,
+    kucoin::*, mexc::*, okx::*, zb::*, zbg::*,
+};
+use crate::WSClient;
+
+/// Creates the websocket client of `exchange` and `market_type`.
+///
+/// `url` overrides the default websocket URL, an error is returned if the
+/// exchange or the market type is not supported.
+pub async fn create_ws_client(
+    exchange: &str,
+    market_type: MarketType,
+    tx: Sender<String>,
+    url: Option<&str>,
+) -> Result<Box<dyn WSClient + Send + Sync>, String> {
+    let ws_client: Box<dyn WSClient + Send + Sync> = match exchange {
+        "binance" => match market_type {
+            MarketType::Spot => Box::new(BinanceSpotWSClient::new(tx, url).await),
+            MarketType::InverseFuture | MarketType::InverseSwap => {
+                Box::new(BinanceInverseWSClient::new(tx, url).await)
+            }
+            MarketType::LinearFuture | MarketType::LinearSwap => {
+                Box::new(BinanceLinearWSClient::new(tx, url).await)
+            }
+            MarketType::EuropeanOption => Box::new(BinanceOptionWSClient::new(tx, url).await),
+            _ => return Err(format!("Binance does NOT have the {market_type} market type")),
+        },
+        "bitfinex" => Box::new(BitfinexWSClient::new(tx, url).await),
+        "bitget" => match market_type {
+            MarketType::Spot => Box::new(BitgetSpotWSClient::new(tx, url).await),
+            MarketType::InverseFuture | MarketType::InverseSwap | MarketType::LinearSwap => {
+                Box::new(BitgetSwapWSClient::new(tx, url).await)
+            }
+            _ => return Err(format!("Bitget does NOT have the {market_type} market type")),
+        },
+        "bithumb" => Box::new(BithumbWSClient::new(tx, url).await),
+        "bitmex" => Box::new(BitmexWSClient::new(tx, url).await),
+        "bitstamp" => Box::new(BitstampWSClient::new(tx, url).await),
+        "bitz" => match market_type {
+            MarketType::Spot => Box::new(BitzSpotWSClient::new(tx, url).await),
+            _ => return Err(format!("Bitz does NOT have the {market_type} market type")),
+        },
+        "bybit" => match market_type {
+            MarketType::InverseFuture | MarketType::InverseSwap => {
+                Box::new(BybitInverseWSClient::new(tx, url).await)
+            }
+            MarketType::LinearSwap => Box::new(BybitLinearSwapWSClient::new(tx, url).await),
+            _ => return Err(format!("Bybit does NOT have the {market_type} market type")),
+        },
+        "coinbase_pro" => Box::new(CoinbaseProWSClient::new(tx, url).await),
+        "deribit" => Box::new(DeribitWSClient::new(tx, url).await),
+        "dydx" => match market_type {
+            MarketType::LinearSwap => Box::new(DydxSwapWSClient::new(tx, url).await),
+            _ => return Err(format!("dYdX does NOT have the {market_type} market type")),
+        },
+        "ftx" => Box::new(FtxWSClient::new(tx, url).await),
+        "gate" => match market_type {
+            MarketType::Spot => Box::new(GateSpotWSClient::new(tx, url).await),
+            MarketType::InverseSwap => Box::new(GateInverseSwapWSClient::new(tx, url).await),
+            MarketType::LinearSwap => Box::new(GateLinearSwapWSClient::new(tx, url).await),
+            MarketType::InverseFuture => Box::new(GateInverseFutureWSClient::new(tx, url).await),
+            MarketType::LinearFuture => Box::new(GateLinearFutureWSClient::new(tx, url).await),
+            _ => return Err(format!("Gate does NOT have the {market_type} market type")),
+        },
+        "huobi" => match market_type {
+            MarketType::Spot => Box::new(HuobiSpotWSClient::new(tx, url).await),
+            MarketType::InverseFuture => Box::new(HuobiFutureWSClient::new(tx, url).await),
+            MarketType::LinearSwap => Box::new(HuobiLinearSwapWSClient::new(tx, url).await),
+            MarketType::InverseSwap => Box::new(HuobiInverseSwapWSClient::new(tx, url).await),
+            MarketType::EuropeanOption => Box::new(HuobiOptionWSClient::new(tx, url).await),
+            _ => return Err(format!("Huobi does NOT have the {market_type} market type")),
+        },
+        "kraken" => match market_type {
+            MarketType::Spot => Box::new(KrakenSpotWSClient::new(tx, url).await),
+            MarketType::InverseFuture | MarketType::InverseSwap => {
+                Box::new(KrakenFuturesWSClient::new(tx, url).await)
+            }
+            _ => return Err(format!("Kraken does NOT have the {market_type} market type")),
+        },
+        "kucoin" => match market_type {
+            MarketType::Spot => Box::new(KuCoinSpotWSClient::new(tx, url).await),
+            MarketType::InverseSwap | MarketType::LinearSwap | MarketType::InverseFuture => {
+                Box::new(KuCoinSwapWSClient::new(tx, url).await)
+            }
+            _ => return Err(format!("KuCoin does NOT have the {market_type} market type")),
+        },
+        "mexc" => match market_type {
+            MarketType::Spot => Box::new(MexcSpotWSClient::new(tx, url).await),
+            MarketType::LinearSwap | MarketType::InverseSwap => {
+                Box::new(MexcSwapWSClient::new(tx, url).await)
+            }
+            _ => return Err(format!("MEXC does NOT have the {market_type} market type")),
+        },
+        "okx" => Box::new(OkxWSClient::new(tx, url).await),
+        "zb" => match market_type {
+            MarketType::Spot => Box::new(ZbSpotWSClient::new(tx, url).await),
+            MarketType::LinearSwap => Box::new(ZbSwapWSClient::new(tx, url).await),
+            _ => return Err(format!("ZB does NOT have the {market_type} market type")),
+        },
+        "zbg" => match market_type {
+            MarketType::Spot => Box::new(ZbgSpotWSClient::new(tx, url).await),
+            MarketType::InverseSwap | MarketType::LinearSwap => {
+                Box::new(ZbgSwapWSClient::new(tx, url).await)
+            }
+            _ => return Err(format!("ZBG does NOT have the {market_type} market type")),
+        },
+        _ => return Err(format!("Unknown exchange {exchange}")),
+    };
+    Ok(ws_client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::create_ws_client;
+    use crypto_market_type::MarketType;
+
+    #[tokio::test]
+    async fn test_unsupported() {
+        let tx = std::sync::mpsc::channel().0;
+        let err = create_ws_client("binance", MarketType::QuantoSwap, tx.clone(), None).await.err();
+        assert_eq!(Some("Binance does NOT have the quanto_swap market type".to_string()), err);
+        let err = create_ws_client("unknown", MarketType::Spot, tx, None).await.err();
+        assert_eq!(Some("Unknown exchange unknown".to_string()), err);
+    }
+}
@@ -16,7 +16,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
-        utils::ensure_frame_size,
+        utils::{ensure_frame_size, first_key_is},
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -83,7 +83,7 @@ panic_l3_orderbook!(OkxWSClient);
 
 impl_ws_client_trait!(OkxWSClient);
 
-struct OkxMessageHandler {}
+pub(crate) struct OkxMessageHandler {}
 struct OkxCommandTranslator {}
 
 impl OkxCommandTranslator {
@@ -134,6 +134,10 @@ impl MessageHandler for OkxMessageHandler {
         if msg == "pong" {
             return MiscMessage::Pong;
         }
+        // fast path for data messages
+        if first_key_is(msg, "arg") {
+            return MiscMessage::Normal;
+        }
         let resp = serde_json::from_str::<HashMap<String, Value>>(msg);
         if resp.is_err() {
             error!("{} is not a JSON string, {}", msg, EXCHANGE_NAME);
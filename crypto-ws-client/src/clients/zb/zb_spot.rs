@@ -58,6 +58,11 @@ struct ZbCommandTranslator {}
 
 impl MessageHandler for ZbMessageHandler {
     fn handle_message(&mut self, msg: &str) -> MiscMessage {
+        // fast path for data messages, which have neither `code` nor `pong`
+        if msg.starts_with('{') && !msg.contains(r#""code""#) && !msg.contains(r#""pong""#) {
+            return MiscMessage::Normal;
+        }
+
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         let channel = obj["channel"].as_str().unwrap();
 
@@ -12,6 +12,7 @@ use crate::{
     common::{
         command_translator::CommandTranslator,
         message_handler::{MessageHandler, MiscMessage},
+        utils::first_key_is,
         ws_client_internal::WSClientInternal,
     },
     WSClient,
@@ -85,6 +86,10 @@ impl MessageHandler for ZbMessageHandler {
             error!("Received {} from {}", msg, EXCHANGE_NAME);
             return MiscMessage::Other;
         }
+        // fast path for data messages
+        if first_key_is(msg, "channel") && msg.contains(r#""data""#) {
+            return MiscMessage::Normal;
+        }
         let obj = serde_json::from_str::<HashMap<String, Value>>(msg).unwrap();
         if obj.contains_key("channel") && obj.contains_key("data") {
             MiscMessage::Normal
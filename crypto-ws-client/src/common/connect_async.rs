@@ -1,5 +1,5 @@
 use fast_socks5::client::{Config, Socks5Stream};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{stream::BoxStream, SinkExt, StreamExt};
 use governor::{Quota, RateLimiter};
 use log::*;
 use nonzero_ext::*;
@@ -7,24 +7,29 @@ use reqwest::Url;
 use std::{env, num::NonZeroU32};
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    sync::mpsc::{Receiver, Sender},
+    sync::mpsc::Sender,
 };
 use tokio_tungstenite::{
     tungstenite::{Error, Message},
     MaybeTlsStream, WebSocketStream,
 };
 
-/// Wraps a websocket client inside an event loop, returns a message_rx to
-/// receive messages and a command_tx to send commands to the websocket server.
+/// The read half of a websocket connection.
+pub(crate) type MessageStream = BoxStream<'static, Result<Message, Error>>;
+
+/// Connects to a websocket server, returns the read half to receive messages
+/// and a command_tx to send commands to the websocket server.
 ///
-/// To close the websocket connection, send a `Message::Close` message to the
-/// command_tx.
+/// Messages are read directly from the returned stream, only uplink commands
+/// go through a background task. To close the websocket connection, send a
+/// `Message::Close` message to the command_tx, after which
+/// `command_tx.closed()` resolves.
 ///
 /// `limit`, max number of uplink messsages, for example, 100 per 10 seconds
 pub async fn connect_async(
     url: &str,
     uplink_limit: Option<(NonZeroU32, std::time::Duration)>,
-) -> Result<(Receiver<Message>, Sender<Message>), Error> {
+) -> Result<(MessageStream, Sender<Message>), Error> {
     if let Ok(proxy_env) = env::var("https_proxy").or_else(|_| env::var("http_proxy")) {
         let proxy_url = Url::parse(&proxy_env).unwrap();
         let proxy_scheme = proxy_url.scheme().to_lowercase();
@@ -59,69 +64,44 @@ pub async fn connect_async(
 async fn connect_async_internal<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
     ws_stream: WebSocketStream<MaybeTlsStream<S>>,
     uplink_limit: Option<(NonZeroU32, std::time::Duration)>,
-) -> Result<(Receiver<Message>, Sender<Message>), Error> {
+) -> Result<(MessageStream, Sender<Message>), Error> {
     let (command_tx, mut command_rx) = tokio::sync::mpsc::channel::<Message>(1);
-    let (message_tx, message_rx) = tokio::sync::mpsc::channel::<Message>(32);
 
-    let (mut write, mut read) = ws_stream.split();
+    let (mut write, read) = ws_stream.split();
 
     let limiter = if let Some((max_burst, duration)) = uplink_limit {
         let quota = Quota::with_period(duration).unwrap().allow_burst(max_burst);
         RateLimiter::direct(quota)
     } else {
-        RateLimiter::direct(Quota::per_second(nonzero!(u32::max_value())))
+        RateLimiter::direct(Quota::per_second(nonzero!(u32::MAX)))
     };
 
     tokio::task::spawn(async move {
-        loop {
-            tokio::select! {
-              command = command_rx.recv() => {
-                match command {
-                  Some(command) => {
-                    match command {
-                      Message::Close(resp) => {
-                        match resp {
-                            Some(frame) => {
-                                warn!(
-                                    "Received a CloseFrame: code: {}, reason: {}",
-                                    frame.code, frame.reason
-                                );
-                            }
-                            None => warn!("Received an empty close message"),
-                        }
-                        break; // close the connection and break the loop
-                      }
-                      _ => {
-                        limiter.until_ready().await;
-                        if let Err(err) =write.send(command).await {
-                          error!("Failed to send, error: {}", err);
+        while let Some(command) = command_rx.recv().await {
+            match command {
+                Message::Close(resp) => {
+                    match resp {
+                        Some(frame) => {
+                            warn!(
+                                "Received a CloseFrame: code: {}, reason: {}",
+                                frame.code, frame.reason
+                            );
                         }
-                      }
+                        None => warn!("Received an empty close message"),
                     }
-                  }
-                  None => {
-                    debug!("command_rx closed");
-                    break;
-                  }
-                }
-              }
-              msg = read.next() => match msg {
-                Some(Ok(msg)) => {
-                  let _= message_tx.send(msg).await;
+                    break; // close the connection and break the loop
                 }
-                Some(Err(err)) => {
-                  error!("Failed to read, error: {}", err);
-                  break;
-                }
-                None => {
-                  debug!("message_tx closed");
-                  break;
+                _ => {
+                    limiter.until_ready().await;
+                    if let Err(err) = write.send(command).await {
+                        error!("Failed to send, error: {}", err);
+                    }
                 }
-              }
-            };
+            }
         }
+        debug!("command_rx closed");
         _ = write.send(Message::Close(None)).await;
     });
 
-    Ok((message_rx, command_tx))
+    Ok((read.boxed(), command_tx))
 }
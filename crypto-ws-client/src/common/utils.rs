@@ -40,3 +40,61 @@ pub(crate) fn ensure_frame_size(
 pub(crate) fn topic_to_raw_channel(topic: &(String, String)) -> String {
     topic.0.replace("SYMBOL", topic.1.as_str())
 }
+
+// Returns the first key of a JSON object and the text after the colon,
+// without parsing the whole message.
+fn first_field(msg: &str) -> Option<(&str, &str)> {
+    let rest = msg.strip_prefix('{')?.trim_start().strip_prefix('"')?;
+    let end = rest.find(['"', '\\'])?;
+    if rest.as_bytes()[end] == b'\\' {
+        return None; // escaped keys are not supported
+    }
+    let value = rest[end + 1..].trim_start().strip_prefix(':')?.trim_start();
+    Some((&rest[..end], value))
+}
+
+/// Returns true if `msg` is a JSON object and its first key is `key`.
+///
+/// This is a cheap check used to recognize data messages without parsing
+/// them.
+pub(crate) fn first_key_is(msg: &str, key: &str) -> bool {
+    first_field(msg).map(|(k, _)| k == key).unwrap_or(false)
+}
+
+/// Returns the value of the first field of a JSON object if its key is
+/// `key` and its value is a string without escape characters.
+pub(crate) fn first_str_value<'a>(msg: &'a str, key: &str) -> Option<&'a str> {
+    let (k, value) = first_field(msg)?;
+    if k != key {
+        return None;
+    }
+    let value = value.strip_prefix('"')?;
+    let end = value.find(['"', '\\'])?;
+    if value.as_bytes()[end] == b'\\' { None } else { Some(&value[..end]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_key_is, first_str_value};
+
+    #[test]
+    fn test_first_key_is() {
+        assert!(first_key_is(r#"{"stream":"btcusdt@aggTrade","data":{}}"#, "stream"));
+        assert!(first_key_is(r#"{ "data" : {"id": 1}, "event": "trade"}"#, "data"));
+        assert!(!first_key_is(r#"{"result":null,"id":9527}"#, "stream"));
+        assert!(!first_key_is(r#"{"streams":[]}"#, "stream"));
+        assert!(!first_key_is(r#"[1,"hb"]"#, "stream"));
+        assert!(!first_key_is("pong", "stream"));
+        assert!(!first_key_is("", "stream"));
+    }
+
+    #[test]
+    fn test_first_str_value() {
+        assert_eq!(Some("message"), first_str_value(r#"{"type":"message","data":{}}"#, "type"));
+        assert_eq!(Some("l2update"), first_str_value(r#"{"type": "l2update"}"#, "type"));
+        assert_eq!(None, first_str_value(r#"{"topic":"trade","type":"message"}"#, "type"));
+        assert_eq!(None, first_str_value(r#"{"type":1}"#, "type"));
+        assert_eq!(None, first_str_value(r#"{"type":"a\"b"}"#, "type"));
+        assert_eq!(None, first_str_value(r#"{"type":"#, "type"));
+    }
+}
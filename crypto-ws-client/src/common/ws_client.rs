@@ -1,5 +1,22 @@
 use async_trait::async_trait;
 
+/// A message received from the websocket server.
+///
+/// Frames are passed by reference to callbacks, see
+/// [`WSClient::run_with_callback`].
+#[derive(Clone, Copy, Debug)]
+pub struct Frame<'a> {
+    /// Exchange name.
+    pub exchange: &'static str,
+    /// The text payload, decompressed and trimmed.
+    pub payload: &'a str,
+    /// Unix timestamp in milliseconds at which the frame was received.
+    pub received_at: u64,
+}
+
+/// A callback invoked on every frame.
+pub type FrameCallback = Box<dyn FnMut(&Frame) + Send>;
+
 /// The public interface of every WebSocket client.
 #[async_trait]
 pub trait WSClient {
@@ -136,6 +153,15 @@ pub trait WSClient {
     /// Starts the infinite event loop.
     async fn run(&self);
 
+    /// Starts the infinite event loop and invokes `callback` inline on every
+    /// frame.
+    ///
+    /// This is a low-latency alternative to `run()`, messages are NOT sent to
+    /// the channel passed to `new()`, which avoids a channel hop and a string
+    /// copy per message. The callback runs on the read task, so it should
+    /// return quickly, otherwise it will delay subsequent messages.
+    async fn run_with_callback(&self, callback: FrameCallback);
+
     /// Close the connection and break the loop in Run().
     async fn close(&self);
 }
@@ -5,15 +5,41 @@ use std::{
         atomic::{AtomicIsize, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use flate2::read::{DeflateDecoder, GzDecoder};
+use futures_util::StreamExt;
 use log::*;
 use reqwest::StatusCode;
 use tokio_tungstenite::tungstenite::{Error, Message};
 
-use crate::common::message_handler::{MessageHandler, MiscMessage};
+use crate::common::{
+    connect_async::MessageStream,
+    message_handler::{MessageHandler, MiscMessage},
+    ws_client::{Frame, FrameCallback},
+};
+
+// Where run() delivers messages to.
+enum Sink {
+    Channel(std::sync::mpsc::Sender<String>),
+    Callback(FrameCallback),
+}
+
+impl Sink {
+    // Returns false if the receiver has been dropped.
+    fn emit(&mut self, exchange: &'static str, payload: &str) -> bool {
+        match self {
+            Sink::Channel(tx) => tx.send(payload.to_string()).is_ok(),
+            Sink::Callback(callback) => {
+                let received_at =
+                    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+                callback(&Frame { exchange, payload, received_at });
+                true
+            }
+        }
+    }
+}
 
 // `WSClientInternal` should be Sync + Send so that it can be put into Arc
 // directly.
@@ -23,11 +49,7 @@ pub(crate) struct WSClientInternal<H: MessageHandler> {
     // pass parameters to run()
     #[allow(clippy::type_complexity)]
     params_rx: std::sync::Mutex<
-        tokio::sync::oneshot::Receiver<(
-            H,
-            tokio::sync::mpsc::Receiver<Message>,
-            std::sync::mpsc::Sender<String>,
-        )>,
+        tokio::sync::oneshot::Receiver<(H, MessageStream, std::sync::mpsc::Sender<String>)>,
     >,
     command_tx: tokio::sync::mpsc::Sender<Message>,
 }
@@ -41,15 +63,12 @@ impl<H: MessageHandler> WSClientInternal<H> {
         tx: std::sync::mpsc::Sender<String>,
     ) -> Self {
         // A channel to send parameters to run()
-        let (params_tx, params_rx) = tokio::sync::oneshot::channel::<(
-            H,
-            tokio::sync::mpsc::Receiver<Message>,
-            std::sync::mpsc::Sender<String>,
-        )>();
+        let (params_tx, params_rx) =
+            tokio::sync::oneshot::channel::<(H, MessageStream, std::sync::mpsc::Sender<String>)>();
 
         match super::connect_async::connect_async(url, uplink_limit).await {
-            Ok((message_rx, command_tx)) => {
-                let _ = params_tx.send((handler, message_rx, tx));
+            Ok((message_stream, command_tx)) => {
+                let _ = params_tx.send((handler, message_stream, tx));
 
                 WSClientInternal {
                     exchange,
@@ -89,17 +108,37 @@ impl<H: MessageHandler> WSClientInternal<H> {
     }
 
     pub async fn run(&self) {
-        let (mut handler, mut message_rx, tx) = {
+        self.run_internal(None).await;
+    }
+
+    /// Same as run(), but invokes `callback` inline instead of sending to the
+    /// channel.
+    pub async fn run_with_callback(&self, callback: FrameCallback) {
+        self.run_internal(Some(callback)).await;
+    }
+
+    async fn run_internal(&self, callback: Option<FrameCallback>) {
+        let (mut handler, mut message_stream, tx) = {
             let mut guard = self.params_rx.lock().unwrap();
             guard.try_recv().unwrap()
         };
+        let mut sink = match callback {
+            Some(callback) => {
+                drop(tx);
+                Sink::Callback(callback)
+            }
+            None => Sink::Channel(tx),
+        };
 
         let num_unanswered_ping = Arc::new(AtomicIsize::new(0)); // for debug only
-        if let Some((msg, interval)) = handler.get_ping_msg_and_interval() {
+
+        // The ping task holds a command_tx, it is aborted when this function
+        // returns or is dropped, so that it doesn't keep the connection open
+        let _ping_task = handler.get_ping_msg_and_interval().map(|(msg, interval)| {
             // send heartbeat periodically
             let command_tx_clone = self.command_tx.clone();
             let num_unanswered_ping_clone = num_unanswered_ping.clone();
-            tokio::task::spawn(async move {
+            AbortOnDrop(tokio::task::spawn(async move {
                 let mut timer = {
                     let duration = Duration::from_secs(interval / 2 + 1);
                     tokio::time::interval(duration)
@@ -113,10 +152,27 @@ impl<H: MessageHandler> WSClientInternal<H> {
                         num_unanswered_ping_clone.fetch_add(1, Ordering::SeqCst);
                     }
                 }
-            });
-        }
+            }))
+        });
 
-        while let Some(msg) = message_rx.recv().await {
+        // Frames are read from the socket on this task and handed to the sink
+        // directly, without an intermediate channel.
+        loop {
+            let msg = tokio::select! {
+                msg = message_stream.next() => match msg {
+                    Some(Ok(msg)) => msg,
+                    Some(Err(err)) => {
+                        error!("Failed to read, error: {}", err);
+                        break;
+                    }
+                    None => {
+                        debug!("The websocket stream of {} has ended", self.url);
+                        break;
+                    }
+                },
+                // close() has been called
+                _ = self.command_tx.closed() => break,
+            };
             let txt = match msg {
                 Message::Text(txt) => Some(txt),
                 Message::Binary(binary) => {
@@ -187,15 +243,15 @@ impl<H: MessageHandler> WSClientInternal<H> {
             };
 
             if let Some(txt) = txt {
-                let txt = txt.as_str().trim().to_string();
-                match handler.handle_message(&txt) {
+                let txt = txt.as_str().trim();
+                match handler.handle_message(txt) {
                     MiscMessage::Normal => {
                         // the receiver might get dropped earlier than this loop
-                        if tx.send(txt).is_err() {
+                        if !sink.emit(self.exchange, txt) {
                             break; // break the loop if there is no receiver
                         }
                     }
-                    MiscMessage::Mutated(txt) => _ = tx.send(txt),
+                    MiscMessage::Mutated(txt) => _ = sink.emit(self.exchange, &txt),
                     MiscMessage::WebSocket(ws_msg) => _ = self.command_tx.send(ws_msg).await,
                     MiscMessage::Pong => {
                         num_unanswered_ping.store(0, Ordering::Release);
@@ -219,3 +275,12 @@ impl<H: MessageHandler> WSClientInternal<H> {
         _ = self.command_tx.send(Message::Close(None)).await;
     }
 }
+
+// Aborts a task when dropped.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
@@ -0,0 +1,82 @@
+//! Frames recorded by the `crypto-ws-client` binary, for offline tests.
+//!
+//! A fixture file is in JSON lines, each line is an object with `exchange`,
+//! `received_at` and `payload` fields.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::Frame;
+
+/// An owned copy of a `Frame`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct RecordedFrame {
+    pub exchange: String,
+    pub payload: String,
+    pub received_at: u64,
+}
+
+impl From<&Frame<'_>> for RecordedFrame {
+    fn from(frame: &Frame) -> Self {
+        RecordedFrame {
+            exchange: frame.exchange.to_string(),
+            payload: frame.payload.to_string(),
+            received_at: frame.received_at,
+        }
+    }
+}
+
+/// Serializes a frame into one line of a fixture file, without the trailing
+/// newline.
+pub fn to_fixture_line(frame: &Frame) -> String {
+    serde_json::json!({
+        "exchange": frame.exchange,
+        "received_at": frame.received_at,
+        "payload": frame.payload,
+    })
+    .to_string()
+}
+
+/// Reads all frames of a fixture file, empty lines are skipped.
+pub fn read_fixture(path: impl AsRef<Path>) -> io::Result<Vec<RecordedFrame>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        frames.push(serde_json::from_str(&line)?);
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture() {
+        let frames = [
+            Frame {
+                exchange: "binance",
+                payload: r#"{"stream":"btcusdt@aggTrade"}"#,
+                received_at: 1,
+            },
+            Frame { exchange: "binance", payload: "pong\n", received_at: 2 },
+        ];
+        let text: String = frames.iter().map(|frame| to_fixture_line(frame) + "\n").collect();
+        let path = std::env::temp_dir().join(format!("fixture_{}.jsonl", std::process::id()));
+        std::fs::write(&path, text + "\n").unwrap();
+        let recorded = read_fixture(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected: Vec<RecordedFrame> = frames.iter().map(RecordedFrame::from).collect();
+        assert_eq!(expected, recorded);
+    }
+}
@@ -45,6 +45,18 @@
 //! * `unsubscribe(&self, topics: &[(String, String)])`
 //! * `send(&self, commands: &[String])`
 //!
+//! ## Callback API
+//!
+//! Latency-sensitive users can call `run_with_callback(&self, callback)`
+//! instead of `run()`, the callback is invoked inline on every `Frame`
+//! without going through the channel.
+//!
+//! ## Command Line
+//!
+//! The `crypto-ws-client` binary subscribes to topics or sends raw commands,
+//! and prints frames with timestamps, `--record` saves them to a fixture
+//! file, which can be read back by `fixture::read_fixture()` in offline tests.
+//!
 //! ## OrderBook Data Categories
 //!
 //! Each orderbook has three properties: `aggregation`, `frequency` and `depth`.
@@ -61,11 +73,16 @@
 
 mod clients;
 mod common;
+pub mod fixture;
+
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
 
-pub use common::ws_client::WSClient;
+pub use common::ws_client::{Frame, FrameCallback, WSClient};
 
 pub use clients::{
     binance::*, binance_option::*, bitfinex::*, bitget::*, bithumb::*, bitmex::*, bitstamp::*,
-    bitz::*, bybit::*, coinbase_pro::*, deribit::*, dydx::*, ftx::*, gate::*, huobi::*, kraken::*,
-    kucoin::*, mexc::*, okx::*, zb::*, zbg::*,
+    bitz::*, bybit::*, coinbase_pro::*, create_ws_client, deribit::*, dydx::*, ftx::*, gate::*,
+    huobi::*, kraken::*, kucoin::*, mexc::*, okx::*, zb::*, zbg::*,
 };
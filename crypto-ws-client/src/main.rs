@@ -0,0 +1,118 @@
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use clap::Parser;
+use crypto_market_type::MarketType;
+use crypto_ws_client::{create_ws_client, fixture::to_fixture_line, Frame};
+use tokio::sync::Notify;
+
+/// Connect to a websocket client and print frames, for debugging.
+///
+/// Each frame is printed as the receiving timestamp in milliseconds, a tab,
+/// and the payload.
+#[derive(Parser)]
+#[command(name = "crypto-ws-client", version)]
+struct Cli {
+    /// Exchange name, e.g., binance
+    exchange: String,
+    /// Market type, e.g., spot, linear_swap
+    #[arg(value_parser = MarketType::from_str)]
+    market_type: MarketType,
+    /// Topic to subscribe, in CHANNEL:SYMBOL format, e.g., aggTrade:BTCUSDT
+    #[arg(long = "topic", value_parser = parse_topic)]
+    topics: Vec<(String, String)>,
+    /// Raw command sent as is, e.g.,
+    /// '{"op":"subscribe","args":[{"channel":"trades","instId":"BTC-USDT"}]}'
+    #[arg(long = "command")]
+    commands: Vec<String>,
+    /// Append frames to this fixture file in JSON lines
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// Exit after receiving this many frames
+    #[arg(long)]
+    count: Option<u64>,
+    /// Exit after this many seconds
+    #[arg(long)]
+    duration: Option<u64>,
+    /// Websocket URL, overrides the default URL of the exchange
+    #[arg(long)]
+    url: Option<String>,
+}
+
+fn parse_topic(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((channel, symbol)) if !channel.is_empty() && !symbol.is_empty() => {
+            Ok((channel.to_string(), symbol.to_string()))
+        }
+        _ => Err(format!("{s} is not in CHANNEL:SYMBOL format")),
+    }
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{err}");
+    std::process::exit(1);
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if cli.topics.is_empty() && cli.commands.is_empty() {
+        exit_with_error("Nothing to subscribe, specify --topic or --command");
+    }
+    let mut recorder = cli.record.as_ref().map(|path| {
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|err| exit_with_error(&format!("{}: {err}", path.display())));
+        LineWriter::new(file)
+    });
+
+    // frames go to the callback, the channel is never used
+    let (tx, _rx) = std::sync::mpsc::channel();
+    let ws_client = create_ws_client(&cli.exchange, cli.market_type, tx, cli.url.as_deref())
+        .await
+        .unwrap_or_else(|err| exit_with_error(&err));
+    if !cli.topics.is_empty() {
+        ws_client.subscribe(&cli.topics).await;
+    }
+    if !cli.commands.is_empty() {
+        ws_client.send(&cli.commands).await;
+    }
+
+    let done = Arc::new(Notify::new());
+    let callback = {
+        let done = done.clone();
+        let count = cli.count;
+        let mut received = 0;
+        Box::new(move |frame: &Frame| {
+            // frames may arrive in a batch before the loop is stopped
+            if count.is_some_and(|count| received >= count) {
+                return;
+            }
+            println!("{}\t{}", frame.received_at, frame.payload);
+            if let Some(recorder) = recorder.as_mut() {
+                if let Err(err) = writeln!(recorder, "{}", to_fixture_line(frame)) {
+                    exit_with_error(&format!("Failed to record, {err}"));
+                }
+            }
+            received += 1;
+            if count.is_some_and(|count| received >= count) {
+                done.notify_one();
+            }
+        })
+    };
+    let duration = cli.duration.map(Duration::from_secs).unwrap_or(Duration::MAX);
+    tokio::select! {
+        _ = ws_client.run_with_callback(callback) => {}
+        _ = done.notified() => {}
+        _ = tokio::time::sleep(duration) => {}
+    }
+    ws_client.close().await;
+}
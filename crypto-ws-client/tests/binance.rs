@@ -37,6 +37,29 @@ mod binance_spot {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn run_with_callback() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        tokio::task::spawn(async move {
+            let ws_client = BinanceSpotWSClient::new(std::sync::mpsc::channel().0, None).await;
+            ws_client.subscribe_trade(&["BTCUSDT".to_string()]).await;
+            let callback = Box::new(move |frame: &crypto_ws_client::Frame| {
+                assert_eq!("binance", frame.exchange);
+                _ = tx.send(frame.payload.to_string());
+            });
+            // run for 60 seconds at most
+            let _ = tokio::time::timeout(
+                std::time::Duration::from_secs(60),
+                ws_client.run_with_callback(callback),
+            )
+            .await;
+            ws_client.close().await;
+        });
+
+        let msg = rx.recv().unwrap();
+        assert!(msg.contains("aggTrade"));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn subscribe_trade() {
         gen_test_code!(